@@ -0,0 +1,484 @@
+// Actions module - applies actions the AI detected, journaling each so it can be undone
+use serde_json::{json, Value};
+use crate::db::Database;
+use crate::journal;
+use crate::targets;
+use crate::models::{
+    parse_date, ActionData, ActionTrigger, AiCreateMode, DailyLog, DetectedAction, InboxItem, InboxItemType, LogCategory,
+    Milestone, MilestoneSource, MilestoneStatus, ProjectStatus, Todo, TodoPriority, TodoStatus, UndoOperation,
+    VersionSettings,
+};
+
+/// Apply a confirmed action. The result is `{ type, data, journal_id }`, where `journal_id`
+/// undoes the change with `undo_action` (null when there was nothing to journal).
+pub fn execute(
+    db: &Database,
+    action: DetectedAction,
+    project_id: Option<String>,
+    trigger: ActionTrigger,
+) -> Result<Value, String> {
+    let record = |action_type: &str, description: &str, project_id: Option<&str>, undo: UndoOperation| {
+        journal::record(db, trigger, action_type, description, project_id, undo)
+    };
+
+    match action.data {
+        ActionData::Todo { title, priority, due_date } => {
+            let mut todo = Todo::new(title.clone(), project_id);
+            if let Some(p) = priority {
+                todo.priority = match p.as_str() {
+                    "low" => TodoPriority::Low,
+                    "high" => TodoPriority::High,
+                    "urgent" => TodoPriority::Urgent,
+                    _ => TodoPriority::Medium,
+                };
+            }
+            todo.due_date = due_date;
+
+            db.create_todo(&todo).map_err(|e| e.to_string())?;
+            let journal_id = record("todo_created", &title, todo.project_id.as_deref(), UndoOperation::DeleteTodo {
+                id: todo.id.clone(),
+            });
+
+            Ok(json!({
+                "type": "todo_created",
+                "data": {
+                    "id": todo.id,
+                    "title": title,
+                    "priority": format!("{:?}", todo.priority).to_lowercase()
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::Progress { summary, category, date } => {
+            let project_id = project_id.ok_or("Project ID required for logging progress")?;
+            let date = date.unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+            let log_category = match category.as_str() {
+                "feature" => LogCategory::Feature,
+                "bugfix" => LogCategory::Bugfix,
+                "refactor" => LogCategory::Refactor,
+                "ui" => LogCategory::Ui,
+                "docs" => LogCategory::Docs,
+                "test" => LogCategory::Test,
+                "chore" => LogCategory::Chore,
+                _ => LogCategory::Other,
+            };
+
+            let log = DailyLog {
+                id: uuid::Uuid::new_v4().to_string(),
+                project_id: project_id.clone(),
+                date: date.clone(),
+                summary: summary.clone(),
+                category: log_category,
+                files_changed: vec![],
+                ai_classification: Some(category.clone()),
+                user_override: None,
+                created_at: chrono::Utc::now(),
+                prompt_version: None,
+                summary_by_user: true,
+            };
+
+            // The day may already have a log, which this rewrites
+            let previous = db.get_daily_log_for_date(&project_id, &date).map_err(|e| e.to_string())?;
+            let log = db.upsert_daily_log(&log).map_err(|e| e.to_string())?;
+            let undo = match previous {
+                Some(previous) => UndoOperation::RestoreDailyLog { log: previous },
+                None => UndoOperation::DeleteDailyLog { id: log.id.clone() },
+            };
+            let journal_id = record("progress_logged", &summary, Some(&project_id), undo);
+
+            Ok(json!({
+                "type": "progress_logged",
+                "data": {
+                    "id": log.id,
+                    "summary": summary,
+                    "date": date
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::Inbox { question, item_type } => {
+            let inbox_type = match item_type.as_str() {
+                "todo_followup" => InboxItemType::TodoFollowup,
+                "planning" => InboxItemType::Planning,
+                _ => InboxItemType::TodoFollowup,
+            };
+
+            let item = InboxItem::new(inbox_type, question.clone(), project_id);
+            db.create_inbox_item(&item).map_err(|e| e.to_string())?;
+            let journal_id = record("inbox_created", &question, item.project_id.as_deref(), UndoOperation::DeleteInboxItem {
+                id: item.id.clone(),
+            });
+
+            Ok(json!({
+                "type": "inbox_created",
+                "data": {
+                    "id": item.id,
+                    "question": question
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::Milestone { title, description, version, git_tag } => {
+            let project_id = project_id.ok_or("Project ID required for creating milestone")?;
+
+            let milestone = Milestone {
+                id: uuid::Uuid::new_v4().to_string(),
+                project_id: project_id.clone(),
+                title: title.clone(),
+                description,
+                version: version.clone(),
+                git_tag,
+                status: MilestoneStatus::Planned,
+                source: MilestoneSource::Ai,
+                target_date: None,
+                completed_at: None,
+                created_at: chrono::Utc::now(),
+            };
+
+            db.create_milestone(&milestone).map_err(|e| e.to_string())?;
+            let journal_id = record("milestone_created", &title, Some(&project_id), UndoOperation::DeleteMilestone {
+                id: milestone.id.clone(),
+            });
+
+            Ok(json!({
+                "type": "milestone_created",
+                "data": {
+                    "id": milestone.id,
+                    "title": title,
+                    "version": version
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::CarryOverTodo { todo_id, title, due_date } => {
+            let previous = targets::resolve_todo(db, Some(&todo_id), &title)?;
+            db.update_todo_due_date(&todo_id, due_date.as_deref()).map_err(|e| e.to_string())?;
+            let todo_project = previous.project_id.clone();
+            let journal_id = record("todo_updated", &title, todo_project.as_deref(), UndoOperation::RestoreTodo {
+                todo: previous,
+            });
+
+            Ok(json!({
+                "type": "todo_updated",
+                "data": {
+                    "id": todo_id,
+                    "title": title,
+                    "due_date": due_date
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::UpdateTodo { todo_id, title, status, priority, due_date, column } => {
+            let status = status.as_deref().map(parse_todo_status).transpose()?;
+            let priority = priority.as_deref().map(parse_priority).transpose()?;
+            let due_date = due_date.as_deref().map(check_date).transpose()?;
+            if let Some(c) = column.as_deref() {
+                if !TODO_COLUMNS.contains(&c) {
+                    return Err(format!("Invalid column: {}", c));
+                }
+            }
+
+            let previous = targets::resolve_todo(db, todo_id.as_deref(), &title)?;
+            let updated = apply_todo_update(&previous, status, priority, due_date, column.as_deref());
+            db.update_todo(&updated).map_err(|e| e.to_string())?;
+            let action_type = if updated.status == TodoStatus::Completed && previous.status != TodoStatus::Completed {
+                "todo_completed"
+            } else {
+                "todo_updated"
+            };
+            let journal_id = record(action_type, &updated.title, updated.project_id.as_deref(), UndoOperation::RestoreTodo {
+                todo: previous,
+            });
+
+            Ok(json!({
+                "type": action_type,
+                "data": {
+                    "id": updated.id,
+                    "title": updated.title,
+                    "status": updated.status.as_str(),
+                    "priority": format!("{:?}", updated.priority).to_lowercase(),
+                    "due_date": updated.due_date,
+                    "column": updated.column
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::DeleteTodo { todo_id, title } => {
+            let todo = targets::resolve_todo(db, todo_id.as_deref(), &title)?;
+            db.delete_todo(&todo.id).map_err(|e| e.to_string())?;
+            let todo_project = todo.project_id.clone();
+            let (id, title) = (todo.id.clone(), todo.title.clone());
+            let journal_id = record("todo_deleted", &title, todo_project.as_deref(), UndoOperation::RecreateTodo {
+                todo,
+            });
+
+            Ok(json!({
+                "type": "todo_deleted",
+                "data": {
+                    "id": id,
+                    "title": title
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::UpdateMilestone { milestone_id, title, status, target_date } => {
+            let status = match status.as_deref() {
+                None => None,
+                Some("planned") => Some(MilestoneStatus::Planned),
+                Some("in_progress") => Some(MilestoneStatus::InProgress),
+                Some("completed") => Some(MilestoneStatus::Completed),
+                Some("cancelled") => Some(MilestoneStatus::Cancelled),
+                Some(s) => return Err(format!("Invalid status: {}", s)),
+            };
+            let target_date = target_date.as_deref().map(check_date).transpose()?;
+
+            let previous = targets::resolve_milestone(db, milestone_id.as_deref(), &title, project_id.as_deref())?;
+            let mut milestone = previous.clone();
+            if let Some(status) = status {
+                milestone.completed_at = match status {
+                    MilestoneStatus::Completed => previous.completed_at.or_else(|| Some(chrono::Utc::now())),
+                    _ => None,
+                };
+                milestone.status = status;
+            }
+            if target_date.is_some() {
+                milestone.target_date = target_date;
+            }
+
+            db.update_milestone(&milestone).map_err(|e| e.to_string())?;
+            let journal_id = record("milestone_updated", &milestone.title, Some(&milestone.project_id), UndoOperation::RestoreMilestone {
+                milestone: previous,
+            });
+
+            Ok(json!({
+                "type": "milestone_updated",
+                "data": {
+                    "id": milestone.id,
+                    "title": milestone.title,
+                    "status": milestone.status.as_str(),
+                    "target_date": milestone.target_date
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::UpdateProject { project_id: target, name, status } => {
+            let project_status = match status.as_str() {
+                "active" => ProjectStatus::Active,
+                "paused" => ProjectStatus::Paused,
+                "archived" => ProjectStatus::Archived,
+                s => return Err(format!("Invalid status: {}", s)),
+            };
+
+            let previous = targets::resolve_project(db, target.as_deref(), &name)?;
+            db.update_project_status(&previous.id, project_status).map_err(|e| e.to_string())?;
+            let (id, name) = (previous.id.clone(), previous.name.clone());
+            let journal_id = record("project_updated", &name, Some(&id), UndoOperation::RestoreProject {
+                project: previous,
+            });
+
+            Ok(json!({
+                "type": "project_updated",
+                "data": {
+                    "id": id,
+                    "name": name,
+                    "status": status
+                },
+                "journal_id": journal_id
+            }))
+        }
+        ActionData::None => {
+            Ok(json!({
+                "type": "no_action",
+                "data": null,
+                "journal_id": null
+            }))
+        }
+    }
+}
+
+/// Whether Auto mode applies `action` without asking: its type is switched on and the AI
+/// is more confident than the threshold
+pub fn should_auto_apply(settings: &VersionSettings, action: &DetectedAction) -> bool {
+    settings.ai_create_mode == AiCreateMode::Auto
+        && !action.confirmed
+        && action.confidence > settings.auto_apply_confidence
+        && settings.auto_apply_types.iter().any(|t| t == action.data.type_name())
+}
+
+/// Apply the actions of a new inbox item that Auto mode covers, marking them confirmed so
+/// the item shows them as already applied. One that fails, or a todo repeating an open one,
+/// is left for the user to confirm. Only for actions the model detected, whose confidence
+/// means something; ones the app builds itself always wait for the user.
+pub fn auto_apply(db: &Database, settings: &VersionSettings, item: &mut InboxItem) -> usize {
+    let mut applied = 0;
+    let mut open_todos: Option<Vec<Todo>> = None;
+    for action in item.detected_actions.iter_mut() {
+        if !should_auto_apply(settings, action) {
+            continue;
+        }
+        if let ActionData::Todo { title, .. } = &action.data {
+            let todos = open_todos.get_or_insert_with(|| db.get_todos(None).unwrap_or_default());
+            if let Some(todo) = targets::duplicate_todo(todos, title) {
+                log::info!("Not auto-applying todo \"{}\": \"{}\" is already open", title, todo.title);
+                continue;
+            }
+        }
+        match execute(db, action.clone(), item.project_id.clone(), ActionTrigger::Scheduler) {
+            Ok(_) => {
+                if let (ActionData::Todo { title, .. }, Some(todos)) = (&action.data, open_todos.as_mut()) {
+                    todos.push(Todo::new(title.clone(), item.project_id.clone()));
+                }
+                action.confirmed = true;
+                applied += 1;
+            }
+            Err(e) => log::warn!("Failed to auto-apply {} action: {}", action.data.type_name(), e),
+        }
+    }
+    applied
+}
+
+/// Apply the actions at `indices` of an inbox item, as confirmed by the user. Each result is
+/// `{ index, result }` or `{ index, error }`; the ones applied are marked confirmed on the item.
+pub fn apply_inbox_actions(db: &Database, item_id: &str, indices: &[usize]) -> Result<Vec<Value>, String> {
+    let mut item = db.get_inbox_item(item_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Inbox item not found: {}", item_id))?;
+
+    let mut results = Vec::new();
+    for &index in indices {
+        let outcome = match item.detected_actions.get(index) {
+            None => Err(format!("No action at index {}", index)),
+            Some(action) if action.confirmed => Err("Already applied".to_string()),
+            Some(action) => execute(db, action.clone(), item.project_id.clone(), ActionTrigger::Inbox),
+        };
+        results.push(match outcome {
+            Ok(result) => {
+                item.detected_actions[index].confirmed = true;
+                json!({ "index": index, "result": result })
+            }
+            Err(e) => json!({ "index": index, "error": e }),
+        });
+    }
+
+    db.update_inbox_detected_actions(&item.id, &item.detected_actions).map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
+/// Kanban columns a todo can be moved to
+const TODO_COLUMNS: [&str; 3] = ["backlog", "in_progress", "done"];
+
+fn parse_todo_status(status: &str) -> Result<TodoStatus, String> {
+    match status {
+        "pending" => Ok(TodoStatus::Pending),
+        "in_progress" => Ok(TodoStatus::InProgress),
+        "completed" => Ok(TodoStatus::Completed),
+        "cancelled" => Ok(TodoStatus::Cancelled),
+        _ => Err(format!("Invalid status: {}", status)),
+    }
+}
+
+fn parse_priority(priority: &str) -> Result<TodoPriority, String> {
+    match priority {
+        "low" => Ok(TodoPriority::Low),
+        "medium" => Ok(TodoPriority::Medium),
+        "high" => Ok(TodoPriority::High),
+        "urgent" => Ok(TodoPriority::Urgent),
+        _ => Err(format!("Invalid priority: {}", priority)),
+    }
+}
+
+fn check_date(date: &str) -> Result<String, String> {
+    parse_date(date).map(|d| d.to_string())
+}
+
+/// `previous` with the given changes. Status and kanban column move together the way
+/// dragging a card does: a new status picks its column, a new column implies its status.
+fn apply_todo_update(
+    previous: &Todo,
+    status: Option<TodoStatus>,
+    priority: Option<TodoPriority>,
+    due_date: Option<String>,
+    column: Option<&str>,
+) -> Todo {
+    let mut todo = previous.clone();
+    let status = status.or_else(|| column.map(|c| match c {
+        "done" => TodoStatus::Completed,
+        "in_progress" => TodoStatus::InProgress,
+        _ => TodoStatus::Pending,
+    }));
+
+    if let Some(status) = status {
+        todo.column = match (column, &status) {
+            (Some(c), _) => c.to_string(),
+            (None, TodoStatus::Completed) => "done".to_string(),
+            (None, TodoStatus::InProgress) => "in_progress".to_string(),
+            (None, TodoStatus::Pending) => "backlog".to_string(),
+            (None, TodoStatus::Cancelled) => previous.column.clone(),
+        };
+        todo.completed_at = match status {
+            TodoStatus::Completed => previous.completed_at.or_else(|| Some(chrono::Utc::now())),
+            _ => None,
+        };
+        todo.status = status;
+    }
+    if let Some(priority) = priority {
+        todo.priority = priority;
+    }
+    if due_date.is_some() {
+        todo.due_date = due_date;
+    }
+    todo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_auto_apply() {
+        let action = |data: ActionData, confidence: f32| DetectedAction {
+            intent: crate::models::ConversationIntent::CreateTodo,
+            confidence,
+            data,
+            confirmed: false,
+        };
+        let todo = |title: &str| ActionData::Todo { title: title.to_string(), priority: None, due_date: None };
+        let mut settings = VersionSettings::default();
+
+        assert!(!should_auto_apply(&settings, &action(todo("a"), 0.9)));
+
+        settings.ai_create_mode = AiCreateMode::Auto;
+        assert!(should_auto_apply(&settings, &action(todo("a"), 0.9)));
+        assert!(!should_auto_apply(&settings, &action(todo("a"), 0.8)));
+        assert!(!should_auto_apply(&settings, &action(ActionData::Progress {
+            summary: "a".to_string(),
+            category: "feature".to_string(),
+            date: None,
+        }, 1.0)));
+
+        let mut applied = action(todo("a"), 1.0);
+        applied.confirmed = true;
+        assert!(!should_auto_apply(&settings, &applied));
+    }
+
+    #[test]
+    fn test_apply_todo_update() {
+        let todo = Todo::new("Write API docs".to_string(), None);
+
+        let done = apply_todo_update(&todo, Some(TodoStatus::Completed), None, None, None);
+        assert_eq!(done.status, TodoStatus::Completed);
+        assert_eq!(done.column, "done");
+        assert!(done.completed_at.is_some());
+
+        let moved = apply_todo_update(&done, None, Some(TodoPriority::Urgent), Some("2026-11-01".to_string()), Some("in_progress"));
+        assert_eq!(moved.status, TodoStatus::InProgress);
+        assert_eq!(moved.column, "in_progress");
+        assert!(moved.completed_at.is_none());
+        assert_eq!(moved.priority, TodoPriority::Urgent);
+        assert_eq!(moved.due_date.as_deref(), Some("2026-11-01"));
+
+        let cancelled = apply_todo_update(&moved, Some(TodoStatus::Cancelled), None, None, None);
+        assert_eq!(cancelled.column, "in_progress");
+        assert_eq!(cancelled.title, "Write API docs");
+    }
+}
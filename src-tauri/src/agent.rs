@@ -0,0 +1,567 @@
+// Agent module - tool-calling chat that answers from the user's data and proposes changes
+use std::path::Path;
+use chrono::{Duration, Local};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Notify;
+use crate::classifier::{parse_category, LOG_CATEGORY_NAMES};
+use crate::db::Database;
+use crate::grok::{GrokClient, GrokError, GrokMessage, ToolCall};
+use crate::models::{
+    parse_date, ActionData, ConversationIntent, DailyLog, DetectedAction, Project, Todo, TodoStatus,
+};
+use crate::scanner::GitScanner;
+use crate::targets;
+
+/// Logs searched when the model gives no start date
+const DEFAULT_SEARCH_DAYS: i64 = 30;
+/// Most logs or todos returned by one call
+const MAX_RESULTS: usize = 50;
+const DEFAULT_RESULTS: usize = 20;
+
+/// Tools that change data; calls to them become actions for the user to confirm
+pub const MUTATING_TOOLS: &[&str] = &[
+    "create_todo", "update_todo", "delete_todo", "log_progress", "create_milestone", "update_milestone", "update_project",
+];
+
+/// Function definitions offered to the model
+pub fn tool_definitions() -> Vec<Value> {
+    let project = json!({ "type": "string", "description": "Project name; defaults to the current project, or all projects when there is none" });
+    let date = |what: &str| json!({ "type": "string", "description": format!("{} as YYYY-MM-DD", what) });
+
+    [
+        ("search_logs", "Search the daily work logs, newest first", json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "Words the summary must contain" },
+                "project": project,
+                "since": date("First day to include, default 30 days ago"),
+                "until": date("Last day to include, default today"),
+                "category": { "type": "string", "enum": LOG_CATEGORY_NAMES },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_RESULTS }
+            }
+        })),
+        ("list_todos", "List TODOs with their ids, status, priority and due date", json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "string", "enum": ["pending", "in_progress", "completed", "cancelled"] },
+                "query": { "type": "string", "description": "Words the title must contain" },
+                "project": project
+            }
+        })),
+        ("get_stats", "Streaks, weekly throughput, category trend and project momentum", json!({
+            "type": "object",
+            "properties": {
+                "period": { "type": "string", "enum": ["week", "month", "year"] }
+            },
+            "required": ["period"]
+        })),
+        ("scan_project", "Today's git changes and uncommitted files of a project", json!({
+            "type": "object",
+            "properties": { "project": project }
+        })),
+        ("create_todo", "Propose a new TODO; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string" },
+                "priority": { "type": "string", "enum": ["low", "medium", "high", "urgent"] },
+                "due_date": date("Due date")
+            },
+            "required": ["title"]
+        })),
+        ("update_todo", "Propose completing a TODO or changing its status, priority, due date or board column; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "todo_id": { "type": "string", "description": "Id from list_todos" },
+                "title": { "type": "string", "description": "The TODO's title, when the id is not known" },
+                "status": { "type": "string", "enum": ["pending", "in_progress", "completed", "cancelled"] },
+                "priority": { "type": "string", "enum": ["low", "medium", "high", "urgent"] },
+                "due_date": date("New due date"),
+                "column": { "type": "string", "enum": ["backlog", "in_progress", "done"] }
+            }
+        })),
+        ("delete_todo", "Propose deleting a TODO; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "todo_id": { "type": "string", "description": "Id from list_todos" },
+                "title": { "type": "string", "description": "The TODO's title, when the id is not known" }
+            }
+        })),
+        ("log_progress", "Propose a progress log for the current project; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "summary": { "type": "string" },
+                "category": { "type": "string", "enum": LOG_CATEGORY_NAMES },
+                "date": date("Day worked on, default today")
+            },
+            "required": ["summary", "category"]
+        })),
+        ("create_milestone", "Propose a milestone for the current project; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "version": { "type": "string" }
+            },
+            "required": ["title"]
+        })),
+        ("update_milestone", "Propose changing a milestone's status or target date; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string", "description": "The milestone's title or version" },
+                "status": { "type": "string", "enum": ["planned", "in_progress", "completed", "cancelled"] },
+                "target_date": date("New target date")
+            },
+            "required": ["title"]
+        })),
+        ("update_project", "Propose pausing, archiving or reactivating a project; the user confirms it", json!({
+            "type": "object",
+            "properties": {
+                "project": { "type": "string", "description": "Project name; defaults to the current project" },
+                "status": { "type": "string", "enum": ["active", "paused", "archived"] }
+            },
+            "required": ["status"]
+        })),
+    ]
+    .into_iter()
+    .map(|(name, description, parameters)| json!({
+        "type": "function",
+        "function": { "name": name, "description": description, "parameters": parameters }
+    }))
+    .collect()
+}
+
+/// One tool call made while answering, as shown to the user
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentStep {
+    pub tool: String,
+    pub arguments: Value,
+    /// What the model was told, an `error` object when the call failed
+    pub result: Value,
+    /// The call changes data and waits for the user to confirm it
+    pub needs_confirmation: bool,
+}
+
+/// The agent's answer to one chat message
+pub struct AgentReply {
+    pub content: String,
+    pub steps: Vec<AgentStep>,
+    /// Proposed by mutating tools, in the order they were called
+    pub actions: Vec<DetectedAction>,
+    pub cancelled: bool,
+}
+
+/// What the tools can see: the database, git, and the project the chat is about
+pub struct AgentContext<'a> {
+    pub db: &'a Database,
+    pub scanner: &'a GitScanner,
+    pub project_id: Option<&'a str>,
+}
+
+enum ToolOutcome {
+    Data(Value),
+    Confirm(DetectedAction),
+}
+
+/// Answer a chat turn, letting the model call tools for up to `max_steps` rounds before it
+/// has to reply. Reply text streams to `on_delta` as it arrives, and `on_step` sees each call
+/// as it completes. `cancel` stops the reply, keeping what was received.
+pub async fn run<F: FnMut(&AgentStep), D: FnMut(&str)>(
+    grok: &GrokClient,
+    ctx: &AgentContext<'_>,
+    mut messages: Vec<GrokMessage>,
+    max_steps: u32,
+    cancel: &Notify,
+    mut on_step: F,
+    mut on_delta: D,
+) -> Result<AgentReply, GrokError> {
+    let tools = tool_definitions();
+    let mut steps = Vec::new();
+    let mut actions = Vec::new();
+
+    for round in 0..=max_steps {
+        let reply = grok.chat_with_tools(messages.clone(), &tools, round < max_steps, cancel, &mut on_delta).await?;
+
+        if reply.cancelled || reply.tool_calls.is_empty() {
+            let cancelled = reply.cancelled;
+            return Ok(AgentReply { content: reply.content, steps, actions, cancelled });
+        }
+
+        let calls = reply.tool_calls.clone();
+        messages.push(reply.into_message());
+        for call in calls {
+            let step = run_call(ctx, &call, &mut actions);
+            messages.push(GrokMessage::tool_result(&call.id, step.result.to_string()));
+            on_step(&step);
+            steps.push(step);
+        }
+    }
+
+    // Only reached when the last round ignored `tool_choice: none`
+    Ok(AgentReply { content: String::new(), steps, actions, cancelled: false })
+}
+
+fn run_call(ctx: &AgentContext<'_>, call: &ToolCall, actions: &mut Vec<DetectedAction>) -> AgentStep {
+    let arguments: Value = match call.function.arguments.trim() {
+        "" => json!({}),
+        raw => serde_json::from_str(raw).unwrap_or(Value::Null),
+    };
+
+    let outcome = if arguments.is_object() {
+        run_tool(ctx, &call.function.name, &arguments)
+    } else {
+        Err("Arguments must be a JSON object".to_string())
+    };
+
+    let (result, needs_confirmation) = match outcome {
+        Ok(ToolOutcome::Data(data)) => (data, false),
+        Ok(ToolOutcome::Confirm(action)) => {
+            actions.push(action);
+            (json!({ "status": "awaiting_user_confirmation" }), true)
+        }
+        Err(e) => {
+            log::warn!("Agent tool {} failed: {}", call.function.name, e);
+            (json!({ "error": e }), false)
+        }
+    };
+
+    AgentStep {
+        tool: call.function.name.clone(),
+        arguments,
+        result,
+        needs_confirmation,
+    }
+}
+
+fn run_tool(ctx: &AgentContext<'_>, name: &str, args: &Value) -> Result<ToolOutcome, String> {
+    match name {
+        "search_logs" => {
+            let projects = ctx.db.get_projects().map_err(|e| e.to_string())?;
+            let project = scoped_project(&projects, ctx.project_id, str_arg(args, "project"))?;
+            let today = Local::now().date_naive();
+            let since = match str_arg(args, "since") {
+                Some(s) => parse_date(s)?,
+                None => today - Duration::days(DEFAULT_SEARCH_DAYS),
+            };
+            let until = str_arg(args, "until").map(parse_date).transpose()?.unwrap_or(today);
+
+            let logs = ctx.db.get_daily_logs_since(&since.to_string()).map_err(|e| e.to_string())?;
+            let filter = LogFilter {
+                project_id: project.map(|p| p.id.as_str()),
+                until: until.to_string(),
+                category: str_arg(args, "category"),
+                query: str_arg(args, "query"),
+            };
+            let limit = args.get("limit").and_then(Value::as_u64).map_or(DEFAULT_RESULTS, |l| l as usize).min(MAX_RESULTS);
+
+            let found: Vec<Value> = filter.apply(logs).into_iter()
+                .take(limit)
+                .map(|log| json!({
+                    "id": log.id,
+                    "date": log.date,
+                    "project": project_name(&projects, &log.project_id),
+                    "category": log.category,
+                    "summary": log.summary,
+                }))
+                .collect();
+            Ok(ToolOutcome::Data(json!({ "since": since.to_string(), "until": until.to_string(), "logs": found })))
+        }
+        "list_todos" => {
+            let projects = ctx.db.get_projects().map_err(|e| e.to_string())?;
+            let project = scoped_project(&projects, ctx.project_id, str_arg(args, "project"))?;
+            let status = str_arg(args, "status").map(parse_status).transpose()?;
+            let todos = ctx.db.get_todos(status).map_err(|e| e.to_string())?;
+
+            let found: Vec<Value> = filter_todos(todos, project.map(|p| p.id.as_str()), str_arg(args, "query"))
+                .into_iter()
+                .take(MAX_RESULTS)
+                .map(|todo| json!({
+                    "id": todo.id,
+                    "title": todo.title,
+                    "status": todo.status.as_str(),
+                    "priority": todo.priority,
+                    "due_date": todo.due_date,
+                    "project": todo.project_id.as_deref().map(|id| project_name(&projects, id)),
+                }))
+                .collect();
+            Ok(ToolOutcome::Data(json!({ "todos": found })))
+        }
+        "get_stats" => {
+            let period = str_arg(args, "period").unwrap_or("week");
+            let report = crate::analytics::build_report(ctx.db, period)?;
+            Ok(ToolOutcome::Data(serde_json::to_value(report).map_err(|e| e.to_string())?))
+        }
+        "scan_project" => {
+            let projects = ctx.db.get_projects().map_err(|e| e.to_string())?;
+            let project = scoped_project(&projects, ctx.project_id, str_arg(args, "project"))?
+                .ok_or("Name the project to scan")?;
+            let path = Path::new(&project.path);
+            let today = ctx.scanner.get_today_diff(path)?;
+            let uncommitted = ctx.scanner.get_uncommitted_changes(path)?;
+            Ok(ToolOutcome::Data(json!({
+                "project": project.name,
+                "committed_today": today,
+                "uncommitted": uncommitted,
+            })))
+        }
+        name if MUTATING_TOOLS.contains(&name) => proposed_action(ctx, name, args).map(ToolOutcome::Confirm),
+        _ => Err(format!("Unknown tool: {}", name)),
+    }
+}
+
+/// The action a mutating tool call proposes, checked against the data it refers to
+fn proposed_action(ctx: &AgentContext<'_>, name: &str, args: &Value) -> Result<DetectedAction, String> {
+    let (intent, data) = match name {
+        "create_todo" => (ConversationIntent::CreateTodo, ActionData::Todo {
+            title: required_arg(args, "title")?,
+            priority: str_arg(args, "priority").map(str::to_string),
+            due_date: str_arg(args, "due_date").map(|d| parse_date(d).map(|d| d.to_string())).transpose()?,
+        }),
+        "update_todo" => {
+            let todo = todo_arg(ctx, args)?;
+            let status = str_arg(args, "status").map(|s| parse_status(s).map(|_| s.to_string())).transpose()?;
+            let due_date = str_arg(args, "due_date").map(|d| parse_date(d).map(|d| d.to_string())).transpose()?;
+            let priority = str_arg(args, "priority").map(str::to_string);
+            let column = str_arg(args, "column").map(str::to_string);
+            if status.is_none() && due_date.is_none() && priority.is_none() && column.is_none() {
+                return Err("Give a new status, priority, due_date or column".to_string());
+            }
+            (ConversationIntent::UpdateTodo, ActionData::UpdateTodo {
+                todo_id: Some(todo.id),
+                title: todo.title,
+                status,
+                priority,
+                due_date,
+                column,
+            })
+        }
+        "delete_todo" => {
+            let todo = todo_arg(ctx, args)?;
+            (ConversationIntent::DeleteTodo, ActionData::DeleteTodo { todo_id: Some(todo.id), title: todo.title })
+        }
+        "log_progress" => {
+            ctx.project_id.ok_or("Progress is logged to the current project, and no project is selected")?;
+            (ConversationIntent::LogProgress, ActionData::Progress {
+                summary: required_arg(args, "summary")?,
+                category: str_arg(args, "category").unwrap_or("other").to_string(),
+                date: str_arg(args, "date").map(|d| parse_date(d).map(|d| d.to_string())).transpose()?,
+            })
+        }
+        "create_milestone" => {
+            ctx.project_id.ok_or("Milestones belong to the current project, and no project is selected")?;
+            (ConversationIntent::CreateMilestone, ActionData::Milestone {
+                title: required_arg(args, "title")?,
+                description: str_arg(args, "description").map(str::to_string),
+                version: str_arg(args, "version").map(str::to_string),
+                git_tag: None,
+            })
+        }
+        "update_milestone" => {
+            let milestone = targets::resolve_milestone(ctx.db, None, &required_arg(args, "title")?, ctx.project_id)?;
+            let status = str_arg(args, "status").map(str::to_string);
+            let target_date = str_arg(args, "target_date").map(|d| parse_date(d).map(|d| d.to_string())).transpose()?;
+            if status.is_none() && target_date.is_none() {
+                return Err("Give a new status or target_date".to_string());
+            }
+            (ConversationIntent::UpdateMilestone, ActionData::UpdateMilestone {
+                milestone_id: Some(milestone.id),
+                title: milestone.title,
+                status,
+                target_date,
+            })
+        }
+        "update_project" => {
+            let projects = ctx.db.get_projects().map_err(|e| e.to_string())?;
+            let project = scoped_project(&projects, ctx.project_id, str_arg(args, "project"))?
+                .ok_or("Name the project to change")?;
+            (ConversationIntent::UpdateProject, ActionData::UpdateProject {
+                project_id: Some(project.id.clone()),
+                name: project.name.clone(),
+                status: required_arg(args, "status")?,
+            })
+        }
+        _ => return Err(format!("{} does not change data", name)),
+    };
+
+    Ok(DetectedAction {
+        intent,
+        // Asked for explicitly, unlike a guessed intent
+        confidence: 1.0,
+        data,
+        confirmed: false,
+    })
+}
+
+/// The todo a tool call names, by `todo_id` or else by `title`
+fn todo_arg(ctx: &AgentContext<'_>, args: &Value) -> Result<Todo, String> {
+    match (str_arg(args, "todo_id"), str_arg(args, "title")) {
+        (None, None) => Err("Give the todo_id or title".to_string()),
+        (id, title) => targets::resolve_todo(ctx.db, id, title.unwrap_or_default())
+            .map_err(|e| format!("{}; look it up with list_todos", e)),
+    }
+}
+
+/// Narrows logs found by date to what a search asked for, newest first
+struct LogFilter<'a> {
+    project_id: Option<&'a str>,
+    until: String,
+    category: Option<&'a str>,
+    query: Option<&'a str>,
+}
+
+impl LogFilter<'_> {
+    fn apply(&self, logs: Vec<DailyLog>) -> Vec<DailyLog> {
+        let query = self.query.map(str::to_lowercase);
+        let mut logs: Vec<DailyLog> = logs.into_iter()
+            .filter(|log| log.date <= self.until)
+            .filter(|log| self.project_id.is_none_or(|id| log.project_id == id))
+            .filter(|log| self.category.is_none_or(|c| parse_category(c) == log.category))
+            .filter(|log| query.as_deref().is_none_or(|q| log.summary.to_lowercase().contains(q)))
+            .collect();
+        logs.sort_by(|a, b| b.date.cmp(&a.date));
+        logs
+    }
+}
+
+fn filter_todos(todos: Vec<Todo>, project_id: Option<&str>, query: Option<&str>) -> Vec<Todo> {
+    let query = query.map(str::to_lowercase);
+    todos.into_iter()
+        .filter(|todo| project_id.is_none_or(|id| todo.project_id.as_deref() == Some(id)))
+        .filter(|todo| query.as_deref().is_none_or(|q| todo.title.to_lowercase().contains(q)))
+        .collect()
+}
+
+/// The project a read-only tool looks at: the one named, else the chat's project, else all
+fn scoped_project<'a>(
+    projects: &'a [Project],
+    current: Option<&str>,
+    named: Option<&str>,
+) -> Result<Option<&'a Project>, String> {
+    match named {
+        Some(name) => find_project(projects, name).map(Some),
+        None => Ok(current.and_then(|id| projects.iter().find(|p| p.id == id))),
+    }
+}
+
+/// Match a project by name, exactly first and then by the name containing `name`
+fn find_project<'a>(projects: &'a [Project], name: &str) -> Result<&'a Project, String> {
+    let name = name.trim().to_lowercase();
+    projects.iter()
+        .find(|p| p.name.to_lowercase() == name)
+        .or_else(|| projects.iter().find(|p| p.name.to_lowercase().contains(&name)))
+        .ok_or_else(|| {
+            let names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+            format!("No project named \"{}\"; projects are: {}", name, names.join(", "))
+        })
+}
+
+fn project_name(projects: &[Project], id: &str) -> String {
+    projects.iter().find(|p| p.id == id).map_or_else(|| id.to_string(), |p| p.name.clone())
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn required_arg(args: &Value, key: &str) -> Result<String, String> {
+    str_arg(args, key).map(str::to_string).ok_or_else(|| format!("Missing {}", key))
+}
+
+fn parse_status(status: &str) -> Result<TodoStatus, String> {
+    match status {
+        "pending" => Ok(TodoStatus::Pending),
+        "in_progress" => Ok(TodoStatus::InProgress),
+        "completed" => Ok(TodoStatus::Completed),
+        "cancelled" => Ok(TodoStatus::Cancelled),
+        _ => Err(format!("Invalid status \"{}\"", status)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+    use crate::models::{LogCategory, ProjectStatus};
+
+    fn project(id: &str, name: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: name.to_string(),
+            path: format!("/tmp/{}", id),
+            status: ProjectStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn log(project_id: &str, date: &str, category: LogCategory, summary: &str) -> DailyLog {
+        DailyLog {
+            id: format!("{}-{}", project_id, date),
+            project_id: project_id.to_string(),
+            date: date.to_string(),
+            summary: summary.to_string(),
+            category,
+            files_changed: vec![],
+            ai_classification: None,
+            user_override: None,
+            created_at: Utc::now(),
+            prompt_version: None,
+            summary_by_user: false,
+        }
+    }
+
+    #[test]
+    fn test_tool_definitions() {
+        let tools = tool_definitions();
+        let names: Vec<&str> = tools.iter().filter_map(|t| t["function"]["name"].as_str()).collect();
+        assert_eq!(names.len(), 11);
+        for tool in MUTATING_TOOLS {
+            assert!(names.contains(tool), "{} is not defined", tool);
+        }
+        assert!(tools.iter().all(|t| t["function"]["parameters"]["type"] == "object"));
+    }
+
+    #[test]
+    fn test_find_project() {
+        let projects = vec![project("1", "Sixarms"), project("2", "Billing API"), project("3", "API")];
+        assert_eq!(find_project(&projects, "api").unwrap().id, "3");
+        assert_eq!(find_project(&projects, "billing").unwrap().id, "2");
+        assert!(find_project(&projects, "website").unwrap_err().contains("Sixarms, Billing API, API"));
+
+        assert_eq!(scoped_project(&projects, Some("1"), None).unwrap().unwrap().id, "1");
+        assert_eq!(scoped_project(&projects, Some("1"), Some("API")).unwrap().unwrap().id, "3");
+        assert!(scoped_project(&projects, None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let logs = vec![
+            log("api", "2024-01-09", LogCategory::Feature, "Added rate limiting"),
+            log("api", "2024-01-16", LogCategory::Bugfix, "Fixed login redirect"),
+            log("web", "2024-01-16", LogCategory::Feature, "Login page"),
+            log("api", "2024-01-20", LogCategory::Feature, "Webhooks"),
+        ];
+
+        let filter = LogFilter { project_id: Some("api"), until: "2024-01-16".to_string(), category: None, query: None };
+        let dates: Vec<String> = filter.apply(logs.clone()).into_iter().map(|l| l.date).collect();
+        assert_eq!(dates, vec!["2024-01-16", "2024-01-09"]);
+
+        let filter = LogFilter { project_id: None, until: "2024-12-31".to_string(), category: Some("feature"), query: Some("LOGIN") };
+        let ids: Vec<String> = filter.apply(logs).into_iter().map(|l| l.id).collect();
+        assert_eq!(ids, vec!["web-2024-01-16"]);
+    }
+
+    #[test]
+    fn test_argument_parsing() {
+        let args = json!({ "title": "  Ship it ", "due_date": "", "status": "in_progress" });
+        assert_eq!(required_arg(&args, "title").unwrap(), "Ship it");
+        assert_eq!(str_arg(&args, "due_date"), None);
+        assert!(required_arg(&args, "summary").is_err());
+
+        assert_eq!(parse_status("in_progress").unwrap(), TodoStatus::InProgress);
+        assert!(parse_status("done").is_err());
+        assert_eq!(parse_date("2024-01-16").unwrap(), NaiveDate::from_ymd_opt(2024, 1, 16).unwrap());
+        assert!(parse_date("last tuesday").is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::Local;
+use tauri::{AppHandle, Emitter, State};
+use crate::agent::{self, AgentContext, AgentStep};
+use crate::db::Database;
+use crate::embeddings::{self, EmbeddingsKey};
+use crate::grok::{AiCommandError, ChatHistoryItem, ChatStreams, GrokClient};
+use crate::i18n::Locale;
+use crate::models::{AiPurpose, ChatMessage, ChatRole, DetectedAction, SourceKind};
+use crate::retrieval;
+use crate::scanner::GitScanner;
+use crate::scheduler::load_settings;
+
+/// Semantic matches considered as sources for one chat message
+const SEMANTIC_MATCHES: usize = 10;
+
+#[derive(Clone, serde::Serialize)]
+struct AgentStepEvent<'a> {
+    request_id: &'a str,
+    step: &'a AgentStep,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ChatDelta<'a> {
+    request_id: &'a str,
+    delta: &'a str,
+}
+
+#[derive(serde::Serialize)]
+pub struct ChatAgentResult {
+    pub message: ChatMessage,
+    /// Changes the agent proposed; each is applied with `execute_detected_action` once confirmed
+    pub detected_actions: Vec<DetectedAction>,
+    pub steps: Vec<AgentStep>,
+    pub cancelled: bool,
+}
+
+/// Chat through the tool-calling agent, emitting a `chat:agent-step` event for each tool call
+/// and `chat:delta` events as reply text arrives.
+/// Records the message is about are retrieved up front, and the ones the reply cites are
+/// returned on it. Cancelled with `cancel_chat_stream`; the reply is saved to chat history either way.
+/// Fails with an `AiCommandError`, whose `kind` tells what went wrong.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn chat_agent(
+    grok: State<'_, GrokClient>,
+    db: State<'_, Database>,
+    scanner: State<'_, GitScanner>,
+    streams: State<'_, ChatStreams>,
+    embeddings_key: State<'_, EmbeddingsKey>,
+    app_handle: AppHandle,
+    request_id: String,
+    message: String,
+    history: Vec<ChatHistoryItem>,
+    project_context: Option<String>,
+    project_id: Option<String>,
+) -> Result<ChatAgentResult, AiCommandError> {
+    let settings = load_settings(&db);
+    let locale = Locale::new(&settings.language);
+
+    let projects = db.get_projects().map_err(|e| e.to_string())?;
+    let project_names: Vec<String> = projects.iter().map(|p| p.name.clone()).collect();
+    let project_context = project_context.or_else(|| {
+        let project = projects.iter().find(|p| Some(&p.id) == project_id.as_ref())?;
+        Some(format!("{} ({})", project.name, project.path))
+    });
+
+    let mut sources = retrieval::gather(&db, &scanner, &message, project_id.as_deref())?;
+    if settings.embeddings.enabled {
+        // Finds records phrased differently from the question; chat works without it
+        let kinds = [SourceKind::Log, SourceKind::Commit, SourceKind::Todo];
+        let matches = match embeddings::embedder(&settings, &embeddings_key) {
+            Ok(embedder) => embeddings::search(&db, embedder.as_ref(), &message, SEMANTIC_MATCHES, Some(&kinds), project_id.as_deref()).await,
+            Err(e) => Err(e),
+        };
+        match matches {
+            Ok(matches) => sources = retrieval::add_matches(sources, matches),
+            Err(e) => log::warn!("Semantic search failed, answering from keyword matches: {}", e),
+        }
+    }
+    let sources_section = if sources.is_empty() {
+        String::new()
+    } else {
+        grok.prompts().get("agent").render("sources", &[("sources", &retrieval::format_sources(&sources, &projects))])
+    };
+
+    let today = Local::now().format("%Y-%m-%d (%A)").to_string();
+    let (messages, prompt_version) = grok.agent_messages(
+        &message,
+        history,
+        project_context.as_deref(),
+        &[("today", &today), ("projects", &locale.join(&project_names)), ("sources", &sources_section)],
+    );
+
+    let cancel = streams.register(&request_id).await;
+    let ctx = AgentContext {
+        db: &db,
+        scanner: &scanner,
+        project_id: project_id.as_deref(),
+    };
+    let outcome = agent::run(
+        &grok.for_purpose(AiPurpose::Agent, project_id.as_deref()),
+        &ctx,
+        messages,
+        settings.ai.agent_max_steps,
+        &cancel,
+        |step| {
+            let _ = app_handle.emit("chat:agent-step", AgentStepEvent { request_id: &request_id, step });
+        },
+        |delta| {
+            let _ = app_handle.emit("chat:delta", ChatDelta { request_id: &request_id, delta });
+        },
+    ).await;
+    streams.finish(&request_id).await;
+    let reply = outcome?;
+
+    let text = match reply.content.trim() {
+        "" if reply.cancelled => locale.t("agent.cancelled", &[]),
+        "" => locale.t("agent.no_answer", &[]),
+        text => text.to_string(),
+    };
+    let citations = retrieval::cited(&text, &sources);
+    let mut message = ChatMessage::new(ChatRole::Assistant, text, project_id);
+    message.prompt_version = Some(prompt_version);
+    message.citations = citations;
+    db.create_chat_message(&message).map_err(|e| e.to_string())?;
+
+    Ok(ChatAgentResult {
+        message,
+        detected_actions: reply.actions,
+        steps: reply.steps,
+        cancelled: reply.cancelled,
+    })
+}
@@ -1,8 +1,15 @@
-use crate::grok::{GrokClient, GrokMessage};
+use crate::ai_cache::ResponseCache;
+use crate::anomaly::Anomaly;
+use crate::evidence::Evidenced;
+use crate::grok::GrokClient;
+use crate::i18n::Locale;
 use crate::models::{
-    ActionData, ConversationIntent, DailyLog, DetectedAction, GitDiffResult,
+    ActionData, AiPurpose, ConversationIntent, DailyLog, DetectedAction, GitDiffResult,
     InboxItem, InboxItemType, Project, SuggestedAction,
 };
+use crate::classifier::LOG_CATEGORY_NAMES;
+use crate::prompts::Prompts;
+use crate::structured::{JsonSchema, Validate};
 use serde::{Deserialize, Serialize};
 
 /// AI Agent for analyzing development patterns and generating insights
@@ -13,8 +20,128 @@ pub struct AiAgent;
 pub struct AnalysisResult {
     pub summary: String,
     pub category: String,
+    #[serde(default)]
     pub insights: Vec<String>,
+    #[serde(default)]
     pub suggested_todos: Vec<String>,
+    /// Template that produced this result; set by us, not the model
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+}
+
+impl Validate for AnalysisResult {
+    fn validate(&self) -> Result<(), String> {
+        if self.summary.trim().is_empty() {
+            return Err("summary is empty".to_string());
+        }
+        validate_category(&self.category)
+    }
+}
+
+/// Summary and category for one day of a batched history analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayAnalysis {
+    pub date: String,
+    pub summary: String,
+    pub category: String,
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DayAnalysisBatch {
+    days: Vec<DayAnalysis>,
+}
+
+impl Validate for DayAnalysisBatch {
+    fn validate(&self) -> Result<(), String> {
+        self.days.iter().try_for_each(|d| validate_category(&d.category))
+    }
+}
+
+fn validate_category(category: &str) -> Result<(), String> {
+    if LOG_CATEGORY_NAMES.contains(&category) {
+        Ok(())
+    } else {
+        Err(format!("category must be one of {}, got \"{}\"", LOG_CATEGORY_NAMES.join("/"), category))
+    }
+}
+
+fn analysis_schema() -> JsonSchema {
+    JsonSchema {
+        name: "daily_analysis",
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "summary": { "type": "string" },
+                "category": { "type": "string", "enum": LOG_CATEGORY_NAMES },
+                "insights": { "type": "array", "items": { "type": "string" } },
+                "suggested_todos": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["summary", "category", "insights", "suggested_todos"]
+        }),
+    }
+}
+
+fn batch_schema() -> JsonSchema {
+    JsonSchema {
+        name: "daily_analysis_batch",
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "days": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "date": { "type": "string" },
+                            "summary": { "type": "string" },
+                            "category": { "type": "string", "enum": LOG_CATEGORY_NAMES }
+                        },
+                        "required": ["date", "summary", "category"]
+                    }
+                }
+            },
+            "required": ["days"]
+        }),
+    }
+}
+
+/// Files listed per day in a batched prompt, to keep long histories within limits
+const MAX_FILES_PER_DAY: usize = 15;
+
+/// Recent user corrections included in classification prompts
+pub const MAX_CORRECTION_EXAMPLES: usize = 5;
+
+/// Files named per correction example
+const EXAMPLE_FILES: usize = 5;
+
+/// Render logs the user re-categorized as few-shot examples for a classification
+/// prompt. Returns an empty string when there are none.
+pub fn format_correction_examples(prompts: &Prompts, corrections: &[DailyLog]) -> String {
+    let template = prompts.get("corrections");
+    let examples: Vec<String> = corrections.iter()
+        .filter_map(|log| {
+            let label = log.user_override.as_deref()?;
+            let files = log.files_changed.iter()
+                .take(EXAMPLE_FILES)
+                .map(|f| f.path.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(template.render("example", &[
+                ("files", if files.is_empty() { "-" } else { &files }),
+                ("summary", &log.summary),
+                ("ai_category", log.ai_classification.as_deref().unwrap_or("-")),
+                ("category", label),
+            ]))
+        })
+        .collect();
+
+    if examples.is_empty() {
+        return String::new();
+    }
+
+    format!("{}\n\n", template.render("header", &[("examples", &examples.join("\n"))]))
 }
 
 impl AiAgent {
@@ -22,12 +149,15 @@ impl AiAgent {
         AiAgent
     }
 
-    /// Analyze a day's work and generate insights
+    /// Analyze a day's work and generate insights. `corrections` are the project's
+    /// recently re-categorized logs, given to the model as examples.
     pub async fn analyze_daily_work(
         &self,
         grok: &GrokClient,
+        cache: &ResponseCache<'_>,
         project: &Project,
         diff: &GitDiffResult,
+        corrections: &[DailyLog],
     ) -> Result<AnalysisResult, String> {
         if !grok.has_api_key().await {
             return Err("Grok API key not configured".to_string());
@@ -38,55 +168,74 @@ impl AiAgent {
             .collect::<Vec<_>>()
             .join("\n");
 
-        let prompt = format!(
-            r#"分析以下專案今日嘅開發進度：
-
-專案名稱：{}
-改動檔案：
-{}
-
-總共新增 {} 行，刪除 {} 行。
-
-請提供：
-1. 一句話嘅工作摘要
-2. 工作分類（feature/bugfix/refactor/ui/docs/test/chore/other）
-3. 2-3 個關鍵洞察
-4. 建議嘅後續任務（如有）
-
-以 JSON 格式回覆：
-{{"summary": "...", "category": "...", "insights": ["..."], "suggested_todos": ["..."]}}"#,
-            project.name,
-            files_changed,
-            diff.total_additions,
-            diff.total_deletions
-        );
+        let template = grok.prompts().get("analysis");
+        let messages = template.messages(&[
+            ("examples", &format_correction_examples(grok.prompts(), corrections)),
+            ("project_name", &project.name),
+            ("files_changed", &files_changed),
+            ("additions", &diff.total_additions.to_string()),
+            ("deletions", &diff.total_deletions.to_string()),
+        ]);
+
+        let grok = grok.for_purpose(AiPurpose::Analysis, Some(&project.id));
+        let prompt_version = template.version_id();
+        let analysis = cache
+            .get_or_fetch(AiPurpose::Analysis, &prompt_version, &messages, || async {
+                Ok::<_, String>(grok.chat_structured::<AnalysisResult>(messages.clone(), &analysis_schema()).await?)
+            })
+            .await?;
 
-        let messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: "你係 Sixarms AI 助手，專注於分析開發進度。請用廣東話回覆。".to_string(),
-            },
-            GrokMessage {
-                role: "user".to_string(),
-                content: prompt,
-            },
-        ];
+        Ok(AnalysisResult {
+            prompt_version: Some(prompt_version),
+            ..analysis
+        })
+    }
 
-        let response = grok.chat(messages).await?;
-
-        // Try to parse as JSON, fallback to simple result
-        match serde_json::from_str::<AnalysisResult>(&response) {
-            Ok(result) => Ok(result),
-            Err(_) => {
-                // If JSON parsing fails, create a simple result
-                Ok(AnalysisResult {
-                    summary: response.clone(),
-                    category: "other".to_string(),
-                    insights: vec![],
-                    suggested_todos: vec![],
-                })
-            }
+    /// Summarize and classify several past days in one request. Each entry is a day's
+    /// diff with its commit subjects. Days missing from the reply are left out.
+    pub async fn analyze_days_batch(
+        &self,
+        grok: &GrokClient,
+        project: &Project,
+        days: &[(GitDiffResult, Vec<String>)],
+        corrections: &[DailyLog],
+    ) -> Result<Vec<DayAnalysis>, String> {
+        if !grok.has_api_key().await {
+            return Err("Grok API key not configured".to_string());
         }
+
+        let template = grok.prompts().get("analysis_batch");
+        let separator = Locale::new(&template.language).t("list.clause_separator", &[]);
+        let day_blocks = days.iter()
+            .map(|(diff, subjects)| {
+                let files = diff.files.iter()
+                    .take(MAX_FILES_PER_DAY)
+                    .map(|f| format!("  {} (+{}/-{})", f.path, f.additions, f.deletions))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                template.render("day", &[
+                    ("date", &diff.date),
+                    ("subjects", &subjects.join(&separator)),
+                    ("files", &files),
+                    ("additions", &diff.total_additions.to_string()),
+                    ("deletions", &diff.total_deletions.to_string()),
+                ])
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let messages = template.messages(&[
+            ("examples", &format_correction_examples(grok.prompts(), corrections)),
+            ("project_name", &project.name),
+            ("days", &day_blocks),
+        ]);
+
+        let grok = grok.for_purpose(AiPurpose::Analysis, Some(&project.id));
+        let batch = grok.chat_structured::<DayAnalysisBatch>(messages, &batch_schema()).await?;
+        let prompt_version = template.version_id();
+        Ok(batch.days.into_iter()
+            .map(|day| DayAnalysis { prompt_version: Some(prompt_version.clone()), ..day })
+            .collect())
     }
 
     /// Detect anomalies in work patterns
@@ -109,37 +258,12 @@ impl AiAgent {
             .collect::<Vec<_>>()
             .join("\n");
 
-        let prompt = format!(
-            r#"分析以下專案嘅最近開發記錄，檢測有無異常模式：
-
-專案名稱：{}
-最近記錄：
-{}
+        let messages = grok.prompts().get("anomaly_detect").messages(&[
+            ("project_name", &project.name),
+            ("logs", &log_summaries),
+        ]);
 
-如果發現以下情況，請指出：
-1. 長時間無進度
-2. 頻繁切換任務類型
-3. 大量刪除代碼
-4. 其他異常模式
-
-如果一切正常，回覆 "NORMAL"。
-如果有異常，簡短描述異常情況（一句話）。"#,
-            project.name,
-            log_summaries
-        );
-
-        let messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: "你係開發模式分析專家。請簡短直接回覆。".to_string(),
-            },
-            GrokMessage {
-                role: "user".to_string(),
-                content: prompt,
-            },
-        ];
-
-        let response = grok.chat(messages).await?;
+        let response = grok.for_purpose(AiPurpose::Anomaly, Some(&project.id)).chat(messages).await?;
 
         if response.trim().to_uppercase() == "NORMAL" {
             Ok(None)
@@ -151,20 +275,21 @@ impl AiAgent {
     /// Generate an inbox item from analysis
     pub fn create_daily_summary_inbox(
         &self,
+        locale: Locale,
         project: &Project,
         analysis: &AnalysisResult,
     ) -> InboxItem {
         let mut item = InboxItem::new(
             InboxItemType::DailySummary,
-            format!("【{}】今日進度：{}", project.name, analysis.summary),
+            locale.t("inbox.daily_summary.question", &[("project", &project.name), ("summary", &analysis.summary)]),
             Some(project.id.clone()),
         );
+        item.prompt_version = analysis.prompt_version.clone();
 
-        item.context = Some(format!(
-            "分類：{}\n洞察：\n{}",
-            analysis.category,
-            analysis.insights.join("\n")
-        ));
+        item.context = Some(locale.t("inbox.daily_summary.context", &[
+            ("category", &analysis.category),
+            ("insights", &analysis.insights.join("\n")),
+        ]));
 
         // Add suggested actions (legacy display)
         item.suggested_actions = analysis.suggested_todos.iter()
@@ -193,29 +318,62 @@ impl AiAgent {
         item
     }
 
-    /// Generate an anomaly detection inbox item
+    /// Reword a rule-based anomaly into one friendly sentence. Returns the sentence
+    /// and the version of the template that produced it.
+    pub async fn phrase_anomaly(
+        &self,
+        grok: &GrokClient,
+        project: &Project,
+        anomaly: &Anomaly,
+        locale: Locale,
+    ) -> Result<(String, String), String> {
+        if !grok.has_api_key().await {
+            return Err("Grok API key not configured".to_string());
+        }
+
+        let template = grok.prompts().get("anomaly_phrase");
+        let messages = template.messages(&[
+            ("project_name", &project.name),
+            ("description", &anomaly.description),
+            ("evidence", &anomaly.evidence_text(locale)),
+        ]);
+
+        let response = grok.for_purpose(AiPurpose::Anomaly, Some(&project.id)).chat(messages).await?;
+        Ok((response.trim().to_string(), template.version_id()))
+    }
+
+    /// Generate an anomaly detection inbox item, keeping the rule evidence in the context
     pub fn create_anomaly_inbox(
         &self,
+        locale: Locale,
         project: &Project,
-        anomaly_description: &str,
+        anomaly: &Anomaly,
+        phrasing: Option<&str>,
     ) -> InboxItem {
         let mut item = InboxItem::new(
             InboxItemType::AnomalyDetection,
-            format!("⚠️ 【{}】偵測到異常模式", project.name),
+            locale.t("inbox.anomaly.question", &[
+                ("project", &project.name),
+                ("description", phrasing.unwrap_or(&anomaly.description)),
+            ]),
             Some(project.id.clone()),
         );
 
-        item.context = Some(anomaly_description.to_string());
+        item.context = Some(format!(
+            "{}\n\n{}",
+            anomaly.description,
+            anomaly.evidence_text(locale)
+        ));
 
         item.suggested_actions = vec![
             SuggestedAction {
                 id: "review".to_string(),
-                label: "查看詳情".to_string(),
+                label: locale.t("action.view_details", &[]),
                 icon: Some("eye".to_string()),
             },
             SuggestedAction {
                 id: "dismiss".to_string(),
-                label: "忽略".to_string(),
+                label: locale.t("action.dismiss", &[]),
                 icon: Some("x".to_string()),
             },
         ];
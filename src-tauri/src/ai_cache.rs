@@ -0,0 +1,113 @@
+// AI cache module - reuses replies to classification and summary calls for identical input
+use std::future::Future;
+use chrono::{Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use crate::db::Database;
+use crate::grok::{GROK_MODEL, GROK_PROVIDER};
+use crate::models::{AiPurpose, AiSettings};
+
+/// Cached replies for one caller. With `force_refresh` every call goes to the API
+/// and its reply replaces what was cached.
+pub struct ResponseCache<'a> {
+    db: &'a Database,
+    ttl_days: u32,
+    max_entries: u32,
+    force_refresh: bool,
+}
+
+/// Content address of a call. Bump a prompt template's version when its reply format
+/// changes in a way the prompt text alone doesn't show.
+pub fn cache_key<I: Serialize + ?Sized>(purpose: AiPurpose, prompt_version: &str, input: &I) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(GROK_PROVIDER);
+    hasher.update([0]);
+    hasher.update(GROK_MODEL);
+    hasher.update([0]);
+    hasher.update(serde_json::to_string(&purpose).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(prompt_version);
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(input).unwrap_or_default());
+
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl<'a> ResponseCache<'a> {
+    pub fn new(db: &'a Database, settings: &AiSettings, force_refresh: bool) -> Self {
+        ResponseCache {
+            db,
+            ttl_days: settings.cache_ttl_days,
+            max_entries: settings.cache_max_entries,
+            force_refresh,
+        }
+    }
+
+    /// Return the cached reply for `input`, or call `fetch` and cache its reply.
+    /// Cache read and write failures are logged and otherwise ignored.
+    pub async fn get_or_fetch<T, E, I, F, Fut>(
+        &self,
+        purpose: AiPurpose,
+        prompt_version: &str,
+        input: &I,
+        fetch: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        I: Serialize + ?Sized,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        // A zero TTL turns the cache off
+        if self.ttl_days == 0 {
+            return fetch().await;
+        }
+
+        let key = cache_key(purpose, prompt_version, input);
+        if !self.force_refresh {
+            let fresh_after = Utc::now() - Duration::days(self.ttl_days as i64);
+            match self.db.get_ai_cache(&key, fresh_after) {
+                Ok(Some(json)) => match serde_json::from_str(&json) {
+                    Ok(value) => return Ok(value),
+                    Err(e) => log::warn!("Ignoring unreadable cached AI reply: {}", e),
+                },
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to read AI cache: {}", e),
+            }
+        }
+
+        let value = fetch().await?;
+        self.store(&key, purpose, &value);
+        Ok(value)
+    }
+
+    fn store<T: Serialize>(&self, key: &str, purpose: AiPurpose, value: &T) {
+        let Ok(json) = serde_json::to_string(value) else { return };
+        let purpose = serde_json::to_string(&purpose).unwrap_or_default();
+        if let Err(e) = self.db.put_ai_cache(key, purpose.trim_matches('"'), &json) {
+            log::warn!("Failed to write AI cache: {}", e);
+            return;
+        }
+
+        let expired_before = Utc::now() - Duration::days(self.ttl_days as i64);
+        if let Err(e) = self.db.prune_ai_cache(expired_before, self.max_entries) {
+            log::warn!("Failed to prune AI cache: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_addresses_content() {
+        let key = cache_key(AiPurpose::Classify, "classify@1", &["a.rs", "b.rs"]);
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, cache_key(AiPurpose::Classify, "classify@1", &["a.rs", "b.rs"]));
+        assert_ne!(key, cache_key(AiPurpose::Classify, "classify@2", &["a.rs", "b.rs"]));
+        assert_ne!(key, cache_key(AiPurpose::Summary, "classify@1", &["a.rs", "b.rs"]));
+        assert_ne!(key, cache_key(AiPurpose::Classify, "classify@1", &["a.rs"]));
+    }
+}
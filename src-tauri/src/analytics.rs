@@ -0,0 +1,373 @@
+// Analytics module - streaks, throughput and trend metrics over stored history
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use crate::db::Database;
+use crate::models::{
+    parse_date, AnalyticsReport, CategoryTrendPoint, DailyLog, MomentumTrend, Project, ProjectMomentum,
+    StreakStats, Todo, WeeklyThroughput,
+};
+
+/// Momentum needs a few points to be meaningful, so short periods look back this far
+const MIN_MOMENTUM_WEEKS: i64 = 4;
+
+/// Slopes smaller than this (active days per week) are treated as steady
+const MOMENTUM_STEADY_EPSILON: f64 = 0.25;
+
+/// Map a period name to its length in days
+pub fn period_days(period: &str) -> Option<i64> {
+    match period {
+        "week" => Some(7),
+        "month" => Some(30),
+        "year" => Some(365),
+        _ => None,
+    }
+}
+
+/// Monday of the week containing `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// All week starts from the week of `since` up to the week of `until`, inclusive
+fn weeks_between(since: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+    let mut weeks = Vec::new();
+    let mut week = week_start(since);
+    while week <= until {
+        weeks.push(week);
+        week += Duration::days(7);
+    }
+    weeks
+}
+
+/// Compute current and longest streaks of consecutive active days.
+/// The current streak is still alive if the last active day is today or yesterday.
+pub fn compute_streaks(dates: &[NaiveDate], today: NaiveDate) -> StreakStats {
+    let days: BTreeSet<NaiveDate> = dates.iter().copied().collect();
+    if days.is_empty() {
+        return StreakStats::default();
+    }
+
+    let mut longest = 0u32;
+    let mut longest_range: Option<(NaiveDate, NaiveDate)> = None;
+    let mut run = 0u32;
+    let mut run_start = NaiveDate::MIN;
+    let mut prev: Option<NaiveDate> = None;
+
+    for &day in &days {
+        if prev.map(|p| day - p == Duration::days(1)).unwrap_or(false) {
+            run += 1;
+        } else {
+            run = 1;
+            run_start = day;
+        }
+        if run > longest {
+            longest = run;
+            longest_range = Some((run_start, day));
+        }
+        prev = Some(day);
+    }
+
+    let last = *days.iter().next_back().unwrap();
+    let current = if today - last <= Duration::days(1) { run } else { 0 };
+
+    StreakStats {
+        current,
+        longest,
+        longest_start: longest_range.map(|(s, _)| s.format("%Y-%m-%d").to_string()),
+        longest_end: longest_range.map(|(_, e)| e.format("%Y-%m-%d").to_string()),
+        active_days: days.len() as u32,
+    }
+}
+
+/// Todos created vs completed per week within the range
+pub fn weekly_throughput(todos: &[Todo], since: NaiveDate, until: NaiveDate) -> Vec<WeeklyThroughput> {
+    let mut buckets: BTreeMap<NaiveDate, (u32, u32)> = weeks_between(since, until)
+        .into_iter()
+        .map(|w| (w, (0, 0)))
+        .collect();
+
+    for todo in todos {
+        let created = todo.created_at.with_timezone(&Local).date_naive();
+        if created >= since && created <= until {
+            if let Some(bucket) = buckets.get_mut(&week_start(created)) {
+                bucket.0 += 1;
+            }
+        }
+
+        if let Some(completed_at) = todo.completed_at {
+            let completed = completed_at.with_timezone(&Local).date_naive();
+            if completed >= since && completed <= until {
+                if let Some(bucket) = buckets.get_mut(&week_start(completed)) {
+                    bucket.1 += 1;
+                }
+            }
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(week, (created, completed))| WeeklyThroughput {
+            week_start: week.format("%Y-%m-%d").to_string(),
+            created,
+            completed,
+        })
+        .collect()
+}
+
+/// Mean hours from created_at to completed_at for todos completed within the range
+pub fn mean_cycle_time_hours(todos: &[Todo], since: NaiveDate, until: NaiveDate) -> Option<f64> {
+    let durations: Vec<f64> = todos
+        .iter()
+        .filter_map(|t| {
+            let completed_at = t.completed_at?;
+            let completed = completed_at.with_timezone(&Local).date_naive();
+            if completed < since || completed > until || completed_at < t.created_at {
+                return None;
+            }
+            Some((completed_at - t.created_at).num_minutes() as f64 / 60.0)
+        })
+        .collect();
+
+    if durations.is_empty() {
+        None
+    } else {
+        Some(durations.iter().sum::<f64>() / durations.len() as f64)
+    }
+}
+
+/// Count of logs per category for each week in the range
+pub fn category_trend(logs: &[DailyLog], since: NaiveDate, until: NaiveDate) -> Vec<CategoryTrendPoint> {
+    let mut buckets: BTreeMap<NaiveDate, BTreeMap<String, u32>> = weeks_between(since, until)
+        .into_iter()
+        .map(|w| (w, BTreeMap::new()))
+        .collect();
+
+    for log in logs {
+        let Ok(date) = parse_date(&log.date) else { continue };
+        if date < since || date > until {
+            continue;
+        }
+        if let Some(counts) = buckets.get_mut(&week_start(date)) {
+            *counts.entry(log.effective_category()).or_insert(0) += 1;
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(week, counts)| CategoryTrendPoint {
+            week_start: week.format("%Y-%m-%d").to_string(),
+            counts,
+        })
+        .collect()
+}
+
+/// Least-squares slope of evenly spaced values
+pub fn linear_slope(values: &[f64]) -> f64 {
+    let n = values.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mean_x = (n - 1) as f64 / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n as f64;
+
+    let mut num = 0.0;
+    let mut den = 0.0;
+    for (i, y) in values.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        num += dx * (y - mean_y);
+        den += dx * dx;
+    }
+
+    if den == 0.0 { 0.0 } else { num / den }
+}
+
+/// Slope of weekly active days for each project over the range
+pub fn project_momentum(
+    projects: &[Project],
+    logs: &[DailyLog],
+    since: NaiveDate,
+    until: NaiveDate,
+) -> Vec<ProjectMomentum> {
+    let weeks = weeks_between(since, until);
+    let index: HashMap<NaiveDate, usize> = weeks.iter().enumerate().map(|(i, w)| (*w, i)).collect();
+
+    let mut momentum: Vec<ProjectMomentum> = projects
+        .iter()
+        .map(|project| {
+            let mut weekly = vec![0u32; weeks.len()];
+            for log in logs.iter().filter(|l| l.project_id == project.id) {
+                let Ok(date) = parse_date(&log.date) else { continue };
+                if date < since || date > until {
+                    continue;
+                }
+                if let Some(&i) = index.get(&week_start(date)) {
+                    weekly[i] += 1;
+                }
+            }
+
+            let values: Vec<f64> = weekly.iter().map(|&v| v as f64).collect();
+            let slope = linear_slope(&values);
+            let trend = if slope > MOMENTUM_STEADY_EPSILON {
+                MomentumTrend::Rising
+            } else if slope < -MOMENTUM_STEADY_EPSILON {
+                MomentumTrend::Falling
+            } else {
+                MomentumTrend::Steady
+            };
+
+            ProjectMomentum {
+                project_id: project.id.clone(),
+                project_name: project.name.clone(),
+                weekly_activity: weekly,
+                slope,
+                trend,
+            }
+        })
+        .collect();
+
+    momentum.sort_by(|a, b| b.slope.partial_cmp(&a.slope).unwrap_or(std::cmp::Ordering::Equal));
+    momentum
+}
+
+/// Build the full analytics report for a period ending today
+pub fn build_report(db: &Database, period: &str) -> Result<AnalyticsReport, String> {
+    let days = period_days(period).ok_or_else(|| format!("Invalid period: {}", period))?;
+    let today = Local::now().date_naive();
+    let since = today - Duration::days(days - 1);
+    let momentum_since = since.min(week_start(today) - Duration::weeks(MIN_MOMENTUM_WEEKS - 1));
+
+    let projects = db.get_projects().map_err(|e| e.to_string())?;
+    let todos = db.get_todos(None).map_err(|e| e.to_string())?;
+    let logs = db
+        .get_daily_logs_since(&momentum_since.format("%Y-%m-%d").to_string())
+        .map_err(|e| e.to_string())?;
+    let active_dates: Vec<NaiveDate> = db
+        .get_active_dates()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|d| parse_date(d).ok())
+        .collect();
+
+    Ok(AnalyticsReport {
+        period: period.to_string(),
+        since: since.format("%Y-%m-%d").to_string(),
+        until: today.format("%Y-%m-%d").to_string(),
+        streaks: compute_streaks(&active_dates, today),
+        throughput: weekly_throughput(&todos, since, today),
+        mean_cycle_time_hours: mean_cycle_time_hours(&todos, since, today),
+        category_trend: category_trend(&logs, since, today),
+        project_momentum: project_momentum(&projects, &logs, momentum_since, today),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use crate::models::LogCategory;
+
+    fn d(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    fn log(project_id: &str, date: &str, category: LogCategory) -> DailyLog {
+        DailyLog {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: project_id.to_string(),
+            date: date.to_string(),
+            summary: String::new(),
+            category,
+            files_changed: vec![],
+            ai_classification: None,
+            user_override: None,
+            created_at: Utc::now(),
+            prompt_version: None,
+            summary_by_user: false,
+        }
+    }
+
+    #[test]
+    fn test_week_start_is_monday() {
+        assert_eq!(week_start(d("2024-01-17")), d("2024-01-15"));
+        assert_eq!(week_start(d("2024-01-15")), d("2024-01-15"));
+        assert_eq!(week_start(d("2024-01-21")), d("2024-01-15"));
+    }
+
+    #[test]
+    fn test_compute_streaks() {
+        let dates = vec![
+            d("2024-01-01"), d("2024-01-02"), d("2024-01-03"),
+            d("2024-01-05"),
+            d("2024-01-09"), d("2024-01-10"),
+        ];
+        let streaks = compute_streaks(&dates, d("2024-01-11"));
+
+        assert_eq!(streaks.longest, 3);
+        assert_eq!(streaks.longest_start.as_deref(), Some("2024-01-01"));
+        assert_eq!(streaks.longest_end.as_deref(), Some("2024-01-03"));
+        assert_eq!(streaks.current, 2);
+        assert_eq!(streaks.active_days, 6);
+    }
+
+    #[test]
+    fn test_compute_streaks_broken() {
+        let dates = vec![d("2024-01-01"), d("2024-01-02")];
+        let streaks = compute_streaks(&dates, d("2024-01-05"));
+        assert_eq!(streaks.current, 0);
+        assert_eq!(streaks.longest, 2);
+        assert_eq!(compute_streaks(&[], d("2024-01-05")), StreakStats::default());
+    }
+
+    #[test]
+    fn test_linear_slope() {
+        assert_eq!(linear_slope(&[1.0, 2.0, 3.0, 4.0]), 1.0);
+        assert_eq!(linear_slope(&[4.0, 4.0, 4.0]), 0.0);
+        assert!(linear_slope(&[5.0, 3.0, 1.0]) < 0.0);
+        assert_eq!(linear_slope(&[3.0]), 0.0);
+    }
+
+    #[test]
+    fn test_weekly_throughput_and_cycle_time() {
+        let mut done = Todo::new("done".to_string(), None);
+        done.created_at = Utc.with_ymd_and_hms(2024, 1, 16, 12, 0, 0).unwrap();
+        done.completed_at = Some(Utc.with_ymd_and_hms(2024, 1, 23, 12, 0, 0).unwrap());
+        let mut open = Todo::new("open".to_string(), None);
+        open.created_at = Utc.with_ymd_and_hms(2024, 1, 24, 12, 0, 0).unwrap();
+
+        let todos = vec![done, open];
+        let throughput = weekly_throughput(&todos, d("2024-01-15"), d("2024-01-28"));
+
+        assert_eq!(throughput.len(), 2);
+        assert_eq!((throughput[0].created, throughput[0].completed), (1, 0));
+        assert_eq!((throughput[1].created, throughput[1].completed), (1, 1));
+        assert_eq!(mean_cycle_time_hours(&todos, d("2024-01-15"), d("2024-01-28")), Some(168.0));
+    }
+
+    #[test]
+    fn test_category_trend_prefers_user_override() {
+        let mut corrected = log("p1", "2024-01-16", LogCategory::Feature);
+        corrected.user_override = Some("bugfix".to_string());
+        let logs = vec![log("p1", "2024-01-15", LogCategory::Feature), corrected];
+
+        let trend = category_trend(&logs, d("2024-01-15"), d("2024-01-21"));
+        assert_eq!(trend.len(), 1);
+        assert_eq!(trend[0].counts.get("feature"), Some(&1));
+        assert_eq!(trend[0].counts.get("bugfix"), Some(&1));
+    }
+
+    #[test]
+    fn test_project_momentum_trend() {
+        let project = Project::new("demo".to_string(), "/tmp/demo".to_string());
+        let logs = vec![
+            log(&project.id, "2024-01-22", LogCategory::Feature),
+            log(&project.id, "2024-01-29", LogCategory::Feature),
+            log(&project.id, "2024-01-30", LogCategory::Feature),
+            log(&project.id, "2024-01-31", LogCategory::Feature),
+        ];
+
+        let momentum = project_momentum(&[project], &logs, d("2024-01-15"), d("2024-02-04"));
+        assert_eq!(momentum[0].weekly_activity, vec![0, 1, 3]);
+        assert_eq!(momentum[0].trend, MomentumTrend::Rising);
+    }
+}
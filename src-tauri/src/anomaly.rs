@@ -0,0 +1,440 @@
+// Anomaly module - deterministic rules over stored history and today's diff
+use std::collections::HashSet;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use crate::evidence::{Evidence, Evidenced};
+use crate::i18n::Locale;
+use crate::models::{parse_date, AnomalySettings, DailyLog, GitDiffResult};
+
+/// Lockfiles whose churn is rarely meaningful work
+const LOCKFILES: &[&str] = &[
+    "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "bun.lockb", "Cargo.lock",
+    "Gemfile.lock", "poetry.lock", "Pipfile.lock", "composer.lock", "go.sum", "Podfile.lock",
+];
+
+/// Extensions treated as binary assets
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "ico", "icns", "bmp", "tiff", "psd", "pdf",
+    "zip", "gz", "tar", "7z", "rar", "dmg", "exe", "dll", "so", "dylib", "a", "jar", "wasm",
+    "woff", "woff2", "ttf", "otf", "mp3", "mp4", "mov", "wav", "sqlite", "db", "bin",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    LowActivity,
+    DeletionHeavy,
+    LateNightBurst,
+    CategorySwitching,
+    DependencyChurn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub description: String,
+    pub evidence: Vec<Evidence>,
+    /// Period the finding covers; the same kind is raised once per window
+    pub window: String,
+}
+
+impl Anomaly {
+    fn new(kind: AnomalyKind, description: String, window: String) -> Self {
+        Anomaly {
+            kind,
+            description,
+            evidence: Vec::new(),
+            window,
+        }
+    }
+
+    pub fn kind_str(&self) -> String {
+        serde_json::to_string(&self.kind).unwrap().trim_matches('"').to_string()
+    }
+}
+
+impl Evidenced for Anomaly {
+    fn evidence(&self) -> &[Evidence] {
+        &self.evidence
+    }
+
+    fn evidence_mut(&mut self) -> &mut Vec<Evidence> {
+        &mut self.evidence
+    }
+}
+
+/// Everything the rules look at for one project
+pub struct AnomalyInput<'a> {
+    pub today: NaiveDate,
+    pub logs: &'a [DailyLog],
+    pub today_diff: Option<&'a GitDiffResult>,
+    pub commit_times: &'a [DateTime<FixedOffset>],
+    /// Language the descriptions and evidence are written in
+    pub locale: Locale,
+}
+
+/// Run every rule and collect the findings
+pub fn detect(input: &AnomalyInput, settings: &AnomalySettings) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    found.extend(low_activity(input, settings));
+    if let Some(diff) = input.today_diff {
+        found.extend(deletion_heavy(diff, input.today, settings, input.locale));
+        found.extend(dependency_churn(diff, input.today, settings, input.locale));
+    }
+    found.extend(late_night_burst(input.commit_times, input.today, settings, input.locale));
+    found.extend(category_switching(input.logs, settings, input.locale));
+    found
+}
+
+/// Active days in the last seven days compared with the preceding weeks
+fn low_activity(input: &AnomalyInput, settings: &AnomalySettings) -> Option<Anomaly> {
+    let weeks = settings.baseline_weeks as i64;
+    if weeks == 0 {
+        return None;
+    }
+
+    let dates: HashSet<NaiveDate> = input.logs.iter().filter_map(|l| parse_date(&l.date).ok()).collect();
+    let active_in = |end: NaiveDate| {
+        (0..7).filter(|i| dates.contains(&(end - Duration::days(*i)))).count() as f64
+    };
+
+    let current = active_in(input.today);
+    let baseline: Vec<f64> = (1..=weeks)
+        .map(|w| active_in(input.today - Duration::days(7 * w)))
+        .collect();
+
+    // Need a history that is mostly active before calling a quiet week unusual
+    let active_weeks = baseline.iter().filter(|v| **v > 0.0).count() as i64;
+    if active_weeks * 2 < weeks {
+        return None;
+    }
+
+    let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+    let std_dev = (baseline.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / baseline.len() as f64).sqrt();
+
+    if mean < 2.0 || current > mean * settings.low_activity_ratio {
+        return None;
+    }
+
+    let window = (input.today - Duration::days(6)).format("%Y-%m-%d").to_string();
+    let (locale, mean) = (input.locale, format!("{:.1}", mean));
+    Some(
+        Anomaly::new(
+            AnomalyKind::LowActivity,
+            locale.t("anomaly.low_activity", &[("current", &current.to_string()), ("mean", &mean)]),
+            window,
+        )
+        .with(locale.t("anomaly.low_activity.active_days", &[]), current)
+        .with(locale.t("anomaly.low_activity.baseline_mean", &[]), locale.t("anomaly.low_activity.per_week", &[("days", &mean)]))
+        .with(locale.t("anomaly.low_activity.baseline_std_dev", &[]), format!("{:.1}", std_dev))
+        .with(locale.t("anomaly.low_activity.baseline_weeks", &[]), weeks),
+    )
+}
+
+/// Days where far more lines were removed than added
+fn deletion_heavy(diff: &GitDiffResult, today: NaiveDate, settings: &AnomalySettings, locale: Locale) -> Option<Anomaly> {
+    let deletions = diff.total_deletions.max(0) as f64;
+    let additions = diff.total_additions.max(0) as f64;
+
+    if deletions < settings.min_deleted_lines as f64 || deletions < additions.max(1.0) * settings.deletion_ratio {
+        return None;
+    }
+
+    let mut top: Vec<_> = diff.files.iter().filter(|f| f.deletions > 0).collect();
+    top.sort_by_key(|f| std::cmp::Reverse(f.deletions));
+    let top_files = top.iter().take(5).map(|f| format!("{} (-{})", f.path, f.deletions)).collect::<Vec<_>>();
+
+    Some(
+        Anomaly::new(
+            AnomalyKind::DeletionHeavy,
+            locale.t("anomaly.deletion_heavy", &[
+                ("deletions", &diff.total_deletions.to_string()),
+                ("additions", &diff.total_additions.to_string()),
+            ]),
+            today.format("%Y-%m-%d").to_string(),
+        )
+        .with(locale.t("anomaly.deletion_heavy.additions", &[]), diff.total_additions)
+        .with(locale.t("anomaly.deletion_heavy.deletions", &[]), diff.total_deletions)
+        .with(locale.t("anomaly.deletion_heavy.top_files", &[]), top_files.join(", ")),
+    )
+}
+
+fn is_late_night(hour: u32, settings: &AnomalySettings) -> bool {
+    let (start, end) = (settings.late_night_start_hour, settings.late_night_end_hour);
+    if start <= end {
+        hour >= start && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// Several commits inside the late-night window during the last day. Dated by the latest
+/// late commit, so a burst still in range on the following day is not raised again.
+fn late_night_burst(
+    commit_times: &[DateTime<FixedOffset>],
+    today: NaiveDate,
+    settings: &AnomalySettings,
+    locale: Locale,
+) -> Option<Anomaly> {
+    let mut late: Vec<_> = commit_times
+        .iter()
+        .map(|t| t.with_timezone(&Local))
+        .filter(|t| {
+            let date = t.date_naive();
+            (date == today || date == today - Duration::days(1)) && is_late_night(t.hour(), settings)
+        })
+        .collect();
+
+    if (late.len() as u32) < settings.late_night_min_commits.max(1) {
+        return None;
+    }
+
+    late.sort();
+    let window = late.last().unwrap().date_naive().format("%Y-%m-%d").to_string();
+    Some(
+        Anomaly::new(
+            AnomalyKind::LateNightBurst,
+            locale.t("anomaly.late_night", &[("count", &late.len().to_string())]),
+            window,
+        )
+        .with(locale.t("anomaly.late_night.commits", &[]), late.len())
+        .with(
+            locale.t("anomaly.late_night.window", &[]),
+            format!("{:02}:00–{:02}:00", settings.late_night_start_hour, settings.late_night_end_hour),
+        )
+        .with(locale.t("anomaly.late_night.first", &[]), late.first().unwrap().format("%Y-%m-%d %H:%M"))
+        .with(locale.t("anomaly.late_night.last", &[]), late.last().unwrap().format("%Y-%m-%d %H:%M")),
+    )
+}
+
+/// The recent logs jump between categories almost every day
+fn category_switching(logs: &[DailyLog], settings: &AnomalySettings, locale: Locale) -> Option<Anomaly> {
+    let window = settings.category_switch_window as usize;
+    if window < 3 {
+        return None;
+    }
+
+    let mut recent: Vec<&DailyLog> = logs.iter().collect();
+    recent.sort_by(|a, b| b.date.cmp(&a.date));
+    recent.truncate(window);
+    if recent.len() < window {
+        return None;
+    }
+    recent.reverse();
+
+    let categories: Vec<String> = recent.iter().map(|l| l.effective_category()).collect();
+    let switches = categories.windows(2).filter(|w| w[0] != w[1]).count();
+    let distinct = categories.iter().collect::<HashSet<_>>().len();
+
+    if distinct < 4 || (switches as f64) < (window - 1) as f64 * 0.8 {
+        return None;
+    }
+
+    Some(
+        Anomaly::new(
+            AnomalyKind::CategorySwitching,
+            locale.t("anomaly.category_switching", &[
+                ("window", &window.to_string()),
+                ("switches", &switches.to_string()),
+            ]),
+            recent.last().unwrap().date.clone(),
+        )
+        .with(locale.t("anomaly.category_switching.sequence", &[]), categories.join(" → "))
+        .with(locale.t("anomaly.category_switching.distinct", &[]), distinct),
+    )
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn is_binary(path: &str) -> bool {
+    file_name(path)
+        .rsplit_once('.')
+        .map(|(_, ext)| BINARY_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Huge lockfile rewrites or many binary files changed in one day
+fn dependency_churn(diff: &GitDiffResult, today: NaiveDate, settings: &AnomalySettings, locale: Locale) -> Option<Anomaly> {
+    let lockfiles: Vec<_> = diff.files.iter().filter(|f| LOCKFILES.contains(&file_name(&f.path))).collect();
+    let lock_lines: i64 = lockfiles.iter().map(|f| (f.additions + f.deletions) as i64).sum();
+    let binaries: Vec<_> = diff.files.iter().filter(|f| is_binary(&f.path)).collect();
+
+    let lock_hit = lock_lines >= settings.lockfile_churn_lines as i64;
+    let binary_hit = binaries.len() as u32 >= settings.binary_files.max(1);
+    if !lock_hit && !binary_hit {
+        return None;
+    }
+
+    let mut anomaly = Anomaly::new(
+        AnomalyKind::DependencyChurn,
+        if lock_hit {
+            locale.t("anomaly.dependency_churn.lockfile", &[("lines", &lock_lines.to_string())])
+        } else {
+            locale.t("anomaly.dependency_churn.binary", &[("count", &binaries.len().to_string())])
+        },
+        today.format("%Y-%m-%d").to_string(),
+    );
+
+    if !lockfiles.is_empty() {
+        anomaly = anomaly
+            .with(locale.t("anomaly.dependency_churn.lockfile_lines", &[]), lock_lines)
+            .with(locale.t("anomaly.dependency_churn.lockfiles", &[]), lockfiles.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", "));
+    }
+    if !binaries.is_empty() {
+        anomaly = anomaly
+            .with(locale.t("anomaly.dependency_churn.binary_count", &[]), binaries.len())
+            .with(locale.t("anomaly.dependency_churn.binaries", &[]), binaries.iter().take(10).map(|f| f.path.as_str()).collect::<Vec<_>>().join(", "));
+    }
+
+    Some(anomaly)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use crate::models::{FileChange, LogCategory};
+
+    fn d(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    fn log(date: NaiveDate, category: LogCategory) -> DailyLog {
+        DailyLog {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: "p1".to_string(),
+            date: date.format("%Y-%m-%d").to_string(),
+            summary: String::new(),
+            category,
+            files_changed: vec![],
+            ai_classification: None,
+            user_override: None,
+            created_at: Utc::now(),
+            prompt_version: None,
+            summary_by_user: false,
+        }
+    }
+
+    fn diff(files: Vec<FileChange>) -> GitDiffResult {
+        GitDiffResult {
+            project_id: "p1".to_string(),
+            date: "2024-03-01".to_string(),
+            total_additions: files.iter().map(|f| f.additions).sum(),
+            total_deletions: files.iter().map(|f| f.deletions).sum(),
+            files,
+        }
+    }
+
+    fn file(path: &str, additions: i32, deletions: i32) -> FileChange {
+        FileChange { path: path.to_string(), additions, deletions }
+    }
+
+    #[test]
+    fn test_low_activity_against_baseline() {
+        let today = d("2024-03-01");
+        // Four active days a week for eight weeks, nothing this week
+        let logs: Vec<DailyLog> = (7..63)
+            .filter(|i| i % 7 < 4)
+            .map(|i| log(today - Duration::days(i), LogCategory::Feature))
+            .collect();
+
+        let input = AnomalyInput { today, logs: &logs, today_diff: None, commit_times: &[], locale: Locale::default() };
+        let found = low_activity(&input, &AnomalySettings::default()).unwrap();
+        assert_eq!(found.kind, AnomalyKind::LowActivity);
+
+        // A sparse history is not a baseline
+        let sparse = vec![log(today - Duration::days(20), LogCategory::Feature)];
+        let input = AnomalyInput { today, logs: &sparse, today_diff: None, commit_times: &[], locale: Locale::default() };
+        assert!(low_activity(&input, &AnomalySettings::default()).is_none());
+    }
+
+    #[test]
+    fn test_deletion_heavy() {
+        let settings = AnomalySettings::default();
+        let heavy = diff(vec![file("src/old.rs", 10, 800)]);
+        assert!(deletion_heavy(&heavy, d("2024-03-01"), &settings, Locale::default()).is_some());
+
+        let balanced = diff(vec![file("src/new.rs", 500, 400)]);
+        assert!(deletion_heavy(&balanced, d("2024-03-01"), &settings, Locale::default()).is_none());
+    }
+
+    #[test]
+    fn test_late_night_burst() {
+        let settings = AnomalySettings::default();
+        let today = d("2024-03-01");
+        let at = |h: u32, m: u32| {
+            Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap().fixed_offset()
+        };
+
+        let burst = vec![at(1, 0), at(1, 30), at(2, 15), at(14, 0)];
+        assert_eq!(late_night_burst(&burst, today, &settings, Locale::default()).unwrap().window, "2024-03-01");
+
+        // Seen again the next day, the same burst keeps its date
+        let next_day = late_night_burst(&burst, d("2024-03-02"), &settings, Locale::default()).unwrap();
+        assert_eq!(next_day.window, "2024-03-01");
+
+        let daytime = vec![at(10, 0), at(11, 0), at(15, 0), at(2, 0)];
+        assert!(late_night_burst(&daytime, today, &settings, Locale::default()).is_none());
+    }
+
+    #[test]
+    fn test_is_late_night_wraps_midnight() {
+        let settings = AnomalySettings {
+            late_night_start_hour: 23,
+            late_night_end_hour: 4,
+            ..AnomalySettings::default()
+        };
+        assert!(is_late_night(23, &settings));
+        assert!(is_late_night(2, &settings));
+        assert!(!is_late_night(12, &settings));
+    }
+
+    #[test]
+    fn test_category_switching() {
+        let settings = AnomalySettings::default();
+        let today = d("2024-03-07");
+        let cycle = [
+            LogCategory::Feature, LogCategory::Bugfix, LogCategory::Ui, LogCategory::Docs,
+            LogCategory::Feature, LogCategory::Test, LogCategory::Refactor,
+        ];
+        let switching: Vec<DailyLog> = cycle.iter().enumerate()
+            .map(|(i, c)| log(today - Duration::days(i as i64), c.clone()))
+            .collect();
+        assert!(category_switching(&switching, &settings, Locale::default()).is_some());
+
+        let focused: Vec<DailyLog> = (0..7)
+            .map(|i| log(today - Duration::days(i), LogCategory::Feature))
+            .collect();
+        assert!(category_switching(&focused, &settings, Locale::default()).is_none());
+    }
+
+    #[test]
+    fn test_dependency_churn() {
+        let settings = AnomalySettings::default();
+        let lock = diff(vec![file("frontend/package-lock.json", 2500, 1800), file("src/a.ts", 3, 1)]);
+        let found = dependency_churn(&lock, d("2024-03-01"), &settings, Locale::default()).unwrap();
+        assert_eq!(found.kind, AnomalyKind::DependencyChurn);
+
+        let images = diff((0..12).map(|i| file(&format!("assets/img{}.PNG", i), 0, 0)).collect());
+        assert!(dependency_churn(&images, d("2024-03-01"), &settings, Locale::default()).is_some());
+
+        let code = diff(vec![file("src/main.rs", 3000, 100)]);
+        assert!(dependency_churn(&code, d("2024-03-01"), &settings, Locale::default()).is_none());
+    }
+
+    #[test]
+    fn test_detect_runs_all_rules() {
+        let today = d("2024-03-01");
+        let heavy = diff(vec![file("src/old.rs", 0, 900)]);
+        let commits = vec![Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap().fixed_offset()];
+        let input = AnomalyInput { today, logs: &[], today_diff: Some(&heavy), commit_times: &commits, locale: Locale::new("en") };
+
+        let found = detect(&input, &AnomalySettings::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind_str(), "deletion_heavy");
+        assert_eq!(found[0].description, "Deleted 900 lines today but added only 0");
+        assert!(found[0].evidence_text(input.locale).starts_with("Lines added: 0\n"));
+    }
+}
@@ -0,0 +1,342 @@
+// Backfill module - walks a repository's history and writes one daily log per active day
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use chrono::Utc;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::ai_agent::{AiAgent, DayAnalysis, MAX_CORRECTION_EXAMPLES};
+use crate::classifier;
+use crate::db::Database;
+use crate::grok::GrokClient;
+use crate::i18n::Locale;
+use crate::models::{parse_date, BackfillJob, BackfillStatus, DailyLog, GitDiffResult, Project};
+use crate::scanner::GitScanner;
+use crate::scheduler::load_settings;
+use crate::usage;
+
+/// Days handled per step; with AI enabled, each step is one request
+const BATCH_DAYS: usize = 7;
+
+/// Minimum time between AI requests, so a long history doesn't hit rate limits
+const AI_REQUEST_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runs history backfills in the background, at most one per project
+#[derive(Clone)]
+pub struct Backfill {
+    /// Running jobs by project; a status set here asks the job to stop with it
+    running: Arc<Mutex<HashMap<String, Option<BackfillStatus>>>>,
+}
+
+impl Backfill {
+    pub fn new() -> Self {
+        Backfill {
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Find the active days in `since..=until` and start writing their logs
+    pub async fn start(
+        &self,
+        app_handle: &AppHandle,
+        project_id: &str,
+        since: &str,
+        until: Option<&str>,
+        use_ai: bool,
+    ) -> Result<BackfillJob, String> {
+        if self.running.lock().await.contains_key(project_id) {
+            return Err("A backfill is already running for this project".to_string());
+        }
+
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+
+        let project = db.get_projects()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|p| p.id == project_id)
+            .ok_or_else(|| format!("Project not found: {}", project_id))?;
+
+        let today = chrono::Local::now().date_naive();
+        let since = parse_date(since)?;
+        let until = match until {
+            Some(u) => parse_date(u)?.min(today),
+            None => today,
+        };
+        if since > until {
+            return Err("Backfill start date is after its end date".to_string());
+        }
+
+        let days = scanner.get_active_days(
+            std::path::Path::new(&project.path),
+            &format!("{} 00:00:00", since.format("%Y-%m-%d")),
+            &format!("{} 23:59:59", until.format("%Y-%m-%d")),
+        )?;
+
+        let job = BackfillJob {
+            project_id: project.id.clone(),
+            since: since.format("%Y-%m-%d").to_string(),
+            until: until.format("%Y-%m-%d").to_string(),
+            use_ai,
+            status: BackfillStatus::Running,
+            days: days.iter().map(|d| d.format("%Y-%m-%d").to_string()).collect(),
+            processed: 0,
+            logs_created: 0,
+            error: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        db.save_backfill_job(&job).map_err(|e| e.to_string())?;
+        log::info!("Starting backfill of {} over {} active days", project.name, job.days.len());
+
+        self.spawn(app_handle.clone(), project, job.clone()).await;
+        Ok(job)
+    }
+
+    /// Ask a running backfill to stop after its current batch; it can be resumed later
+    pub async fn pause(&self, project_id: &str) -> Result<(), String> {
+        self.request_stop(project_id, BackfillStatus::Paused).await
+    }
+
+    /// Continue a paused, failed or interrupted backfill from where it stopped
+    pub async fn resume(&self, app_handle: &AppHandle, project_id: &str) -> Result<BackfillJob, String> {
+        if self.running.lock().await.contains_key(project_id) {
+            return Err("Backfill is already running".to_string());
+        }
+
+        let db = app_handle.state::<Database>();
+        let mut job = db.get_backfill_job(project_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "No backfill to resume".to_string())?;
+
+        // A running job that isn't in the map was interrupted by the app quitting
+        if !matches!(job.status, BackfillStatus::Paused | BackfillStatus::Running | BackfillStatus::Failed) {
+            return Err("Backfill has already finished".to_string());
+        }
+
+        let project = db.get_projects()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|p| p.id == project_id)
+            .ok_or_else(|| format!("Project not found: {}", project_id))?;
+
+        job.status = BackfillStatus::Running;
+        job.error = None;
+        job.updated_at = Utc::now();
+        db.save_backfill_job(&job).map_err(|e| e.to_string())?;
+
+        self.spawn(app_handle.clone(), project, job.clone()).await;
+        Ok(job)
+    }
+
+    /// Stop a backfill for good. Logs already written are kept.
+    pub async fn cancel(&self, app_handle: &AppHandle, project_id: &str) -> Result<(), String> {
+        if self.request_stop(project_id, BackfillStatus::Cancelled).await.is_ok() {
+            return Ok(());
+        }
+
+        let db = app_handle.state::<Database>();
+        let mut job = db.get_backfill_job(project_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "No backfill to cancel".to_string())?;
+        if matches!(job.status, BackfillStatus::Completed | BackfillStatus::Cancelled) {
+            return Err("Backfill has already finished".to_string());
+        }
+
+        job.status = BackfillStatus::Cancelled;
+        job.updated_at = Utc::now();
+        db.save_backfill_job(&job).map_err(|e| e.to_string())?;
+        let _ = app_handle.emit("backfill:progress", &job);
+        Ok(())
+    }
+
+    /// Restart jobs that were still running when the app last quit
+    pub async fn resume_interrupted(&self, app_handle: &AppHandle) {
+        let jobs = match app_handle.state::<Database>().get_backfill_jobs() {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                log::error!("Failed to load backfill jobs: {}", e);
+                return;
+            }
+        };
+
+        for job in jobs.into_iter().filter(|j| j.status == BackfillStatus::Running) {
+            if let Err(e) = self.resume(app_handle, &job.project_id).await {
+                log::warn!("Failed to resume backfill for {}: {}", job.project_id, e);
+            }
+        }
+    }
+
+    async fn request_stop(&self, project_id: &str, status: BackfillStatus) -> Result<(), String> {
+        match self.running.lock().await.get_mut(project_id) {
+            Some(request) => {
+                *request = Some(status);
+                Ok(())
+            }
+            None => Err("Backfill is not running".to_string()),
+        }
+    }
+
+    async fn spawn(&self, app_handle: AppHandle, project: Project, job: BackfillJob) {
+        self.running.lock().await.insert(project.id.clone(), None);
+
+        let backfill = self.clone();
+        tokio::spawn(async move {
+            backfill.run(app_handle, project, job).await;
+        });
+    }
+
+    async fn run(&self, app_handle: AppHandle, project: Project, mut job: BackfillJob) {
+        let db = app_handle.state::<Database>();
+        let mut last_ai_request: Option<Instant> = None;
+
+        while (job.processed as usize) < job.days.len() {
+            if let Some(Some(status)) = self.running.lock().await.get(&project.id).cloned() {
+                job.status = status;
+                break;
+            }
+
+            let start = job.processed as usize;
+            let end = (start + BATCH_DAYS).min(job.days.len());
+            let batch = job.days[start..end].to_vec();
+
+            match self.process_batch(&app_handle, &project, &batch, job.use_ai, &mut last_ai_request).await {
+                Ok(created) => {
+                    job.processed = end as u32;
+                    job.logs_created += created;
+                    if created > 0 {
+                        let _ = app_handle.emit("data:daily-log-updated", ());
+                    }
+                }
+                Err(e) => {
+                    log::error!("Backfill of {} failed: {}", project.name, e);
+                    job.status = BackfillStatus::Failed;
+                    job.error = Some(e);
+                    break;
+                }
+            }
+
+            job.updated_at = Utc::now();
+            if let Err(e) = db.save_backfill_job(&job) {
+                log::error!("Failed to save backfill progress: {}", e);
+            }
+            let _ = app_handle.emit("backfill:progress", &job);
+        }
+
+        if job.status == BackfillStatus::Running {
+            job.status = BackfillStatus::Completed;
+            log::info!("Backfill of {} complete: {} logs created", project.name, job.logs_created);
+        }
+
+        job.updated_at = Utc::now();
+        if let Err(e) = db.save_backfill_job(&job) {
+            log::error!("Failed to save backfill job: {}", e);
+        }
+
+        self.running.lock().await.remove(&project.id);
+        let _ = app_handle.emit("backfill:progress", &job);
+        if job.status == BackfillStatus::Completed {
+            let _ = app_handle.emit("backfill:complete", &job);
+        }
+    }
+
+    /// Write logs for a batch of days, skipping days that already have one.
+    /// Returns the number of logs created.
+    async fn process_batch(
+        &self,
+        app_handle: &AppHandle,
+        project: &Project,
+        days: &[String],
+        use_ai: bool,
+        last_ai_request: &mut Option<Instant>,
+    ) -> Result<u32, String> {
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+        let grok = app_handle.state::<GrokClient>();
+        let ai_agent = app_handle.state::<AiAgent>();
+        let path = std::path::Path::new(&project.path);
+
+        let mut entries: Vec<(GitDiffResult, Vec<String>)> = Vec::new();
+        for day in days {
+            if db.get_daily_log_for_date(&project.id, day).map_err(|e| e.to_string())?.is_some() {
+                continue;
+            }
+
+            let since = format!("{} 00:00:00", day);
+            let until = format!("{} 23:59:59", day);
+            let mut diff = scanner.get_diff_for_range(path, &since, Some(&until))?;
+            if diff.files.is_empty() {
+                continue;
+            }
+            diff.project_id = project.id.clone();
+            diff.date = day.clone();
+
+            let subjects = scanner.get_commit_subjects(path, &since, Some(&until))?;
+            entries.push((diff, subjects));
+        }
+
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut analyses: HashMap<String, DayAnalysis> = HashMap::new();
+        let settings = load_settings(&db);
+        let locale = Locale::new(&settings.language);
+        let use_ai = use_ai && !usage::over_budget(&db, &settings.ai);
+        if use_ai && grok.has_api_key().await && !grok.is_paused() {
+            if let Some(last) = *last_ai_request {
+                tokio::time::sleep_until(last + AI_REQUEST_INTERVAL).await;
+            }
+            *last_ai_request = Some(Instant::now());
+
+            let corrections = db.get_corrected_logs(&project.id, MAX_CORRECTION_EXAMPLES).map_err(|e| e.to_string())?;
+            match ai_agent.analyze_days_batch(&grok, project, &entries, &corrections).await {
+                Ok(results) => {
+                    analyses = results.into_iter().map(|a| (a.date.clone(), a)).collect();
+                }
+                Err(e) => log::warn!("Batch analysis failed for {}, using offline summaries: {}", project.name, e),
+            }
+        }
+
+        let mut created = 0;
+        for (diff, subjects) in entries {
+            let (summary, category, ai_classification, prompt_version) = match analyses.remove(&diff.date) {
+                Some(a) => (a.summary, classifier::parse_category(&a.category), Some(a.category), a.prompt_version),
+                None => (
+                    classifier::summarize_offline(locale, &subjects, &diff),
+                    classifier::classify_offline(&subjects, &diff.files),
+                    None,
+                    None,
+                ),
+            };
+
+            let log = DailyLog {
+                id: uuid::Uuid::new_v4().to_string(),
+                project_id: project.id.clone(),
+                date: diff.date.clone(),
+                summary,
+                category,
+                files_changed: diff.files,
+                ai_classification,
+                user_override: None,
+                created_at: Utc::now(),
+                prompt_version,
+                summary_by_user: false,
+            };
+
+            // A scan may have written the day since it was checked; its log is kept
+            if db.create_daily_log(&log).map_err(|e| e.to_string())? {
+                created += 1;
+            }
+        }
+
+        Ok(created)
+    }
+}
+
+impl Default for Backfill {
+    fn default() -> Self {
+        Self::new()
+    }
+}
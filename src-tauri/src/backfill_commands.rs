@@ -0,0 +1,52 @@
+use tauri::State;
+use crate::backfill::Backfill;
+use crate::db::Database;
+use crate::models::BackfillJob;
+
+/// Start backfilling daily logs from a project's git history
+#[tauri::command]
+pub async fn start_backfill(
+    backfill: State<'_, Backfill>,
+    app_handle: tauri::AppHandle,
+    project_id: String,
+    since: String,
+    until: Option<String>,
+    use_ai: bool,
+) -> Result<BackfillJob, String> {
+    backfill.start(&app_handle, &project_id, &since, until.as_deref(), use_ai).await
+}
+
+/// Pause a running backfill after its current batch
+#[tauri::command]
+pub async fn pause_backfill(
+    backfill: State<'_, Backfill>,
+    project_id: String,
+) -> Result<(), String> {
+    backfill.pause(&project_id).await
+}
+
+/// Resume a paused backfill
+#[tauri::command]
+pub async fn resume_backfill(
+    backfill: State<'_, Backfill>,
+    app_handle: tauri::AppHandle,
+    project_id: String,
+) -> Result<BackfillJob, String> {
+    backfill.resume(&app_handle, &project_id).await
+}
+
+/// Cancel a backfill, keeping the logs it already wrote
+#[tauri::command]
+pub async fn cancel_backfill(
+    backfill: State<'_, Backfill>,
+    app_handle: tauri::AppHandle,
+    project_id: String,
+) -> Result<(), String> {
+    backfill.cancel(&app_handle, &project_id).await
+}
+
+/// Get the latest backfill job for every project that has one
+#[tauri::command]
+pub fn get_backfill_jobs(db: State<Database>) -> Result<Vec<BackfillJob>, String> {
+    db.get_backfill_jobs().map_err(|e| e.to_string())
+}
@@ -0,0 +1,222 @@
+// Classifier module - offline classification and summaries when AI analysis isn't available
+use std::collections::HashMap;
+use crate::i18n::Locale;
+use crate::models::{FileChange, GitDiffResult, LogCategory};
+
+/// Commit subjects quoted in an offline summary
+const MAX_SUMMARY_SUBJECTS: usize = 3;
+
+/// A file kind must account for more than this share of changed lines to decide the category
+const PATH_MAJORITY: f64 = 0.5;
+
+/// Tie-break order when several categories get the same number of votes
+const PRIORITY: [LogCategory; 7] = [
+    LogCategory::Feature,
+    LogCategory::Bugfix,
+    LogCategory::Refactor,
+    LogCategory::Ui,
+    LogCategory::Test,
+    LogCategory::Docs,
+    LogCategory::Chore,
+];
+
+/// Category names as stored in the database and used in AI prompts
+pub const LOG_CATEGORY_NAMES: [&str; 8] = ["feature", "bugfix", "refactor", "ui", "docs", "test", "chore", "other"];
+
+/// Map a category name, as returned by AI analysis, onto a log category
+pub fn parse_category(category: &str) -> LogCategory {
+    match category.trim().to_lowercase().as_str() {
+        "feature" => LogCategory::Feature,
+        "bugfix" => LogCategory::Bugfix,
+        "refactor" => LogCategory::Refactor,
+        "ui" => LogCategory::Ui,
+        "docs" => LogCategory::Docs,
+        "test" => LogCategory::Test,
+        "chore" => LogCategory::Chore,
+        _ => LogCategory::Other,
+    }
+}
+
+/// Category implied by a commit subject: a conventional-commit prefix, else a leading keyword
+fn category_from_subject(subject: &str) -> Option<LogCategory> {
+    let lower = subject.trim().to_lowercase();
+
+    // `feat(scope)!: ...` -> `feat`
+    if let Some((prefix, _)) = lower.split_once(':') {
+        let kind = prefix.split(['(', '!']).next().unwrap_or("").trim();
+        let category = match kind {
+            "feat" | "feature" => Some(LogCategory::Feature),
+            "fix" | "bugfix" | "hotfix" => Some(LogCategory::Bugfix),
+            "refactor" | "perf" => Some(LogCategory::Refactor),
+            "docs" | "doc" => Some(LogCategory::Docs),
+            "test" | "tests" => Some(LogCategory::Test),
+            "ui" | "style" => Some(LogCategory::Ui),
+            "chore" | "build" | "ci" | "deps" | "release" => Some(LogCategory::Chore),
+            _ => None,
+        };
+        if category.is_some() {
+            return category;
+        }
+    }
+
+    let first_word = lower.split_whitespace().next().unwrap_or("");
+    match first_word {
+        "add" | "adds" | "added" | "implement" | "implements" | "implemented" | "introduce" | "support" => Some(LogCategory::Feature),
+        "fix" | "fixes" | "fixed" | "resolve" | "resolves" | "resolved" => Some(LogCategory::Bugfix),
+        "refactor" | "refactors" | "refactored" | "cleanup" | "simplify" | "rename" | "extract" => Some(LogCategory::Refactor),
+        "bump" | "upgrade" | "update" if lower.contains("dependenc") || lower.contains("version") => Some(LogCategory::Chore),
+        _ => None,
+    }
+}
+
+/// Category implied by a file path
+fn category_from_path(path: &str) -> Option<LogCategory> {
+    let lower = path.to_lowercase();
+    let file_name = lower.rsplit('/').next().unwrap_or(&lower);
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+
+    if lower.starts_with("docs/") || lower.contains("/docs/") || matches!(extension, "md" | "mdx" | "rst" | "txt") {
+        return Some(LogCategory::Docs);
+    }
+    if lower.starts_with("test") || lower.contains("/test") || lower.contains("__tests__")
+        || file_name.contains(".test.") || file_name.contains(".spec.") || file_name.contains("_test.")
+    {
+        return Some(LogCategory::Test);
+    }
+    if matches!(extension, "css" | "scss" | "sass" | "less" | "html" | "vue" | "svelte" | "tsx" | "jsx") {
+        return Some(LogCategory::Ui);
+    }
+    if file_name.ends_with(".lock") || file_name.ends_with("-lock.json") || file_name.ends_with("lock.yaml")
+        || file_name == "dockerfile" || lower.starts_with(".github/")
+        || matches!(extension, "yml" | "yaml" | "toml" | "ini" | "cfg")
+    {
+        return Some(LogCategory::Chore);
+    }
+    None
+}
+
+fn pick_by_priority(counts: &HashMap<LogCategory, u64>) -> Option<(LogCategory, u64)> {
+    PRIORITY
+        .iter()
+        .filter_map(|c| counts.get(c).map(|n| (c.clone(), *n)))
+        .fold(None, |best, (c, n)| match best {
+            Some((_, m)) if m >= n => best,
+            _ => Some((c, n)),
+        })
+}
+
+/// Classify a day's work from its commit subjects, falling back to which kinds of
+/// files saw most of the changed lines
+pub fn classify_offline(subjects: &[String], files: &[FileChange]) -> LogCategory {
+    let mut votes: HashMap<LogCategory, u64> = HashMap::new();
+    for category in subjects.iter().filter_map(|s| category_from_subject(s)) {
+        *votes.entry(category).or_default() += 1;
+    }
+    if let Some((category, _)) = pick_by_priority(&votes) {
+        return category;
+    }
+
+    let mut lines: HashMap<LogCategory, u64> = HashMap::new();
+    let mut total = 0u64;
+    for file in files {
+        let changed = (file.additions + file.deletions).max(0) as u64;
+        total += changed;
+        if let Some(category) = category_from_path(&file.path) {
+            *lines.entry(category).or_default() += changed;
+        }
+    }
+
+    match pick_by_priority(&lines) {
+        Some((category, n)) if total > 0 && n as f64 / total as f64 > PATH_MAJORITY => category,
+        _ => LogCategory::Other,
+    }
+}
+
+/// One-line summary built from commit subjects and diff totals
+pub fn summarize_offline(locale: Locale, subjects: &[String], diff: &GitDiffResult) -> String {
+    let additions = diff.total_additions.to_string();
+    let deletions = diff.total_deletions.to_string();
+
+    if subjects.is_empty() {
+        return locale.t("offline.files_changed", &[
+            ("files", &diff.files.len().to_string()),
+            ("additions", &additions),
+            ("deletions", &deletions),
+        ]);
+    }
+
+    let separator = locale.t("list.clause_separator", &[]);
+    let mut quoted = subjects.iter().take(MAX_SUMMARY_SUBJECTS).cloned().collect::<Vec<_>>().join(&separator);
+    if subjects.len() > MAX_SUMMARY_SUBJECTS {
+        quoted.push('…');
+    }
+
+    locale.t("offline.subjects", &[
+        ("subjects", &quoted),
+        ("commits", &subjects.len().to_string()),
+        ("additions", &additions),
+        ("deletions", &deletions),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subjects(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn file(path: &str, lines: i32) -> FileChange {
+        FileChange {
+            path: path.to_string(),
+            additions: lines,
+            deletions: 0,
+        }
+    }
+
+    #[test]
+    fn test_classify_from_conventional_commits() {
+        let s = subjects(&["fix(api): handle timeout", "fix: null check", "feat: add export"]);
+        assert_eq!(classify_offline(&s, &[]), LogCategory::Bugfix);
+
+        let s = subjects(&["Add login page", "Fix typo"]);
+        // Tie goes to the higher-priority category
+        assert_eq!(classify_offline(&s, &[]), LogCategory::Feature);
+    }
+
+    #[test]
+    fn test_classify_falls_back_to_paths() {
+        let s = subjects(&["wip"]);
+        let files = vec![file("docs/guide.md", 80), file("src/main.rs", 10)];
+        assert_eq!(classify_offline(&s, &files), LogCategory::Docs);
+
+        let files = vec![file("README.md", 10), file("src/main.rs", 80)];
+        assert_eq!(classify_offline(&s, &files), LogCategory::Other);
+
+        let files = vec![file("src/__tests__/app.test.ts", 50)];
+        assert_eq!(classify_offline(&[], &files), LogCategory::Test);
+    }
+
+    #[test]
+    fn test_summarize_offline() {
+        let diff = GitDiffResult {
+            project_id: String::new(),
+            date: "2024-01-15".to_string(),
+            files: vec![file("a.rs", 5)],
+            total_additions: 5,
+            total_deletions: 1,
+        };
+
+        let locale = Locale::default();
+        assert_eq!(summarize_offline(locale, &[], &diff), "1 個檔案變更（+5/-1）");
+        assert_eq!(
+            summarize_offline(locale, &subjects(&["a", "b", "c", "d"]), &diff),
+            "a；b；c…（4 個 commit，+5/-1）"
+        );
+        assert_eq!(
+            summarize_offline(Locale::new("en"), &subjects(&["a", "b"]), &diff),
+            "a; b (2 commits, +5/-1)"
+        );
+    }
+}
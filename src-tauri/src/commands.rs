@@ -1,5 +1,10 @@
 use tauri::{State, Emitter};
 use crate::db::Database;
+use crate::grok::GrokClient;
+use crate::i18n::Locale;
+use crate::inbox;
+use crate::scheduler::load_settings;
+use crate::usage;
 use crate::models::*;
 
 // ============================================
@@ -55,6 +60,60 @@ pub fn get_daily_logs(db: State<Database>, project_id: Option<String>, limit: Op
     db.get_daily_logs(project_id.as_deref(), limit).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn override_log_category(
+    db: State<Database>,
+    app: tauri::AppHandle,
+    log_id: String,
+    category: String,
+) -> Result<DailyLog, String> {
+    edit_log(db, app, log_id, None, Some(category))
+}
+
+/// Edit a log's summary and/or category. A category change is kept as a user
+/// override and recorded as a correction of the AI's label; an edited summary is
+/// kept over later scans of the day.
+#[tauri::command]
+pub fn edit_log(
+    db: State<Database>,
+    app: tauri::AppHandle,
+    log_id: String,
+    summary: Option<String>,
+    category: Option<String>,
+) -> Result<DailyLog, String> {
+    let log = db.get_daily_log(&log_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Daily log not found: {}", log_id))?;
+
+    if let Some(category) = &category {
+        if !crate::classifier::LOG_CATEGORY_NAMES.contains(&category.as_str()) {
+            return Err(format!("Unknown category: {}", category));
+        }
+        db.override_log_category(&log, category).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(summary) = summary.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        db.update_daily_log_summary(&log_id, summary).map_err(|e| e.to_string())?;
+    }
+
+    let _ = app.emit("data:daily-log-updated", ());
+
+    db.get_daily_log(&log_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Daily log not found: {}", log_id))
+}
+
+/// Per-project share of AI categories the user kept
+#[tauri::command]
+pub fn get_classification_accuracy(db: State<Database>) -> Result<Vec<ClassificationAccuracy>, String> {
+    db.get_classification_accuracy().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_daily_log_revisions(db: State<Database>, log_id: String) -> Result<Vec<DailyLogRevision>, String> {
+    db.get_daily_log_revisions(&log_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn create_daily_log(
     db: State<Database>,
@@ -85,9 +144,12 @@ pub fn create_daily_log(
         ai_classification: None,
         user_override: None,
         created_at: chrono::Utc::now(),
+        prompt_version: None,
+        summary_by_user: true,
     };
 
-    db.create_daily_log(&log).map_err(|e| e.to_string())?;
+    // Rewrites any log already recorded for the day, keeping its override
+    let log = db.upsert_daily_log(&log).map_err(|e| e.to_string())?;
 
     // Emit event to notify frontend
     let _ = app.emit("data:daily-log-updated", ());
@@ -268,21 +330,119 @@ pub fn get_inbox_items(db: State<Database>, status: Option<String>) -> Result<Ve
         "pending" => InboxStatus::Pending,
         "answered" => InboxStatus::Answered,
         "skipped" => InboxStatus::Skipped,
+        "snoozed" => InboxStatus::Snoozed,
+        "expired" => InboxStatus::Expired,
         _ => InboxStatus::Pending,
     });
+    inbox::refresh(&db)?;
     db.get_inbox_items(status).map_err(|e| e.to_string())
 }
 
+/// Answer an inbox item, either by choosing one of its suggested actions (`action_id`),
+/// which runs that action, or in free text, which the AI may turn into follow-up actions
 #[tauri::command]
-pub fn answer_inbox_item(db: State<Database>, app: tauri::AppHandle, id: String, answer: String) -> Result<(), String> {
-    db.answer_inbox_item(&id, &answer).map_err(|e| e.to_string())?;
+pub async fn answer_inbox_item(
+    db: State<'_, Database>,
+    grok: State<'_, GrokClient>,
+    app: tauri::AppHandle,
+    id: String,
+    answer: String,
+    action_id: Option<String>,
+) -> Result<inbox::AnswerOutcome, String> {
+    let mut item = db.get_inbox_item(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Inbox item not found: {}", id))?;
+    let settings = load_settings(&db);
+
+    let outcome = match action_id {
+        Some(action_id) => inbox::AnswerOutcome {
+            results: inbox::choose_action(&db, &item, &action_id, Locale::new(&settings.language))?,
+            follow_ups: vec![],
+        },
+        None => {
+            db.answer_inbox_item(&id, &answer).map_err(|e| e.to_string())?;
+
+            let interpret = settings.ai.interpret_answers
+                && !usage::over_budget(&db, &settings.ai)
+                && grok.has_api_key().await;
+            let follow_ups = if interpret {
+                inbox::interpret_answer(&grok, &db, &item, &answer).await
+            } else {
+                vec![]
+            };
+            if !follow_ups.is_empty() {
+                item.detected_actions.extend(follow_ups.iter().cloned());
+                db.update_inbox_detected_actions(&id, &item.detected_actions).map_err(|e| e.to_string())?;
+            }
+            inbox::AnswerOutcome { results: vec![], follow_ups }
+        }
+    };
 
     // Emit event to notify frontend
+    let _ = app.emit("data:inbox-updated", ());
+    if !outcome.results.is_empty() {
+        let _ = app.emit("data:todo-updated", ());
+    }
+
+    Ok(outcome)
+}
+
+#[tauri::command]
+pub fn skip_inbox_item(db: State<Database>, app: tauri::AppHandle, id: String) -> Result<(), String> {
+    db.skip_inbox_item(&id).map_err(|e| e.to_string())?;
+
+    let _ = app.emit("data:inbox-updated", ());
+
+    Ok(())
+}
+
+/// Hide an item from the pending list until `until` (RFC 3339)
+#[tauri::command]
+pub fn snooze_inbox_item(db: State<Database>, app: tauri::AppHandle, id: String, until: String) -> Result<(), String> {
+    let until = chrono::DateTime::parse_from_rfc3339(&until)
+        .map_err(|e| format!("Invalid snooze time \"{}\": {}", until, e))?
+        .with_timezone(&chrono::Utc);
+    if until <= chrono::Utc::now() {
+        return Err("Snooze time must be in the future".to_string());
+    }
+    db.snooze_inbox_item(&id, until).map_err(|e| e.to_string())?;
+
     let _ = app.emit("data:inbox-updated", ());
 
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_inbox_item_priority(db: State<Database>, app: tauri::AppHandle, id: String, priority: String) -> Result<(), String> {
+    let priority = match priority.as_str() {
+        "low" => InboxPriority::Low,
+        "normal" => InboxPriority::Normal,
+        "high" => InboxPriority::High,
+        _ => return Err(format!("Invalid priority: {}", priority)),
+    };
+    db.set_inbox_item_priority(&id, priority).map_err(|e| e.to_string())?;
+
+    let _ = app.emit("data:inbox-updated", ());
+
+    Ok(())
+}
+
+/// Apply several of an inbox item's detected actions at once
+#[tauri::command]
+pub fn apply_inbox_actions(
+    db: State<Database>,
+    app: tauri::AppHandle,
+    item_id: String,
+    action_indices: Vec<usize>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let results = crate::actions::apply_inbox_actions(&db, &item_id, &action_indices)?;
+
+    let _ = app.emit("data:inbox-updated", ());
+    let _ = app.emit("data:todo-updated", ());
+
+    Ok(results)
+}
+
 #[tauri::command]
 pub fn create_inbox_item(
     db: State<Database>,
@@ -311,6 +471,41 @@ pub fn create_inbox_item(
     Ok(item)
 }
 
+// ============================================
+// Weekly Review Commands
+// ============================================
+
+#[tauri::command]
+pub fn get_weekly_reviews(db: State<Database>, limit: Option<i32>) -> Result<Vec<WeeklyReview>, String> {
+    db.get_weekly_reviews(limit.unwrap_or(12)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn answer_weekly_review(
+    db: State<Database>,
+    app: tauri::AppHandle,
+    id: String,
+    answers: Vec<String>,
+) -> Result<(), String> {
+    let review = db.get_weekly_review(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Weekly review not found: {}", id))?;
+
+    db.answer_weekly_review(&id, &answers).map_err(|e| e.to_string())?;
+
+    // Mirror the answers onto the inbox item so it leaves the pending list
+    let answer_text = review.questions.iter()
+        .zip(answers.iter())
+        .map(|(q, a)| format!("{}\n{}", q, a))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    db.answer_inbox_item(&review.inbox_item_id, &answer_text).map_err(|e| e.to_string())?;
+
+    let _ = app.emit("data:inbox-updated", ());
+
+    Ok(())
+}
+
 // ============================================
 // Chat Commands
 // ============================================
@@ -354,9 +549,12 @@ pub fn get_settings(db: State<Database>) -> Result<UserSettings, String> {
 }
 
 #[tauri::command]
-pub fn save_settings(db: State<Database>, settings: UserSettings) -> Result<(), String> {
+pub fn save_settings(db: State<Database>, grok: State<GrokClient>, settings: UserSettings) -> Result<(), String> {
     let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
-    db.set_setting("user_settings", &json).map_err(|e| e.to_string())
+    db.set_setting("user_settings", &json).map_err(|e| e.to_string())?;
+    grok.configure(&settings.ai);
+    grok.prompts().set_language(&settings.language);
+    Ok(())
 }
 
 // ============================================
@@ -374,6 +572,20 @@ pub fn get_category_distribution(db: State<Database>) -> Result<Vec<(String, i32
     db.get_category_distribution().map_err(|e| e.to_string())
 }
 
+/// Streaks, throughput and trend analytics for a period (week/month/year)
+#[tauri::command]
+pub fn get_analytics(db: State<Database>, period: Option<String>) -> Result<AnalyticsReport, String> {
+    let period = period.unwrap_or_else(|| "week".to_string());
+    crate::analytics::build_report(&db, &period)
+}
+
+/// AI calls, tokens and cost per day and purpose over the last `days` days
+#[tauri::command]
+pub fn get_ai_usage(db: State<Database>, days: Option<i32>) -> Result<Vec<AiUsageSummary>, String> {
+    let days = days.unwrap_or(30);
+    db.get_ai_usage(days).map_err(|e| e.to_string())
+}
+
 // ============================================
 // Health Check
 // ============================================
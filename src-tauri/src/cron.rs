@@ -0,0 +1,254 @@
+// Cron module - schedule expressions for scheduler jobs, evaluated in local time
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Timelike};
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead to search for the next matching day before giving up
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+/// A job schedule. Accepted forms:
+/// - five-field cron: `*/15 9-18 * * mon-fri`
+/// - aliases: `@hourly`, `@daily`, `@weekly`, `@monthly`
+/// - fixed interval: `@every 30m`, `@every 2h`, `@every 1d`
+/// - time of day: `18:00`, or with weekdays: `fri 17:00`, `mon,thu 09:30`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(CronExpr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl Schedule {
+    pub fn parse(spec: &str) -> Result<Schedule, String> {
+        let spec = spec.trim().to_lowercase();
+
+        if let Some(every) = spec.strip_prefix("@every") {
+            return parse_every(every.trim()).map(Schedule::Every);
+        }
+
+        let expanded = match spec.as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * sun".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            _ => time_of_day(&spec).unwrap_or_else(|| spec.clone()),
+        };
+
+        CronExpr::parse(&expanded).map(Schedule::Cron)
+    }
+
+    /// Next run strictly after `after`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Schedule::Every(interval) => Some(after + *interval),
+            Schedule::Cron(expr) => expr.next_after(after),
+        }
+    }
+}
+
+fn parse_every(spec: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid interval: {}", spec);
+    let unit_at = spec.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (count, unit) = spec.split_at(unit_at);
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    if count == 0 {
+        return Err(invalid());
+    }
+
+    match unit {
+        "m" | "min" | "mins" | "minutes" => Ok(Duration::minutes(count)),
+        "h" | "hour" | "hours" => Ok(Duration::hours(count)),
+        "d" | "day" | "days" => Ok(Duration::days(count)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Rewrite `HH:MM` or `<weekdays> HH:MM` into a cron expression
+fn time_of_day(spec: &str) -> Option<String> {
+    let (days, time) = match spec.rsplit_once(' ') {
+        Some((days, time)) => (Some(days.trim()), time),
+        None => (None, spec),
+    };
+
+    let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+
+    // A cron expression also has spaces; only treat the prefix as weekdays if it parses
+    let days = match days {
+        Some(days) => {
+            parse_field(days, 0, 7, Some(&WEEKDAY_NAMES)).ok()?;
+            days
+        }
+        None => "*",
+    };
+
+    Some(format!("{} {} * * {}", time.minute(), time.hour(), days))
+}
+
+impl CronExpr {
+    fn parse(spec: &str) -> Result<CronExpr, String> {
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("Expected 5 cron fields, got {}: {}", fields.len(), spec));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, Some(&WEEKDAY_NAMES))?;
+        // Both 0 and 7 mean Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(CronExpr {
+            minutes: parse_field(fields[0], 0, 59, None)?,
+            hours: parse_field(fields[1], 0, 23, None)?,
+            days_of_month: parse_field(fields[2], 1, 31, None)?,
+            months: parse_field(fields[3], 1, 12, None)?,
+            days_of_week,
+            any_day_of_month: fields[2] == "*",
+            any_day_of_week: fields[4] == "*",
+        })
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+
+        // Standard cron: when both day fields are restricted, either may match
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        }
+    }
+
+    fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start.date() + Duration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+
+            for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                for minute in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                    let candidate = date.and_hms_opt(hour, minute, 0)?;
+                    if candidate >= start {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Parse one cron field into a bitmask of allowed values
+fn parse_field(field: &str, min: u32, max: u32, names: Option<&[&str]>) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        if let Some(i) = names.and_then(|n| n.iter().position(|name| *name == s)) {
+            return Ok(i as u32);
+        }
+        let v: u32 = s.parse().map_err(|_| format!("Invalid cron value: {}", s))?;
+        if v < min || v > max {
+            return Err(format!("Cron value {} out of range {}-{}", v, min, max));
+        }
+        Ok(v)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("Invalid cron step: {}", part))?;
+                if step == 0 {
+                    return Err(format!("Invalid cron step: {}", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (value(lo)?, value(hi)?)
+        } else {
+            let v = value(range)?;
+            // `5/15` means every 15 starting at 5
+            (v, if step > 1 { max } else { v })
+        };
+
+        if lo > hi {
+            return Err(format!("Invalid cron range: {}", part));
+        }
+
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(spec: &str, after: &str) -> NaiveDateTime {
+        Schedule::parse(spec).unwrap().next_after(at(after)).unwrap()
+    }
+
+    #[test]
+    fn test_time_of_day_forms() {
+        // 2024-01-17 is a Wednesday
+        assert_eq!(next("18:00", "2024-01-17 12:00"), at("2024-01-17 18:00"));
+        assert_eq!(next("18:00", "2024-01-17 18:00"), at("2024-01-18 18:00"));
+        assert_eq!(next("fri 17:00", "2024-01-17 12:00"), at("2024-01-19 17:00"));
+        assert_eq!(next("mon,thu 09:30", "2024-01-19 12:00"), at("2024-01-22 09:30"));
+    }
+
+    #[test]
+    fn test_cron_fields() {
+        assert_eq!(next("*/15 * * * *", "2024-01-17 12:07"), at("2024-01-17 12:15"));
+        assert_eq!(next("0 9-18 * * mon-fri", "2024-01-19 18:30"), at("2024-01-22 09:00"));
+        assert_eq!(next("@hourly", "2024-01-17 12:00"), at("2024-01-17 13:00"));
+        assert_eq!(next("0 0 * * 7", "2024-01-17 12:00"), at("2024-01-21 00:00"));
+        // Day-of-month or day-of-week when both are restricted
+        assert_eq!(next("0 8 20 * mon", "2024-01-17 12:00"), at("2024-01-20 08:00"));
+    }
+
+    #[test]
+    fn test_every_interval() {
+        assert_eq!(next("@every 30m", "2024-01-17 12:07"), at("2024-01-17 12:37"));
+        assert_eq!(next("@every 2h", "2024-01-17 23:00"), at("2024-01-18 01:00"));
+        assert!(Schedule::parse("@every 0m").is_err());
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        assert!(Schedule::parse("").is_err());
+        assert!(Schedule::parse("61 * * * *").is_err());
+        assert!(Schedule::parse("* * *").is_err());
+        assert!(Schedule::parse("someday 17:00").is_err());
+    }
+}
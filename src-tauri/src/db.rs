@@ -1,7 +1,7 @@
 use rusqlite::{Connection, Result as SqlResult, params};
 use std::path::PathBuf;
 use std::sync::{Mutex, MutexGuard, PoisonError};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use crate::models::*;
 
@@ -66,6 +66,77 @@ impl Database {
                 UNIQUE(project_id, date)
             );
 
+            -- Earlier versions of daily logs rewritten by later scans
+            CREATE TABLE IF NOT EXISTS daily_log_revisions (
+                id TEXT PRIMARY KEY,
+                log_id TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                category TEXT NOT NULL DEFAULT 'other',
+                files_changed TEXT NOT NULL DEFAULT '[]',
+                ai_classification TEXT,
+                revised_at TEXT NOT NULL,
+                FOREIGN KEY (log_id) REFERENCES daily_logs(id)
+            );
+
+            -- User corrections of log categories, fed back into AI prompts
+            CREATE TABLE IF NOT EXISTS log_corrections (
+                id TEXT PRIMARY KEY,
+                log_id TEXT NOT NULL,
+                project_id TEXT NOT NULL,
+                ai_category TEXT,
+                user_category TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (log_id) REFERENCES daily_logs(id)
+            );
+
+            -- AI call usage, for cost accounting
+            CREATE TABLE IF NOT EXISTS ai_usage (
+                id TEXT PRIMARY KEY,
+                model TEXT NOT NULL,
+                purpose TEXT NOT NULL,
+                project_id TEXT,
+                prompt_tokens INTEGER NOT NULL,
+                completion_tokens INTEGER NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                cost_usd REAL NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            -- Replies to deterministic AI calls, keyed by a hash of the call
+            CREATE TABLE IF NOT EXISTS ai_cache (
+                key TEXT PRIMARY KEY,
+                purpose TEXT NOT NULL,
+                response TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                last_used_at TEXT NOT NULL
+            );
+
+            -- Changes made by the AI or slash commands, with how to undo them
+            CREATE TABLE IF NOT EXISTS action_journal (
+                id TEXT PRIMARY KEY,
+                trigger_source TEXT NOT NULL,
+                action_type TEXT NOT NULL,
+                description TEXT NOT NULL,
+                project_id TEXT,
+                undo TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                undone_at TEXT
+            );
+
+            -- Vectors of stored text for semantic search, one per record
+            CREATE TABLE IF NOT EXISTS embeddings (
+                source_kind TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                project_id TEXT,
+                date TEXT NOT NULL,
+                text TEXT NOT NULL,
+                model TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                vector BLOB NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (source_kind, source_id)
+            );
+
             -- Milestones table
             CREATE TABLE IF NOT EXISTS milestones (
                 id TEXT PRIMARY KEY,
@@ -135,6 +206,35 @@ impl Database {
                 FOREIGN KEY (project_id) REFERENCES projects(id)
             );
 
+            -- Weekly reviews table
+            CREATE TABLE IF NOT EXISTS weekly_reviews (
+                id TEXT PRIMARY KEY,
+                inbox_item_id TEXT NOT NULL,
+                week_start TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                questions TEXT NOT NULL DEFAULT '[]',
+                answers TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL,
+                answered_at TEXT,
+                FOREIGN KEY (inbox_item_id) REFERENCES inbox_items(id)
+            );
+
+            -- History backfill jobs, one per project
+            CREATE TABLE IF NOT EXISTS backfill_jobs (
+                project_id TEXT PRIMARY KEY,
+                since TEXT NOT NULL,
+                until TEXT NOT NULL,
+                use_ai INTEGER NOT NULL DEFAULT 0,
+                status TEXT NOT NULL,
+                days TEXT NOT NULL DEFAULT '[]',
+                processed INTEGER NOT NULL DEFAULT 0,
+                logs_created INTEGER NOT NULL DEFAULT 0,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id)
+            );
+
             -- Settings table
             CREATE TABLE IF NOT EXISTS settings (
                 key TEXT PRIMARY KEY,
@@ -143,6 +243,10 @@ impl Database {
 
             -- Create indexes
             CREATE INDEX IF NOT EXISTS idx_daily_logs_project_date ON daily_logs(project_id, date);
+            CREATE INDEX IF NOT EXISTS idx_daily_log_revisions_log ON daily_log_revisions(log_id);
+            CREATE INDEX IF NOT EXISTS idx_log_corrections_project ON log_corrections(project_id);
+            CREATE INDEX IF NOT EXISTS idx_ai_usage_created ON ai_usage(created_at);
+            CREATE INDEX IF NOT EXISTS idx_ai_cache_last_used ON ai_cache(last_used_at);
             CREATE INDEX IF NOT EXISTS idx_milestones_project ON milestones(project_id);
             CREATE INDEX IF NOT EXISTS idx_milestones_status ON milestones(status);
             CREATE INDEX IF NOT EXISTS idx_git_tags_project ON git_tags(project_id);
@@ -150,8 +254,12 @@ impl Database {
             CREATE INDEX IF NOT EXISTS idx_todos_status ON todos(status);
             CREATE INDEX IF NOT EXISTS idx_inbox_status ON inbox_items(status);
             CREATE INDEX IF NOT EXISTS idx_chat_project ON chat_messages(project_id);
+            CREATE INDEX IF NOT EXISTS idx_weekly_reviews_week ON weekly_reviews(week_start);
         "#)?;
 
+        // Release the lock before migrations take their own
+        drop(conn);
+
         // Run migrations
         self.run_migrations()?;
 
@@ -206,6 +314,85 @@ impl Database {
             )?;
         }
 
+        // Persist AI-detected actions on inbox items
+        let has_detected_actions: bool = conn
+            .prepare("SELECT detected_actions FROM inbox_items LIMIT 1")
+            .is_ok();
+
+        if !has_detected_actions {
+            log::info!("Running migration: adding detected_actions column to inbox_items");
+            conn.execute(
+                "ALTER TABLE inbox_items ADD COLUMN detected_actions TEXT NOT NULL DEFAULT '[]'",
+                [],
+            )?;
+        }
+
+        // Citations of grounded chat replies
+        let has_citations: bool = conn
+            .prepare("SELECT citations FROM chat_messages LIMIT 1")
+            .is_ok();
+
+        if !has_citations {
+            log::info!("Running migration: adding citations column to chat_messages");
+            conn.execute(
+                "ALTER TABLE chat_messages ADD COLUMN citations TEXT NOT NULL DEFAULT '[]'",
+                [],
+            )?;
+        }
+
+        // Inbox lifecycle: priority, dedupe keys, snooze and expiry
+        let has_inbox_priority: bool = conn
+            .prepare("SELECT priority FROM inbox_items LIMIT 1")
+            .is_ok();
+
+        if !has_inbox_priority {
+            log::info!("Running migration: adding lifecycle columns to inbox_items");
+            conn.execute_batch(
+                "ALTER TABLE inbox_items ADD COLUMN priority TEXT NOT NULL DEFAULT 'normal';
+                 ALTER TABLE inbox_items ADD COLUMN dedupe_key TEXT;
+                 ALTER TABLE inbox_items ADD COLUMN snoozed_until TEXT;
+                 ALTER TABLE inbox_items ADD COLUMN expires_at TEXT;
+                 CREATE INDEX IF NOT EXISTS idx_inbox_items_dedupe_key ON inbox_items(dedupe_key);",
+            )?;
+        }
+
+        // Mark daily logs whose summary the user wrote, so scans don't replace it
+        let has_summary_by_user: bool = conn
+            .prepare("SELECT summary_by_user FROM daily_logs LIMIT 1")
+            .is_ok();
+
+        if !has_summary_by_user {
+            log::info!("Running migration: adding summary_by_user column to daily_logs");
+            conn.execute("ALTER TABLE daily_logs ADD COLUMN summary_by_user INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+
+        // Record which prompt template produced AI-written rows
+        for table in ["daily_logs", "inbox_items", "chat_messages"] {
+            let has_prompt_version: bool = conn
+                .prepare(&format!("SELECT prompt_version FROM {} LIMIT 1", table))
+                .is_ok();
+
+            if !has_prompt_version {
+                log::info!("Running migration: adding prompt_version column to {}", table);
+                conn.execute(&format!("ALTER TABLE {} ADD COLUMN prompt_version TEXT", table), [])?;
+            }
+        }
+
+        // Bring saved settings up to date with where each setting lives now
+        let settings_json = match conn.query_row(
+            "SELECT value FROM settings WHERE key = 'user_settings'",
+            [],
+            |row| row.get::<_, String>(0),
+        ) {
+            Ok(json) => Some(json),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+        if let Some(migrated) = settings_json.as_deref().and_then(UserSettings::migrate_json) {
+            log::info!("Running migration: moving settings saved by an older version");
+            conn.execute("UPDATE settings SET value = ?1 WHERE key = 'user_settings'", params![migrated])?;
+        }
+
         Ok(())
     }
 
@@ -282,11 +469,13 @@ impl Database {
     // Daily Log Operations
     // ============================================
 
-    pub fn create_daily_log(&self, log: &DailyLog) -> SqlResult<()> {
+    /// Insert the log unless its project already has one for that date, which is left as it
+    /// is. Returns whether the log was inserted.
+    pub fn create_daily_log(&self, log: &DailyLog) -> SqlResult<bool> {
         let conn = self.get_conn()?;
-        conn.execute(
-            "INSERT OR REPLACE INTO daily_logs (id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO daily_logs (id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
             params![
                 log.id,
                 log.project_id,
@@ -297,18 +486,126 @@ impl Database {
                 log.ai_classification,
                 log.user_override,
                 log.created_at.to_rfc3339(),
+                log.prompt_version,
+                log.summary_by_user,
             ],
         )?;
-        Ok(())
+        Ok(inserted > 0)
+    }
+
+    /// Create the log for its project and date, or rewrite the existing one in place as
+    /// `DailyLog::rewritten_with` describes, filing the previous summary, category and
+    /// files as a revision when the summary or category changes. Returns the stored log.
+    pub fn upsert_daily_log(&self, log: &DailyLog) -> SqlResult<DailyLog> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        let existing = match tx.query_row(
+            "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user
+             FROM daily_logs WHERE project_id = ?1 AND date = ?2",
+            params![log.project_id, log.date],
+            Self::row_to_daily_log,
+        ) {
+            Ok(existing) => Some(existing),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+
+        let stored = match existing {
+            None => {
+                tx.execute(
+                    "INSERT INTO daily_logs (id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    params![
+                        log.id,
+                        log.project_id,
+                        log.date,
+                        log.summary,
+                        serde_json::to_string(&log.category).unwrap().trim_matches('"'),
+                        serde_json::to_string(&log.files_changed).unwrap(),
+                        log.ai_classification,
+                        log.user_override,
+                        log.created_at.to_rfc3339(),
+                        log.prompt_version,
+                        log.summary_by_user,
+                    ],
+                )?;
+                log.clone()
+            }
+            Some(existing) => {
+                let stored = existing.rewritten_with(log);
+                if stored.summary != existing.summary || stored.category != existing.category {
+                    tx.execute(
+                        "INSERT INTO daily_log_revisions (id, log_id, summary, category, files_changed, ai_classification, revised_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            uuid::Uuid::new_v4().to_string(),
+                            existing.id,
+                            existing.summary,
+                            serde_json::to_string(&existing.category).unwrap().trim_matches('"'),
+                            serde_json::to_string(&existing.files_changed).unwrap(),
+                            existing.ai_classification,
+                            Utc::now().to_rfc3339(),
+                        ],
+                    )?;
+                }
+                tx.execute(
+                    "UPDATE daily_logs SET summary = ?1, category = ?2, files_changed = ?3, ai_classification = ?4, prompt_version = ?5,
+                     summary_by_user = ?6 WHERE id = ?7",
+                    params![
+                        stored.summary,
+                        serde_json::to_string(&stored.category).unwrap().trim_matches('"'),
+                        serde_json::to_string(&stored.files_changed).unwrap(),
+                        stored.ai_classification,
+                        stored.prompt_version,
+                        stored.summary_by_user,
+                        stored.id,
+                    ],
+                )?;
+                stored
+            }
+        };
+
+        tx.commit()?;
+        Ok(stored)
+    }
+
+    /// Earlier versions of a daily log, newest first
+    pub fn get_daily_log_revisions(&self, log_id: &str) -> SqlResult<Vec<DailyLogRevision>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, log_id, summary, category, files_changed, ai_classification, revised_at
+             FROM daily_log_revisions WHERE log_id = ?1 ORDER BY revised_at DESC"
+        )?;
+
+        let revisions = stmt
+            .query_map(params![log_id], |row| {
+                let category_str: String = row.get(3)?;
+                let files_json: String = row.get(4)?;
+                Ok(DailyLogRevision {
+                    id: row.get(0)?,
+                    log_id: row.get(1)?,
+                    summary: row.get(2)?,
+                    category: Self::parse_log_category(&category_str),
+                    files_changed: serde_json::from_str(&files_json).unwrap_or_default(),
+                    ai_classification: row.get(5)?,
+                    revised_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(revisions)
     }
 
     pub fn get_daily_logs(&self, project_id: Option<&str>, limit: i32) -> SqlResult<Vec<DailyLog>> {
         let conn = self.get_conn()?;
 
         let query = match project_id {
-            Some(_) => "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at
+            Some(_) => "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user
                         FROM daily_logs WHERE project_id = ?1 ORDER BY date DESC LIMIT ?2",
-            None => "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at
+            None => "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user
                      FROM daily_logs ORDER BY date DESC LIMIT ?1",
         };
 
@@ -325,9 +622,186 @@ impl Database {
         Ok(logs)
     }
 
-    fn row_to_daily_log(row: &rusqlite::Row) -> rusqlite::Result<DailyLog> {
-        let category_str: String = row.get(4)?;
-        let category = match category_str.as_str() {
+    pub fn get_daily_logs_since(&self, since: &str) -> SqlResult<Vec<DailyLog>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user
+             FROM daily_logs WHERE date >= ?1 ORDER BY date"
+        )?;
+
+        let logs = stmt
+            .query_map(params![since], Self::row_to_daily_log)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(logs)
+    }
+
+    pub fn get_daily_log_for_date(&self, project_id: &str, date: &str) -> SqlResult<Option<DailyLog>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user
+             FROM daily_logs WHERE project_id = ?1 AND date = ?2"
+        )?;
+
+        match stmt.query_row(params![project_id, date], Self::row_to_daily_log) {
+            Ok(log) => Ok(Some(log)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_daily_log(&self, id: &str) -> SqlResult<Option<DailyLog>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, project_id, date, summary, category, files_changed, ai_classification, user_override, created_at, prompt_version, summary_by_user
+             FROM daily_logs WHERE id = ?1"
+        )?;
+
+        match stmt.query_row(params![id], Self::row_to_daily_log) {
+            Ok(log) => Ok(Some(log)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Set the user's category for a log and record the correction against what
+    /// the AI (or the offline classifier) had picked
+    pub fn override_log_category(&self, log: &DailyLog, category: &str) -> SqlResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        let previous = log.ai_classification.clone().unwrap_or_else(|| {
+            serde_json::to_string(&log.category).unwrap().trim_matches('"').to_string()
+        });
+
+        tx.execute(
+            "UPDATE daily_logs SET user_override = ?1 WHERE id = ?2",
+            params![category, log.id],
+        )?;
+        tx.execute(
+            "INSERT INTO log_corrections (id, log_id, project_id, ai_category, user_category, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                log.id,
+                log.project_id,
+                previous,
+                category,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+
+        tx.commit()
+    }
+
+    /// Replace a log's summary with the user's own, which later scans keep
+    pub fn update_daily_log_summary(&self, id: &str, summary: &str) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE daily_logs SET summary = ?1, summary_by_user = 1 WHERE id = ?2",
+            params![summary, id],
+        )?;
+        Ok(())
+    }
+
+    /// Put a log's content back as it was, e.g. to undo an AI rewrite
+    pub fn restore_daily_log(&self, log: &DailyLog) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE daily_logs SET summary = ?1, category = ?2, files_changed = ?3, ai_classification = ?4, user_override = ?5, prompt_version = ?6,
+             summary_by_user = ?7 WHERE id = ?8",
+            params![
+                log.summary,
+                serde_json::to_string(&log.category).unwrap().trim_matches('"'),
+                serde_json::to_string(&log.files_changed).unwrap(),
+                log.ai_classification,
+                log.user_override,
+                log.prompt_version,
+                log.summary_by_user,
+                log.id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_daily_log(&self, id: &str) -> SqlResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM daily_log_revisions WHERE log_id = ?1", params![id])?;
+        tx.execute("DELETE FROM log_corrections WHERE log_id = ?1", params![id])?;
+        tx.execute("DELETE FROM daily_logs WHERE id = ?1", params![id])?;
+        tx.commit()
+    }
+
+    /// A project's logs whose category the user corrected, most recently corrected first
+    pub fn get_corrected_logs(&self, project_id: &str, limit: usize) -> SqlResult<Vec<DailyLog>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT d.id, d.project_id, d.date, d.summary, d.category, d.files_changed, d.ai_classification, d.user_override, d.created_at, d.prompt_version, d.summary_by_user
+             FROM daily_logs d
+             JOIN (SELECT log_id, MAX(created_at) AS corrected_at FROM log_corrections
+                   WHERE project_id = ?1 GROUP BY log_id) c ON c.log_id = d.id
+             WHERE d.user_override IS NOT NULL
+             ORDER BY c.corrected_at DESC LIMIT ?2"
+        )?;
+
+        let logs = stmt
+            .query_map(params![project_id, limit as i64], Self::row_to_daily_log)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(logs)
+    }
+
+    /// Per project, how many AI-labeled logs the user left as they were
+    pub fn get_classification_accuracy(&self) -> SqlResult<Vec<ClassificationAccuracy>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT project_id, COUNT(*),
+                    SUM(CASE WHEN user_override IS NOT NULL AND user_override != ai_classification THEN 1 ELSE 0 END)
+             FROM daily_logs WHERE ai_classification IS NOT NULL
+             GROUP BY project_id"
+        )?;
+
+        let stats = stmt
+            .query_map([], |row| {
+                let ai_labeled: u32 = row.get(1)?;
+                let corrected: u32 = row.get(2)?;
+                Ok(ClassificationAccuracy {
+                    project_id: row.get(0)?,
+                    ai_labeled,
+                    corrected,
+                    accuracy: (ai_labeled - corrected) as f64 / ai_labeled as f64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// Distinct dates that have at least one daily log, oldest first
+    pub fn get_active_dates(&self) -> SqlResult<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT date FROM daily_logs ORDER BY date")?;
+
+        let dates = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(dates)
+    }
+
+    /// Most recent log date for a project, if it has any logs
+    pub fn get_last_log_date(&self, project_id: &str) -> SqlResult<Option<String>> {
+        let conn = self.get_conn()?;
+        conn.query_row(
+            "SELECT MAX(date) FROM daily_logs WHERE project_id = ?1",
+            params![project_id],
+            |row| row.get(0),
+        )
+    }
+
+    fn parse_log_category(category: &str) -> LogCategory {
+        match category {
             "feature" => LogCategory::Feature,
             "bugfix" => LogCategory::Bugfix,
             "refactor" => LogCategory::Refactor,
@@ -336,7 +810,12 @@ impl Database {
             "test" => LogCategory::Test,
             "chore" => LogCategory::Chore,
             _ => LogCategory::Other,
-        };
+        }
+    }
+
+    fn row_to_daily_log(row: &rusqlite::Row) -> rusqlite::Result<DailyLog> {
+        let category_str: String = row.get(4)?;
+        let category = Self::parse_log_category(&category_str);
 
         let files_json: String = row.get(5)?;
         let files_changed: Vec<FileChange> = serde_json::from_str(&files_json).unwrap_or_default();
@@ -353,6 +832,8 @@ impl Database {
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now()),
+            prompt_version: row.get(9)?,
+            summary_by_user: row.get(10)?,
         })
     }
 
@@ -440,6 +921,41 @@ impl Database {
         Ok(())
     }
 
+    pub fn get_milestone(&self, id: &str) -> SqlResult<Option<Milestone>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT id, project_id, title, description, version, git_tag, status, source, target_date, completed_at, created_at
+             FROM milestones WHERE id = ?1",
+            params![id],
+            Self::row_to_milestone,
+        );
+
+        match result {
+            Ok(milestone) => Ok(Some(milestone)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Overwrite the editable fields of a stored milestone
+    pub fn update_milestone(&self, milestone: &Milestone) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE milestones SET title = ?1, description = ?2, version = ?3, status = ?4, target_date = ?5, completed_at = ?6
+             WHERE id = ?7",
+            params![
+                milestone.title,
+                milestone.description,
+                milestone.version,
+                milestone.status.as_str(),
+                milestone.target_date,
+                milestone.completed_at.map(|dt| dt.to_rfc3339()),
+                milestone.id,
+            ],
+        )?;
+        Ok(())
+    }
+
     fn row_to_milestone(row: &rusqlite::Row) -> rusqlite::Result<Milestone> {
         let status_str: String = row.get(6)?;
         let status = match status_str.as_str() {
@@ -580,7 +1096,7 @@ impl Database {
                 todo.title,
                 todo.description,
                 serde_json::to_string(&todo.priority).unwrap().trim_matches('"'),
-                serde_json::to_string(&todo.status).unwrap().trim_matches('"'),
+                todo.status.as_str(),
                 todo.due_date,
                 todo.column,
                 todo.position,
@@ -634,6 +1150,53 @@ impl Database {
         Ok(())
     }
 
+    pub fn update_todo_due_date(&self, id: &str, due_date: Option<&str>) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE todos SET due_date = ?1 WHERE id = ?2",
+            params![due_date, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_todo(&self, id: &str) -> SqlResult<Option<Todo>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT id, project_id, title, description, priority, status, due_date, column, position, created_at, completed_at
+             FROM todos WHERE id = ?1",
+            params![id],
+            Self::row_to_todo,
+        );
+
+        match result {
+            Ok(todo) => Ok(Some(todo)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Overwrite every field of a stored todo, e.g. with an earlier copy of itself
+    pub fn update_todo(&self, todo: &Todo) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE todos SET project_id = ?1, title = ?2, description = ?3, priority = ?4, status = ?5, due_date = ?6, column = ?7, position = ?8, completed_at = ?9
+             WHERE id = ?10",
+            params![
+                todo.project_id,
+                todo.title,
+                todo.description,
+                serde_json::to_string(&todo.priority).unwrap().trim_matches('"'),
+                todo.status.as_str(),
+                todo.due_date,
+                todo.column,
+                todo.position,
+                todo.completed_at.map(|dt| dt.to_rfc3339()),
+                todo.id,
+            ],
+        )?;
+        Ok(())
+    }
+
     pub fn delete_todo(&self, id: &str) -> SqlResult<()> {
         let conn = self.get_conn()?;
         conn.execute("DELETE FROM todos WHERE id = ?1", params![id])?;
@@ -708,8 +1271,8 @@ impl Database {
     pub fn create_inbox_item(&self, item: &InboxItem) -> SqlResult<()> {
         let conn = self.get_conn()?;
         conn.execute(
-            "INSERT INTO inbox_items (id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO inbox_items (id, item_type, project_id, question, context, suggested_actions, detected_actions, status, answer, created_at, answered_at, prompt_version, priority, dedupe_key, snoozed_until, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             params![
                 item.id,
                 serde_json::to_string(&item.item_type).unwrap().trim_matches('"'),
@@ -717,26 +1280,79 @@ impl Database {
                 item.question,
                 item.context,
                 serde_json::to_string(&item.suggested_actions).unwrap(),
+                serde_json::to_string(&item.detected_actions).unwrap(),
                 serde_json::to_string(&item.status).unwrap().trim_matches('"'),
                 item.answer,
                 item.created_at.to_rfc3339(),
                 item.answered_at.map(|dt| dt.to_rfc3339()),
+                item.prompt_version,
+                item.priority.as_str(),
+                item.dedupe_key,
+                item.snoozed_until.map(|dt| dt.to_rfc3339()),
+                item.expires_at.map(|dt| dt.to_rfc3339()),
             ],
         )?;
         Ok(())
     }
 
-    pub fn get_inbox_items(&self, status: Option<InboxStatus>) -> SqlResult<Vec<InboxItem>> {
+    /// Replace what an item says, keeping its id, status and answer
+    pub fn update_inbox_item(&self, item: &InboxItem) -> SqlResult<()> {
         let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET question = ?1, context = ?2, suggested_actions = ?3, detected_actions = ?4,
+                 prompt_version = ?5, priority = ?6, expires_at = ?7
+             WHERE id = ?8",
+            params![
+                item.question,
+                item.context,
+                serde_json::to_string(&item.suggested_actions).unwrap(),
+                serde_json::to_string(&item.detected_actions).unwrap(),
+                item.prompt_version,
+                item.priority.as_str(),
+                item.expires_at.map(|dt| dt.to_rfc3339()),
+                item.id,
+            ],
+        )?;
+        Ok(())
+    }
 
-        let query = match status {
-            Some(_) => "SELECT id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at
-                        FROM inbox_items WHERE status = ?1 ORDER BY created_at DESC",
-            None => "SELECT id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at
-                     FROM inbox_items ORDER BY created_at DESC",
-        };
+    /// The latest item posted under `key`, whatever its status
+    pub fn get_inbox_item_by_key(&self, key: &str) -> SqlResult<Option<InboxItem>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at, detected_actions, prompt_version, priority, dedupe_key, snoozed_until, expires_at
+             FROM inbox_items WHERE dedupe_key = ?1
+             ORDER BY created_at DESC LIMIT 1",
+            params![key],
+            Self::row_to_inbox_item,
+        );
 
-        let mut stmt = conn.prepare(query)?;
+        match result {
+            Ok(item) => Ok(Some(item)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn delete_inbox_item(&self, id: &str) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute("DELETE FROM inbox_items WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn get_inbox_items(&self, status: Option<InboxStatus>) -> SqlResult<Vec<InboxItem>> {
+        let conn = self.get_conn()?;
+
+        let query = match status {
+            Some(_) => "SELECT id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at, detected_actions, prompt_version, priority, dedupe_key, snoozed_until, expires_at
+                        FROM inbox_items WHERE status = ?1
+                        ORDER BY CASE priority WHEN 'high' THEN 0 WHEN 'normal' THEN 1 ELSE 2 END, created_at DESC",
+            None => "SELECT id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at, detected_actions, prompt_version, priority, dedupe_key, snoozed_until, expires_at
+                     FROM inbox_items
+                     ORDER BY CASE priority WHEN 'high' THEN 0 WHEN 'normal' THEN 1 ELSE 2 END, created_at DESC",
+        };
+
+        let mut stmt = conn.prepare(query)?;
 
         let items = if let Some(s) = status {
             let status_str = serde_json::to_string(&s).unwrap().trim_matches('"').to_string();
@@ -750,6 +1366,31 @@ impl Database {
         Ok(items)
     }
 
+    pub fn get_inbox_item(&self, id: &str) -> SqlResult<Option<InboxItem>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT id, item_type, project_id, question, context, suggested_actions, status, answer, created_at, answered_at, detected_actions, prompt_version, priority, dedupe_key, snoozed_until, expires_at
+             FROM inbox_items WHERE id = ?1",
+            params![id],
+            Self::row_to_inbox_item,
+        );
+
+        match result {
+            Ok(item) => Ok(Some(item)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn update_inbox_detected_actions(&self, id: &str, actions: &[DetectedAction]) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET detected_actions = ?1 WHERE id = ?2",
+            params![serde_json::to_string(actions).unwrap(), id],
+        )?;
+        Ok(())
+    }
+
     pub fn answer_inbox_item(&self, id: &str, answer: &str) -> SqlResult<()> {
         let conn = self.get_conn()?;
         conn.execute(
@@ -759,6 +1400,51 @@ impl Database {
         Ok(())
     }
 
+    pub fn skip_inbox_item(&self, id: &str) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET status = 'skipped', snoozed_until = NULL WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    pub fn snooze_inbox_item(&self, id: &str, until: DateTime<Utc>) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET status = 'snoozed', snoozed_until = ?1 WHERE id = ?2 AND status IN ('pending', 'snoozed')",
+            params![until.to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_inbox_item_priority(&self, id: &str, priority: InboxPriority) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET priority = ?1 WHERE id = ?2",
+            params![priority.as_str(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Return snoozed items whose time has come to the pending list
+    pub fn wake_snoozed_inbox_items(&self, now: DateTime<Utc>) -> SqlResult<usize> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET status = 'pending', snoozed_until = NULL WHERE status = 'snoozed' AND snoozed_until <= ?1",
+            params![now.to_rfc3339()],
+        )
+    }
+
+    /// Expire pending items left unanswered past their expiry
+    pub fn expire_inbox_items(&self, now: DateTime<Utc>) -> SqlResult<usize> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE inbox_items SET status = 'expired' WHERE status = 'pending' AND expires_at <= ?1",
+            params![now.to_rfc3339()],
+        )
+    }
+
     fn row_to_inbox_item(row: &rusqlite::Row) -> rusqlite::Result<InboxItem> {
         let item_type_str: String = row.get(1)?;
         let item_type = match item_type_str.as_str() {
@@ -766,6 +1452,10 @@ impl Database {
             "classification" => InboxItemType::Classification,
             "todo_followup" => InboxItemType::TodoFollowup,
             "planning" => InboxItemType::Planning,
+            "anomaly_detection" => InboxItemType::AnomalyDetection,
+            "weekly_review" => InboxItemType::WeeklyReview,
+            "pattern_insight" => InboxItemType::PatternInsight,
+            "major_update" => InboxItemType::MajorUpdate,
             _ => InboxItemType::StaleProject,
         };
 
@@ -773,12 +1463,17 @@ impl Database {
         let status = match status_str.as_str() {
             "answered" => InboxStatus::Answered,
             "skipped" => InboxStatus::Skipped,
+            "snoozed" => InboxStatus::Snoozed,
+            "expired" => InboxStatus::Expired,
             _ => InboxStatus::Pending,
         };
 
         let actions_json: String = row.get(5)?;
         let suggested_actions: Vec<SuggestedAction> = serde_json::from_str(&actions_json).unwrap_or_default();
 
+        let detected_json: String = row.get(10)?;
+        let detected_actions: Vec<DetectedAction> = serde_json::from_str(&detected_json).unwrap_or_default();
+
         Ok(InboxItem {
             id: row.get(0)?,
             item_type,
@@ -786,7 +1481,7 @@ impl Database {
             question: row.get(3)?,
             context: row.get(4)?,
             suggested_actions,
-            detected_actions: Vec::new(), // AI-detected actions are generated at runtime
+            detected_actions,
             status,
             answer: row.get(7)?,
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
@@ -795,6 +1490,185 @@ impl Database {
             answered_at: row.get::<_, Option<String>>(9)?
                 .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
                 .map(|dt| dt.with_timezone(&Utc)),
+            prompt_version: row.get(11)?,
+            priority: match row.get::<_, String>(12)?.as_str() {
+                "high" => InboxPriority::High,
+                "low" => InboxPriority::Low,
+                _ => InboxPriority::Normal,
+            },
+            dedupe_key: row.get(13)?,
+            snoozed_until: row.get::<_, Option<String>>(14)?
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+            expires_at: row.get::<_, Option<String>>(15)?
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+        })
+    }
+
+    // ============================================
+    // Weekly Review Operations
+    // ============================================
+
+    pub fn create_weekly_review(&self, review: &WeeklyReview) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO weekly_reviews (id, inbox_item_id, week_start, summary, questions, answers, created_at, answered_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                review.id,
+                review.inbox_item_id,
+                review.week_start,
+                serde_json::to_string(&review.summary).unwrap(),
+                serde_json::to_string(&review.questions).unwrap(),
+                serde_json::to_string(&review.answers).unwrap(),
+                review.created_at.to_rfc3339(),
+                review.answered_at.map(|dt| dt.to_rfc3339()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_weekly_reviews(&self, limit: i32) -> SqlResult<Vec<WeeklyReview>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, inbox_item_id, week_start, summary, questions, answers, created_at, answered_at
+             FROM weekly_reviews ORDER BY week_start DESC LIMIT ?1"
+        )?;
+
+        let reviews = stmt
+            .query_map(params![limit], Self::row_to_weekly_review)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(reviews)
+    }
+
+    pub fn get_weekly_review(&self, id: &str) -> SqlResult<Option<WeeklyReview>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, inbox_item_id, week_start, summary, questions, answers, created_at, answered_at
+             FROM weekly_reviews WHERE id = ?1"
+        )?;
+
+        match stmt.query_row(params![id], Self::row_to_weekly_review) {
+            Ok(review) => Ok(Some(review)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn answer_weekly_review(&self, id: &str, answers: &[String]) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE weekly_reviews SET answers = ?1, answered_at = ?2 WHERE id = ?3",
+            params![serde_json::to_string(answers).unwrap(), Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    fn row_to_weekly_review(row: &rusqlite::Row) -> rusqlite::Result<WeeklyReview> {
+        let summary_json: String = row.get(3)?;
+        let questions_json: String = row.get(4)?;
+        let answers_json: String = row.get(5)?;
+
+        Ok(WeeklyReview {
+            id: row.get(0)?,
+            inbox_item_id: row.get(1)?,
+            week_start: row.get(2)?,
+            summary: serde_json::from_str(&summary_json).unwrap_or_default(),
+            questions: serde_json::from_str(&questions_json).unwrap_or_default(),
+            answers: serde_json::from_str(&answers_json).unwrap_or_default(),
+            created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            answered_at: row.get::<_, Option<String>>(7)?
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+        })
+    }
+
+    // ============================================
+    // Backfill Operations
+    // ============================================
+
+    /// Insert or update the backfill job for a project
+    pub fn save_backfill_job(&self, job: &BackfillJob) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO backfill_jobs (project_id, since, until, use_ai, status, days, processed, logs_created, error, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                job.project_id,
+                job.since,
+                job.until,
+                job.use_ai,
+                serde_json::to_string(&job.status).unwrap().trim_matches('"'),
+                serde_json::to_string(&job.days).unwrap(),
+                job.processed,
+                job.logs_created,
+                job.error,
+                job.created_at.to_rfc3339(),
+                job.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_backfill_job(&self, project_id: &str) -> SqlResult<Option<BackfillJob>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT project_id, since, until, use_ai, status, days, processed, logs_created, error, created_at, updated_at
+             FROM backfill_jobs WHERE project_id = ?1"
+        )?;
+
+        match stmt.query_row(params![project_id], Self::row_to_backfill_job) {
+            Ok(job) => Ok(Some(job)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_backfill_jobs(&self) -> SqlResult<Vec<BackfillJob>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT project_id, since, until, use_ai, status, days, processed, logs_created, error, created_at, updated_at
+             FROM backfill_jobs ORDER BY updated_at DESC"
+        )?;
+
+        let jobs = stmt
+            .query_map([], Self::row_to_backfill_job)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(jobs)
+    }
+
+    fn row_to_backfill_job(row: &rusqlite::Row) -> rusqlite::Result<BackfillJob> {
+        let status_str: String = row.get(4)?;
+        let days_json: String = row.get(5)?;
+        let parse_time = |s: String| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now())
+        };
+
+        Ok(BackfillJob {
+            project_id: row.get(0)?,
+            since: row.get(1)?,
+            until: row.get(2)?,
+            use_ai: row.get(3)?,
+            status: match status_str.as_str() {
+                "running" => BackfillStatus::Running,
+                "paused" => BackfillStatus::Paused,
+                "completed" => BackfillStatus::Completed,
+                "cancelled" => BackfillStatus::Cancelled,
+                _ => BackfillStatus::Failed,
+            },
+            days: serde_json::from_str(&days_json).unwrap_or_default(),
+            processed: row.get(6)?,
+            logs_created: row.get(7)?,
+            error: row.get(8)?,
+            created_at: parse_time(row.get(9)?),
+            updated_at: parse_time(row.get(10)?),
         })
     }
 
@@ -805,8 +1679,8 @@ impl Database {
     pub fn create_chat_message(&self, message: &ChatMessage) -> SqlResult<()> {
         let conn = self.get_conn()?;
         conn.execute(
-            "INSERT INTO chat_messages (id, project_id, role, content, actions, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO chat_messages (id, project_id, role, content, actions, created_at, prompt_version, citations)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
                 message.id,
                 message.project_id,
@@ -814,6 +1688,8 @@ impl Database {
                 message.content,
                 message.actions.as_ref().map(|a| serde_json::to_string(a).unwrap()),
                 message.created_at.to_rfc3339(),
+                message.prompt_version,
+                serde_json::to_string(&message.citations).unwrap(),
             ],
         )?;
         Ok(())
@@ -823,9 +1699,9 @@ impl Database {
         let conn = self.get_conn()?;
 
         let query = match project_id {
-            Some(_) => "SELECT id, project_id, role, content, actions, created_at
+            Some(_) => "SELECT id, project_id, role, content, actions, created_at, prompt_version, citations
                         FROM chat_messages WHERE project_id = ?1 ORDER BY created_at DESC LIMIT ?2",
-            None => "SELECT id, project_id, role, content, actions, created_at
+            None => "SELECT id, project_id, role, content, actions, created_at, prompt_version, citations
                      FROM chat_messages ORDER BY created_at DESC LIMIT ?1",
         };
 
@@ -862,6 +1738,8 @@ impl Database {
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(5)?)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now()),
+            prompt_version: row.get(6)?,
+            citations: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
         })
     }
 
@@ -881,6 +1759,13 @@ impl Database {
         }
     }
 
+    /// Write a consistent copy of the database to `dest`
+    pub fn backup_to(&self, dest: &std::path::Path) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute("VACUUM INTO ?1", params![dest.to_string_lossy()])?;
+        Ok(())
+    }
+
     pub fn set_setting(&self, key: &str, value: &str) -> SqlResult<()> {
         let conn = self.get_conn()?;
         conn.execute(
@@ -890,6 +1775,338 @@ impl Database {
         Ok(())
     }
 
+    // ============================================
+    // AI Usage Operations
+    // ============================================
+
+    pub fn record_ai_usage(&self, usage: &AiUsage) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO ai_usage (id, model, purpose, project_id, prompt_tokens, completion_tokens, latency_ms, cost_usd, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                usage.id,
+                usage.model,
+                serde_json::to_string(&usage.purpose).unwrap().trim_matches('"'),
+                usage.project_id,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.latency_ms as i64,
+                usage.cost_usd,
+                usage.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Usage totals per local day and purpose over the last `days` days, newest first
+    pub fn get_ai_usage(&self, days: i32) -> SqlResult<Vec<AiUsageSummary>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT date(created_at, 'localtime') as day, purpose, COUNT(*),
+                    SUM(prompt_tokens), SUM(completion_tokens), SUM(cost_usd), AVG(latency_ms)
+             FROM ai_usage
+             WHERE date(created_at, 'localtime') >= date('now', 'localtime', ?1)
+             GROUP BY day, purpose ORDER BY day DESC, purpose"
+        )?;
+
+        let days_param = format!("-{} days", days);
+        let summaries = stmt
+            .query_map(params![days_param], |row| {
+                let purpose: String = row.get(1)?;
+                Ok(AiUsageSummary {
+                    date: row.get(0)?,
+                    purpose: Self::parse_ai_purpose(&purpose),
+                    calls: row.get(2)?,
+                    prompt_tokens: row.get::<_, i64>(3)? as u64,
+                    completion_tokens: row.get::<_, i64>(4)? as u64,
+                    cost_usd: row.get(5)?,
+                    avg_latency_ms: row.get::<_, f64>(6)? as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(summaries)
+    }
+
+    /// Total AI cost of calls made at or after `since`
+    pub fn get_ai_cost_since(&self, since: chrono::DateTime<Utc>) -> SqlResult<f64> {
+        let conn = self.get_conn()?;
+        conn.query_row(
+            "SELECT COALESCE(SUM(cost_usd), 0) FROM ai_usage WHERE created_at >= ?1",
+            params![since.to_rfc3339()],
+            |row| row.get(0),
+        )
+    }
+
+    /// A cached reply written after `fresh_after`; marks it as used
+    pub fn get_ai_cache(&self, key: &str, fresh_after: chrono::DateTime<Utc>) -> SqlResult<Option<String>> {
+        let conn = self.get_conn()?;
+        let response = conn.query_row(
+            "SELECT response FROM ai_cache WHERE key = ?1 AND created_at >= ?2",
+            params![key, fresh_after.to_rfc3339()],
+            |row| row.get::<_, String>(0),
+        );
+
+        match response {
+            Ok(response) => {
+                conn.execute(
+                    "UPDATE ai_cache SET last_used_at = ?1 WHERE key = ?2",
+                    params![Utc::now().to_rfc3339(), key],
+                )?;
+                Ok(Some(response))
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn put_ai_cache(&self, key: &str, purpose: &str, response: &str) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT OR REPLACE INTO ai_cache (key, purpose, response, created_at, last_used_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![key, purpose, response, now],
+        )?;
+        Ok(())
+    }
+
+    /// Drop entries written before `expired_before`, then the least recently used
+    /// beyond `max_entries`. Returns the number removed.
+    pub fn prune_ai_cache(&self, expired_before: chrono::DateTime<Utc>, max_entries: u32) -> SqlResult<usize> {
+        let conn = self.get_conn()?;
+        let expired = conn.execute(
+            "DELETE FROM ai_cache WHERE created_at < ?1",
+            params![expired_before.to_rfc3339()],
+        )?;
+        let evicted = conn.execute(
+            "DELETE FROM ai_cache WHERE key NOT IN
+                (SELECT key FROM ai_cache ORDER BY last_used_at DESC LIMIT ?1)",
+            params![max_entries],
+        )?;
+        Ok(expired + evicted)
+    }
+
+    // ============================================
+    // Action Journal Operations
+    // ============================================
+
+    pub fn create_journal_entry(&self, entry: &JournalEntry) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "INSERT INTO action_journal (id, trigger_source, action_type, description, project_id, undo, created_at, undone_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                entry.id,
+                serde_json::to_string(&entry.trigger).unwrap().trim_matches('"'),
+                entry.action_type,
+                entry.description,
+                entry.project_id,
+                serde_json::to_string(&entry.undo).unwrap(),
+                entry.created_at.to_rfc3339(),
+                entry.undone_at.map(|dt| dt.to_rfc3339()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent entries first
+    pub fn get_journal_entries(&self, limit: i32) -> SqlResult<Vec<JournalEntry>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, trigger_source, action_type, description, project_id, undo, created_at, undone_at
+             FROM action_journal ORDER BY created_at DESC LIMIT ?1"
+        )?;
+        let entries = stmt
+            .query_map(params![limit], Self::row_to_journal_entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    pub fn get_journal_entry(&self, id: &str) -> SqlResult<Option<JournalEntry>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT id, trigger_source, action_type, description, project_id, undo, created_at, undone_at
+             FROM action_journal WHERE id = ?1",
+            params![id],
+            Self::row_to_journal_entry,
+        );
+
+        match result {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The newest chat or inbox entry that hasn't been undone. Scheduler entries happen
+    /// in the background, so they are only undone by id.
+    pub fn get_last_undoable_entry(&self) -> SqlResult<Option<JournalEntry>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            "SELECT id, trigger_source, action_type, description, project_id, undo, created_at, undone_at
+             FROM action_journal WHERE undone_at IS NULL AND trigger_source IN ('chat', 'inbox')
+             ORDER BY created_at DESC LIMIT 1",
+            [],
+            Self::row_to_journal_entry,
+        );
+
+        match result {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn mark_journal_entry_undone(&self, id: &str) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "UPDATE action_journal SET undone_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(())
+    }
+
+    fn row_to_journal_entry(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+        let trigger = match row.get::<_, String>(1)?.as_str() {
+            "inbox" => ActionTrigger::Inbox,
+            "scheduler" => ActionTrigger::Scheduler,
+            _ => ActionTrigger::Chat,
+        };
+        let undo: String = row.get(5)?;
+        let undo = serde_json::from_str(&undo).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+        let parse_time = |s: String| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+        };
+
+        Ok(JournalEntry {
+            id: row.get(0)?,
+            trigger,
+            action_type: row.get(2)?,
+            description: row.get(3)?,
+            project_id: row.get(4)?,
+            undo,
+            created_at: parse_time(row.get(6)?).unwrap_or_else(Utc::now),
+            undone_at: row.get::<_, Option<String>>(7)?.and_then(parse_time),
+        })
+    }
+
+    // ============================================
+    // Embedding Operations
+    // ============================================
+
+    /// Content hash of every indexed record, by kind and id
+    pub fn get_embedding_hashes(&self) -> SqlResult<std::collections::HashMap<(SourceKind, String), String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT source_kind, source_id, content_hash FROM embeddings")?;
+        let hashes = stmt
+            .query_map([], |row| {
+                let kind: String = row.get(0)?;
+                Ok(((Self::parse_source_kind(&kind), row.get(1)?), row.get(2)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(hashes)
+    }
+
+    pub fn upsert_embedding(&self, doc: &EmbeddingDocument, model: &str, content_hash: &str, vector: &[f32]) -> SqlResult<()> {
+        let conn = self.get_conn()?;
+        let bytes: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        conn.execute(
+            "INSERT OR REPLACE INTO embeddings (source_kind, source_id, project_id, date, text, model, content_hash, vector, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                doc.kind.as_str(),
+                doc.id,
+                doc.project_id,
+                doc.date,
+                doc.text,
+                model,
+                content_hash,
+                bytes,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_embeddings(&self, keys: &[(SourceKind, String)]) -> SqlResult<usize> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        let mut deleted = 0;
+        for (kind, id) in keys {
+            deleted += tx.execute(
+                "DELETE FROM embeddings WHERE source_kind = ?1 AND source_id = ?2",
+                params![kind.as_str(), id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Drop vectors of records whose project has been deleted
+    pub fn delete_orphan_embeddings(&self) -> SqlResult<usize> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            "DELETE FROM embeddings WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects)",
+            [],
+        )
+    }
+
+    /// Every record embedded with `model`, with its vector
+    pub fn get_embeddings(&self, model: &str) -> SqlResult<Vec<(EmbeddingDocument, Vec<f32>)>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT source_kind, source_id, project_id, date, text, vector FROM embeddings WHERE model = ?1"
+        )?;
+        let embeddings = stmt
+            .query_map(params![model], |row| {
+                let kind: String = row.get(0)?;
+                let bytes: Vec<u8> = row.get(5)?;
+                let vector = bytes.chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                Ok((
+                    EmbeddingDocument {
+                        kind: Self::parse_source_kind(&kind),
+                        id: row.get(1)?,
+                        project_id: row.get(2)?,
+                        date: row.get(3)?,
+                        text: row.get(4)?,
+                    },
+                    vector,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(embeddings)
+    }
+
+    fn parse_source_kind(kind: &str) -> SourceKind {
+        match kind {
+            "commit" => SourceKind::Commit,
+            "todo" => SourceKind::Todo,
+            "milestone" => SourceKind::Milestone,
+            "chat" => SourceKind::Chat,
+            _ => SourceKind::Log,
+        }
+    }
+
+    fn parse_ai_purpose(purpose: &str) -> AiPurpose {
+        match purpose {
+            "classify" => AiPurpose::Classify,
+            "summary" => AiPurpose::Summary,
+            "analysis" => AiPurpose::Analysis,
+            "intent" => AiPurpose::Intent,
+            "anomaly" => AiPurpose::Anomaly,
+            "agent" => AiPurpose::Agent,
+            _ => AiPurpose::Chat,
+        }
+    }
+
     // ============================================
     // Statistics Operations
     // ============================================
@@ -924,3 +2141,87 @@ impl Database {
         Ok(distribution)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A fresh database with one project, kept until the returned dir is dropped
+    fn open() -> (TempDir, Database, Project) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::new(dir.path().to_path_buf()).unwrap();
+        let project = Project::new("Parser".to_string(), "/tmp/parser".to_string());
+        db.create_project(&project).unwrap();
+        (dir, db, project)
+    }
+
+    fn log(project: &Project, summary: &str, files: usize, summary_by_user: bool) -> DailyLog {
+        DailyLog {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: project.id.clone(),
+            date: "2024-03-01".to_string(),
+            summary: summary.to_string(),
+            category: LogCategory::Feature,
+            files_changed: (0..files)
+                .map(|i| FileChange { path: format!("src/{}.rs", i), additions: 10, deletions: 0 })
+                .collect(),
+            ai_classification: None,
+            user_override: None,
+            created_at: Utc::now(),
+            prompt_version: None,
+            summary_by_user,
+        }
+    }
+
+    #[test]
+    fn test_upsert_daily_log() {
+        let (_dir, db, project) = open();
+
+        let first = db.upsert_daily_log(&log(&project, "Started the parser", 1, false)).unwrap();
+        let stored = db.get_daily_log_for_date(&project.id, "2024-03-01").unwrap().unwrap();
+        assert_eq!(stored.id, first.id);
+        assert_eq!(stored.summary, "Started the parser");
+        assert!(db.get_daily_log_revisions(&first.id).unwrap().is_empty());
+
+        let rewritten = db.upsert_daily_log(&log(&project, "Finished the parser", 3, false)).unwrap();
+        assert_eq!(rewritten.id, first.id);
+        let stored = db.get_daily_log_for_date(&project.id, "2024-03-01").unwrap().unwrap();
+        assert_eq!(stored.summary, "Finished the parser");
+        assert_eq!(stored.files_changed.len(), 3);
+        let revisions = db.get_daily_log_revisions(&first.id).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].summary, "Started the parser");
+    }
+
+    #[test]
+    fn test_upsert_keeps_user_summary_from_insert() {
+        let (_dir, db, project) = open();
+
+        db.upsert_daily_log(&log(&project, "Paired on the release", 1, true)).unwrap();
+        db.upsert_daily_log(&log(&project, "Updated 4 files", 4, false)).unwrap();
+
+        let stored = db.get_daily_log_for_date(&project.id, "2024-03-01").unwrap().unwrap();
+        assert!(stored.summary_by_user);
+        assert_eq!(stored.summary, "Paired on the release");
+        assert_eq!(stored.files_changed.len(), 4);
+    }
+
+    #[test]
+    fn test_create_daily_log_keeps_existing_day() {
+        let (_dir, db, project) = open();
+        let mut scanned = log(&project, "Scanned", 2, false);
+        scanned.user_override = Some("bugfix".to_string());
+        db.upsert_daily_log(&scanned).unwrap();
+
+        assert!(!db.create_daily_log(&log(&project, "Backfilled", 1, false)).unwrap());
+        let stored = db.get_daily_log_for_date(&project.id, "2024-03-01").unwrap().unwrap();
+        assert_eq!(stored.id, scanned.id);
+        assert_eq!(stored.summary, "Scanned");
+        assert_eq!(stored.user_override.as_deref(), Some("bugfix"));
+
+        let mut next_day = log(&project, "Backfilled", 1, false);
+        next_day.date = "2024-03-02".to_string();
+        assert!(db.create_daily_log(&next_day).unwrap());
+    }
+}
@@ -0,0 +1,367 @@
+// Embeddings module - a semantic index of logs, commits, todos and chat for search by meaning
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{DateTime, Duration as DateDuration, Local, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use crate::db::Database;
+use crate::models::{EmbeddingDocument, EmbeddingSettings, ProjectStatus, SemanticMatch, SourceKind, UserSettings};
+use crate::scanner::GitScanner;
+
+/// Chat messages indexed, newest first
+const MAX_CHAT_MESSAGES: i32 = 5000;
+/// Longest text embedded per record; longer chat replies are cut
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// A record in the index: its kind and id
+type IndexKey = (SourceKind, String);
+
+pub type EmbedFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Vec<f32>>, String>> + Send + 'a>>;
+
+/// Turns texts into vectors. Vectors are only compared with others from the same `model_id`.
+pub trait Embedder: Send + Sync {
+    fn model_id(&self) -> String;
+    /// One vector per text, in order
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbedFuture<'a>;
+}
+
+/// An OpenAI-compatible `/embeddings` endpoint, such as a local Ollama or llama.cpp server
+pub struct EndpointEmbedder {
+    client: Client,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    timeout: Duration,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
+}
+
+impl EndpointEmbedder {
+    pub fn new(settings: &EmbeddingSettings, api_key: Option<String>, timeout: Duration) -> Self {
+        EndpointEmbedder {
+            client: Client::new(),
+            endpoint: settings.endpoint.trim().to_string(),
+            model: settings.model.trim().to_string(),
+            api_key,
+            timeout,
+        }
+    }
+}
+
+impl Embedder for EndpointEmbedder {
+    fn model_id(&self) -> String {
+        self.model.clone()
+    }
+
+    fn embed<'a>(&'a self, texts: &'a [String]) -> EmbedFuture<'a> {
+        Box::pin(async move {
+            let mut request = self.client
+                .post(&self.endpoint)
+                .timeout(self.timeout)
+                .json(&serde_json::json!({ "model": self.model, "input": texts }));
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Embedding request failed: {}", e))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(format!("Embedding endpoint returned {} {}", status, body));
+            }
+
+            let mut reply: EmbeddingResponse = response.json()
+                .await
+                .map_err(|e| format!("Failed to parse embeddings: {}", e))?;
+            if reply.data.len() != texts.len() {
+                return Err(format!("Expected {} embeddings, got {}", texts.len(), reply.data.len()));
+            }
+            reply.data.sort_by_key(|d| d.index);
+            Ok(reply.data.into_iter().map(|d| d.embedding).collect())
+        })
+    }
+}
+
+/// The embeddings endpoint's API key, loaded from the keychain at startup.
+/// Local servers need none; hosted ones get it as a bearer token.
+#[derive(Default)]
+pub struct EmbeddingsKey(Mutex<Option<String>>);
+
+impl EmbeddingsKey {
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, key: Option<String>) {
+        *self.0.lock().unwrap() = key.filter(|k| !k.trim().is_empty());
+    }
+}
+
+/// The embedder the settings describe, or an error when semantic search is off
+pub fn embedder(settings: &UserSettings, key: &EmbeddingsKey) -> Result<Box<dyn Embedder>, String> {
+    let embeddings = &settings.embeddings;
+    if !embeddings.enabled {
+        return Err("Semantic search is turned off in settings".to_string());
+    }
+    if embeddings.endpoint.trim().is_empty() || embeddings.model.trim().is_empty() {
+        return Err("Semantic search needs an embeddings endpoint and model".to_string());
+    }
+    let timeout = Duration::from_secs(settings.ai.request_timeout_secs);
+    Ok(Box::new(EndpointEmbedder::new(embeddings, key.get(), timeout)))
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct IndexStats {
+    pub embedded: usize,
+    pub removed: usize,
+}
+
+/// Identifies what a vector was computed from, so unchanged records aren't embedded again
+pub fn content_hash(model: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model);
+    hasher.update([0]);
+    hasher.update(text);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Everything the index covers. Commits are read back `commit_days` from each repository.
+pub fn collect_documents(db: &Database, scanner: &GitScanner, commit_days: u32) -> Result<Vec<EmbeddingDocument>, String> {
+    let mut docs = Vec::new();
+    let local_date = |at: DateTime<Utc>| at.with_timezone(&Local).date_naive().to_string();
+
+    for log in db.get_daily_logs_since("0000-00-00").map_err(|e| e.to_string())? {
+        docs.push(EmbeddingDocument {
+            kind: SourceKind::Log,
+            id: log.id,
+            project_id: Some(log.project_id),
+            date: log.date,
+            text: log.summary,
+        });
+    }
+
+    let today = Local::now().date_naive();
+    let since = (today - DateDuration::days(commit_days as i64)).to_string();
+    let until = today.to_string();
+    for project in db.get_projects().map_err(|e| e.to_string())? {
+        if project.status == ProjectStatus::Archived {
+            continue;
+        }
+        let Ok(commits) = scanner.get_commits(Path::new(&project.path), &since, &until) else { continue };
+        docs.extend(commits.into_iter().map(|c| EmbeddingDocument {
+            kind: SourceKind::Commit,
+            id: c.hash,
+            project_id: Some(project.id.clone()),
+            date: c.date,
+            text: c.subject,
+        }));
+    }
+
+    for todo in db.get_todos(None).map_err(|e| e.to_string())? {
+        let text = match todo.description.as_deref().map(str::trim) {
+            Some(description) if !description.is_empty() => format!("{}\n{}", todo.title, description),
+            _ => todo.title,
+        };
+        docs.push(EmbeddingDocument {
+            kind: SourceKind::Todo,
+            id: todo.id,
+            project_id: todo.project_id,
+            date: local_date(todo.created_at),
+            text,
+        });
+    }
+
+    for message in db.get_chat_messages(None, MAX_CHAT_MESSAGES).map_err(|e| e.to_string())? {
+        docs.push(EmbeddingDocument {
+            kind: SourceKind::Chat,
+            id: message.id,
+            project_id: message.project_id,
+            date: local_date(message.created_at),
+            text: message.content.chars().take(MAX_TEXT_CHARS).collect(),
+        });
+    }
+
+    docs.retain(|d| !d.text.trim().is_empty());
+    Ok(docs)
+}
+
+/// Records that are new or changed since they were embedded, and indexed records that are
+/// gone. Commits past the indexed window stay indexed; they can't change.
+fn plan_updates<'a>(
+    docs: &'a [EmbeddingDocument],
+    indexed: &HashMap<IndexKey, String>,
+    model: &str,
+) -> (Vec<(&'a EmbeddingDocument, String)>, Vec<IndexKey>) {
+    let stale = docs.iter()
+        .map(|doc| (doc, content_hash(model, &doc.text)))
+        .filter(|(doc, hash)| indexed.get(&(doc.kind, doc.id.clone())) != Some(hash))
+        .collect();
+
+    let current: HashSet<(SourceKind, &str)> = docs.iter().map(|d| (d.kind, d.id.as_str())).collect();
+    let mut removed: Vec<IndexKey> = indexed.keys()
+        .filter(|(kind, id)| *kind != SourceKind::Commit && !current.contains(&(*kind, id.as_str())))
+        .cloned()
+        .collect();
+    removed.sort_by(|a, b| a.1.cmp(&b.1));
+    (stale, removed)
+}
+
+/// Embed what changed since the last run and drop what was deleted
+pub async fn reindex(
+    db: &Database,
+    scanner: &GitScanner,
+    embedder: &dyn Embedder,
+    settings: &EmbeddingSettings,
+) -> Result<IndexStats, String> {
+    let docs = collect_documents(db, scanner, settings.commit_days)?;
+    let model = embedder.model_id();
+    let indexed = db.get_embedding_hashes().map_err(|e| e.to_string())?;
+    let (stale, removed) = plan_updates(&docs, &indexed, &model);
+
+    let mut stats = IndexStats::default();
+    for batch in stale.chunks(settings.batch_size.max(1) as usize) {
+        let texts: Vec<String> = batch.iter().map(|(doc, _)| doc.text.clone()).collect();
+        // Whatever was embedded before a failure is kept, so the next run picks up from there
+        let vectors = embedder.embed(&texts).await?;
+        for ((doc, hash), vector) in batch.iter().zip(&vectors) {
+            db.upsert_embedding(doc, &model, hash, vector).map_err(|e| e.to_string())?;
+        }
+        stats.embedded += batch.len();
+    }
+
+    stats.removed = db.delete_embeddings(&removed).map_err(|e| e.to_string())?
+        + db.delete_orphan_embeddings().map_err(|e| e.to_string())?;
+    Ok(stats)
+}
+
+/// The indexed records closest in meaning to `query`, best first
+pub async fn search(
+    db: &Database,
+    embedder: &dyn Embedder,
+    query: &str,
+    limit: usize,
+    kinds: Option<&[SourceKind]>,
+    project_id: Option<&str>,
+) -> Result<Vec<SemanticMatch>, String> {
+    let vectors = embedder.embed(&[query.to_string()]).await?;
+    let query_vector = vectors.first().ok_or("No embedding returned for the query")?;
+
+    let candidates = db.get_embeddings(&embedder.model_id())
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(doc, _)| kinds.is_none_or(|k| k.contains(&doc.kind)))
+        .filter(|(doc, _)| project_id.is_none_or(|p| doc.project_id.as_deref() == Some(p)));
+    Ok(rank(query_vector, candidates, limit))
+}
+
+/// The `limit` candidates most similar to `query`
+fn rank(
+    query: &[f32],
+    candidates: impl Iterator<Item = (EmbeddingDocument, Vec<f32>)>,
+    limit: usize,
+) -> Vec<SemanticMatch> {
+    let mut scored: Vec<(f32, EmbeddingDocument)> = candidates
+        .filter_map(|(doc, vector)| cosine_similarity(query, &vector).map(|score| (score, doc)))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(limit);
+
+    scored.into_iter()
+        .map(|(score, doc)| SemanticMatch {
+            kind: doc.kind,
+            id: doc.id,
+            date: doc.date,
+            project_id: doc.project_id,
+            text: doc.text,
+            score,
+        })
+        .collect()
+}
+
+/// None for vectors of different lengths, e.g. from another model, or all zeros
+fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    (denominator > 0.0).then(|| dot / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(kind: SourceKind, id: &str, text: &str) -> EmbeddingDocument {
+        EmbeddingDocument {
+            kind,
+            id: id.to_string(),
+            project_id: None,
+            date: "2024-01-09".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        let candidates = vec![
+            (doc(SourceKind::Log, "l1", "Fixed login redirect"), vec![0.9, 0.1]),
+            (doc(SourceKind::Todo, "t1", "Update docs"), vec![0.0, 1.0]),
+            (doc(SourceKind::Commit, "abc1234", "auth: rotate tokens"), vec![1.0, 0.0]),
+        ];
+        let matches = rank(&[1.0, 0.0], candidates.into_iter(), 2);
+        let ids: Vec<&str> = matches.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["abc1234", "l1"]);
+        assert!(matches[0].score > matches[1].score);
+    }
+
+    #[test]
+    fn test_plan_updates() {
+        let docs = vec![
+            doc(SourceKind::Log, "l1", "Fixed login redirect"),
+            doc(SourceKind::Todo, "t1", "Rate limit login"),
+        ];
+        let indexed = HashMap::from([
+            ((SourceKind::Log, "l1".to_string()), content_hash("m", "Fixed login redirect")),
+            ((SourceKind::Todo, "t1".to_string()), content_hash("m", "Rate limit")),
+            ((SourceKind::Todo, "gone".to_string()), content_hash("m", "Deleted")),
+            ((SourceKind::Commit, "old1234".to_string()), content_hash("m", "Older than the window")),
+        ]);
+
+        let (stale, removed) = plan_updates(&docs, &indexed, "m");
+        let stale_ids: Vec<&str> = stale.iter().map(|(d, _)| d.id.as_str()).collect();
+        assert_eq!(stale_ids, vec!["t1"]);
+        assert_eq!(removed, vec![(SourceKind::Todo, "gone".to_string())]);
+
+        // Another model invalidates every vector
+        let (stale, _) = plan_updates(&docs, &indexed, "other");
+        assert_eq!(stale.len(), 2);
+    }
+}
@@ -0,0 +1,63 @@
+use tauri::State;
+use crate::db::Database;
+use crate::embeddings::{self, EmbeddingsKey};
+use crate::keychain::Keychain;
+use crate::models::{SemanticMatch, SourceKind};
+use crate::scheduler::load_settings;
+
+/// Indexed records closest in meaning to `query`, best first. Only records embedded so far
+/// are searched; the `embeddings` scheduler job keeps the index current.
+#[tauri::command]
+pub async fn semantic_search(
+    db: State<'_, Database>,
+    embeddings_key: State<'_, EmbeddingsKey>,
+    query: String,
+    limit: Option<usize>,
+    kinds: Option<Vec<SourceKind>>,
+    project_id: Option<String>,
+) -> Result<Vec<SemanticMatch>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let embedder = embeddings::embedder(&load_settings(&db), &embeddings_key)?;
+    embeddings::search(
+        &db,
+        embedder.as_ref(),
+        query,
+        limit.unwrap_or(20).clamp(1, 100),
+        kinds.as_deref(),
+        project_id.as_deref(),
+    ).await
+}
+
+/// Store the key sent to the embeddings endpoint; an empty key removes it
+#[tauri::command]
+pub async fn set_embeddings_api_key(
+    keychain: State<'_, Keychain>,
+    embeddings_key: State<'_, EmbeddingsKey>,
+    key: String,
+) -> Result<(), String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return delete_embeddings_api_key(keychain, embeddings_key).await;
+    }
+    keychain.save_embeddings_api_key(key)?;
+    embeddings_key.set(Some(key.to_string()));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn has_embeddings_api_key(embeddings_key: State<'_, EmbeddingsKey>) -> Result<bool, String> {
+    Ok(embeddings_key.get().is_some())
+}
+
+#[tauri::command]
+pub async fn delete_embeddings_api_key(
+    keychain: State<'_, Keychain>,
+    embeddings_key: State<'_, EmbeddingsKey>,
+) -> Result<(), String> {
+    embeddings_key.set(None);
+    keychain.delete_embeddings_api_key()
+}
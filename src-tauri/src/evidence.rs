@@ -0,0 +1,32 @@
+// Evidence module - the labelled facts that back anomaly and pattern findings
+use serde::{Deserialize, Serialize};
+use crate::i18n::Locale;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evidence {
+    pub label: String,
+    pub value: String,
+}
+
+/// A finding that lists the evidence behind it
+pub trait Evidenced: Sized {
+    fn evidence(&self) -> &[Evidence];
+    fn evidence_mut(&mut self) -> &mut Vec<Evidence>;
+
+    fn with(mut self, label: String, value: impl ToString) -> Self {
+        self.evidence_mut().push(Evidence {
+            label,
+            value: value.to_string(),
+        });
+        self
+    }
+
+    /// Evidence formatted one item per line, for inbox context
+    fn evidence_text(&self, locale: Locale) -> String {
+        self.evidence()
+            .iter()
+            .map(|e| locale.t("evidence.line", &[("label", &e.label), ("value", &e.value)]))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
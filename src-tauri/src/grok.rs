@@ -1,15 +1,180 @@
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use crate::models::AiResponseWithActions;
+use std::time::{Duration, Instant};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{Mutex, Notify, OwnedSemaphorePermit, Semaphore};
+use crate::ai_cache::ResponseCache;
+use crate::i18n::Locale;
+use crate::models::{AiPurpose, AiResponseWithActions, AiSettings, AiUsage};
+use crate::prompts::{PromptTemplate, Prompts};
+use crate::structured::{self, JsonSchema, StructuredError, Validate};
 
 const GROK_API_URL: &str = "https://api.x.ai/v1/chat/completions";
+pub const GROK_PROVIDER: &str = "x.ai";
+pub const GROK_MODEL: &str = "grok-4-1-fast-reasoning";
+
+/// First retry delay when the server doesn't send `Retry-After`; doubles each attempt
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone)]
 pub struct GrokClient {
     client: Client,
     api_key: Arc<Mutex<Option<String>>>,
+    /// Cleared once the API rejects `response_format`, so later calls skip it
+    response_format_supported: Arc<AtomicBool>,
+    settings: Arc<std::sync::Mutex<AiSettings>>,
+    /// Limits requests in flight; replaced when the limit changes
+    permits: Arc<std::sync::Mutex<Arc<Semaphore>>>,
+    circuit: Arc<std::sync::Mutex<Circuit>>,
+    /// Where completed calls are reported for cost accounting
+    usage_sink: Arc<std::sync::OnceLock<UnboundedSender<AiUsage>>>,
+    prompts: Prompts,
+    /// Recorded with each call; set per caller with `for_purpose`
+    purpose: AiPurpose,
+    project_id: Option<String>,
+}
+
+/// Why an API call failed, by what the user can do about it
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrokError {
+    /// Missing, invalid or unauthorized API key
+    Auth(String),
+    /// Still rate limited after retries, or out of credits
+    Quota(String),
+    /// Timeouts, connection failures, server errors, or AI paused after repeated failures
+    Network(String),
+    /// The API answered with a body we couldn't read
+    Parse(String),
+    /// Any other rejected request, e.g. an unsupported parameter
+    Api(String),
+}
+
+impl std::fmt::Display for GrokError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GrokError::Auth(e) => write!(f, "Authentication failed: {}", e),
+            GrokError::Quota(e) => write!(f, "Quota exceeded: {}", e),
+            GrokError::Network(e) => write!(f, "Network error: {}", e),
+            GrokError::Parse(e) => write!(f, "Failed to parse response: {}", e),
+            GrokError::Api(e) => write!(f, "API error: {}", e),
+        }
+    }
+}
+
+impl From<GrokError> for String {
+    fn from(e: GrokError) -> String {
+        e.to_string()
+    }
+}
+
+impl GrokError {
+    pub fn kind(&self) -> AiErrorKind {
+        match self {
+            GrokError::Auth(_) => AiErrorKind::Auth,
+            GrokError::Quota(_) => AiErrorKind::Quota,
+            GrokError::Network(_) => AiErrorKind::Network,
+            GrokError::Parse(_) => AiErrorKind::Parse,
+            GrokError::Api(_) => AiErrorKind::Api,
+        }
+    }
+}
+
+/// What went wrong in an AI command, so the frontend can react without reading the message
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AiErrorKind {
+    Auth,
+    Quota,
+    Network,
+    Parse,
+    Api,
+    /// Not from the AI call, e.g. a database error
+    Other,
+}
+
+/// The error AI commands return, serialized as `{ kind, message }`
+#[derive(Debug, Clone, Serialize)]
+pub struct AiCommandError {
+    pub kind: AiErrorKind,
+    pub message: String,
+}
+
+impl From<GrokError> for AiCommandError {
+    fn from(e: GrokError) -> Self {
+        AiCommandError { kind: e.kind(), message: e.to_string() }
+    }
+}
+
+impl From<String> for AiCommandError {
+    fn from(message: String) -> Self {
+        AiCommandError { kind: AiErrorKind::Other, message }
+    }
+}
+
+/// Map an unsuccessful response to an error; rate limits and server errors are retryable
+fn classify_status(status: StatusCode, body: &str) -> (GrokError, bool) {
+    let detail = format!("{} {}", status, body);
+    match status.as_u16() {
+        401 | 403 => (GrokError::Auth(detail), false),
+        402 => (GrokError::Quota(detail), false),
+        429 => (GrokError::Quota(detail), true),
+        500..=599 => (GrokError::Network(detail), true),
+        _ => (GrokError::Api(detail), false),
+    }
+}
+
+/// `Retry-After` in seconds; the HTTP-date form is rare for APIs and treated as absent
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs: u64 = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// Delay before retry number `attempt` (0-based): the server's `Retry-After`, else exponential
+fn backoff_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempt)))
+        .min(MAX_BACKOFF)
+}
+
+/// Consecutive failed calls; once over the threshold, calls fail fast until the cooldown ends
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl Circuit {
+    fn check(&self, now: Instant) -> Result<(), GrokError> {
+        match self.open_until {
+            Some(until) if now < until => Err(GrokError::Network(format!(
+                "AI paused after {} failed requests, retrying in {}s",
+                self.failures,
+                (until - now).as_secs() + 1
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn record(&mut self, success: bool, settings: &AiSettings, now: Instant) {
+        if success {
+            *self = Circuit::default();
+            return;
+        }
+
+        self.failures += 1;
+        // After the cooldown a single failed trial call opens the circuit again
+        if self.failures >= settings.circuit_failure_threshold.max(1) {
+            log::warn!("AI paused for {}s after {} failed requests", settings.circuit_cooldown_secs, self.failures);
+            self.open_until = Some(now + Duration::from_secs(settings.circuit_cooldown_secs));
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -18,12 +183,75 @@ struct GrokRequest {
     messages: Vec<GrokMessage>,
     temperature: f32,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+    /// Function definitions the model may call instead of replying
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
+    /// `auto`, or `none` to make the model answer without calling tools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'static str>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GrokMessage {
     pub role: String,
+    /// Null in replies that only call tools, read as empty
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub content: String,
+    /// Functions the model wants called, on assistant messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// The call a `tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl GrokMessage {
+    pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        GrokMessage {
+            role: role.into(),
+            content: content.into(),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// The result of a tool call, sent back to the model
+    pub fn tool_result(tool_call_id: &str, content: impl Into<String>) -> Self {
+        GrokMessage {
+            tool_call_id: Some(tool_call_id.to_string()),
+            ..Self::new("tool", content)
+        }
+    }
+}
+
+fn null_as_empty<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A function call requested by the model; `arguments` is a JSON object as text
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: ToolFunction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ToolFunction {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -35,6 +263,13 @@ pub struct ChatHistoryItem {
 #[derive(Debug, Deserialize)]
 struct GrokResponse {
     choices: Vec<GrokChoice>,
+    usage: Option<GrokUsage>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+struct GrokUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,12 +277,218 @@ struct GrokChoice {
     message: GrokMessage,
 }
 
+#[derive(Debug, Deserialize)]
+struct GrokStreamChunk {
+    #[serde(default)]
+    choices: Vec<GrokStreamChoice>,
+    usage: Option<GrokUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrokStreamChoice {
+    delta: GrokDelta,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrokDelta {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ToolCallDelta>,
+}
+
+/// A piece of a streamed tool call; pieces with the same `index` make up one call
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+struct ToolCallDelta {
+    #[serde(default)]
+    index: usize,
+    id: Option<String>,
+    function: Option<ToolFunctionDelta>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+struct ToolFunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+/// Add streamed pieces to the tool calls received so far
+fn merge_tool_call_deltas(calls: &mut Vec<ToolCall>, deltas: Vec<ToolCallDelta>) {
+    for delta in deltas {
+        while calls.len() <= delta.index {
+            calls.push(ToolCall {
+                id: String::new(),
+                kind: function_type(),
+                function: ToolFunction { name: String::new(), arguments: String::new() },
+            });
+        }
+        let call = &mut calls[delta.index];
+        if let Some(id) = delta.id {
+            call.id = id;
+        }
+        if let Some(function) = delta.function {
+            call.function.name.push_str(&function.name.unwrap_or_default());
+            call.function.arguments.push_str(&function.arguments.unwrap_or_default());
+        }
+    }
+}
+
+/// One line of a server-sent event stream
+#[derive(Debug, PartialEq)]
+enum SseEvent {
+    Delta(String),
+    ToolCalls(Vec<ToolCallDelta>),
+    /// Token counts, sent in a final chunk without text
+    Usage(GrokUsage),
+    Done,
+}
+
+fn parse_sse_line(line: &str) -> Option<SseEvent> {
+    let data = line.trim_end_matches('\r').strip_prefix("data:")?.trim_start();
+    if data == "[DONE]" {
+        return Some(SseEvent::Done);
+    }
+
+    let chunk: GrokStreamChunk = serde_json::from_str(data).ok()?;
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    for choice in chunk.choices {
+        content.push_str(&choice.delta.content.unwrap_or_default());
+        tool_calls.extend(choice.delta.tool_calls);
+    }
+    if !content.is_empty() {
+        Some(SseEvent::Delta(content))
+    } else if !tool_calls.is_empty() {
+        Some(SseEvent::ToolCalls(tool_calls))
+    } else {
+        chunk.usage.map(SseEvent::Usage)
+    }
+}
+
+/// Text and tool calls received from a streamed completion
+pub struct StreamOutcome {
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub cancelled: bool,
+}
+
+impl StreamOutcome {
+    /// The reply as an assistant message, to send back with the results of its tool calls
+    pub fn into_message(self) -> GrokMessage {
+        GrokMessage {
+            tool_calls: Some(self.tool_calls).filter(|calls| !calls.is_empty()),
+            ..GrokMessage::new("assistant", self.content)
+        }
+    }
+}
+
+/// Cancellation handles for in-flight streamed chats, by request id
+#[derive(Clone, Default)]
+pub struct ChatStreams {
+    streams: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+}
+
+impl ChatStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn register(&self, request_id: &str) -> Arc<Notify> {
+        let cancel = Arc::new(Notify::new());
+        self.streams.lock().await.insert(request_id.to_string(), cancel.clone());
+        cancel
+    }
+
+    /// Returns false if no stream with this id is running
+    pub async fn cancel(&self, request_id: &str) -> bool {
+        match self.streams.lock().await.get(request_id) {
+            Some(cancel) => {
+                // Stores a permit if the stream isn't waiting right now
+                cancel.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn finish(&self, request_id: &str) {
+        self.streams.lock().await.remove(request_id);
+    }
+}
+
 impl GrokClient {
     pub fn new() -> Self {
+        let settings = AiSettings::default();
         GrokClient {
             client: Client::new(),
             api_key: Arc::new(Mutex::new(None)),
+            response_format_supported: Arc::new(AtomicBool::new(true)),
+            permits: Arc::new(std::sync::Mutex::new(Arc::new(Semaphore::new(
+                settings.max_concurrent_requests.max(1) as usize,
+            )))),
+            settings: Arc::new(std::sync::Mutex::new(settings)),
+            circuit: Arc::new(std::sync::Mutex::new(Circuit::default())),
+            usage_sink: Arc::new(std::sync::OnceLock::new()),
+            prompts: Prompts::new(),
+            purpose: AiPurpose::default(),
+            project_id: None,
+        }
+    }
+
+    /// A handle on the same client whose calls are recorded under `purpose` and `project_id`
+    pub fn for_purpose(&self, purpose: AiPurpose, project_id: Option<&str>) -> GrokClient {
+        GrokClient {
+            purpose,
+            project_id: project_id.map(str::to_string),
+            ..self.clone()
+        }
+    }
+
+    /// Prompt templates used by this client and `AiAgent`
+    pub fn prompts(&self) -> &Prompts {
+        &self.prompts
+    }
+
+    /// Report every completed call to `sink`; only the first sink set is used
+    pub fn set_usage_sink(&self, sink: UnboundedSender<AiUsage>) {
+        let _ = self.usage_sink.set(sink);
+    }
+
+    fn record_usage(&self, usage: Option<&GrokUsage>, started: Instant) {
+        let Some(sink) = self.usage_sink.get() else { return };
+        let (prompt_tokens, completion_tokens) = usage.map_or((0, 0), |u| (u.prompt_tokens, u.completion_tokens));
+        let cost_usd = {
+            let settings = self.settings.lock().unwrap();
+            (prompt_tokens as f64 * settings.prompt_price_per_million
+                + completion_tokens as f64 * settings.completion_price_per_million)
+                / 1_000_000.0
+        };
+
+        let _ = sink.send(AiUsage {
+            id: uuid::Uuid::new_v4().to_string(),
+            model: GROK_MODEL.to_string(),
+            purpose: self.purpose,
+            project_id: self.project_id.clone(),
+            prompt_tokens,
+            completion_tokens,
+            latency_ms: started.elapsed().as_millis() as u64,
+            cost_usd,
+            created_at: chrono::Utc::now(),
+        });
+    }
+
+    /// Apply timeout, retry, concurrency and circuit breaker settings.
+    /// Requests already in flight keep the limits they started with.
+    pub fn configure(&self, settings: &AiSettings) {
+        let mut current = self.settings.lock().unwrap();
+        if current.max_concurrent_requests != settings.max_concurrent_requests {
+            *self.permits.lock().unwrap() = Arc::new(Semaphore::new(settings.max_concurrent_requests.max(1) as usize));
         }
+        *current = settings.clone();
+    }
+
+    /// True while the circuit breaker is holding off AI work after repeated failures
+    pub fn is_paused(&self) -> bool {
+        self.circuit.lock().unwrap().check(Instant::now()).is_err()
     }
 
     pub async fn set_api_key(&self, key: String) {
@@ -60,136 +501,281 @@ impl GrokClient {
         api_key.is_some()
     }
 
-    pub async fn chat(&self, messages: Vec<GrokMessage>) -> Result<String, String> {
-        let api_key = {
-            let key = self.api_key.lock().await;
-            key.clone().ok_or("API key not set")?
-        };
+    pub async fn chat(&self, messages: Vec<GrokMessage>) -> Result<String, GrokError> {
+        self.send(messages, None).await
+    }
+
+    /// Chat expecting a JSON reply of type `T`. The schema goes to the API as
+    /// `response_format` when supported; replies that don't parse or validate are
+    /// retried with the error fed back, up to `structured::MAX_ATTEMPTS` times.
+    pub async fn chat_structured<T: DeserializeOwned + Validate>(
+        &self,
+        mut messages: Vec<GrokMessage>,
+        schema: &JsonSchema,
+    ) -> Result<T, StructuredError> {
+        let mut last_error = String::new();
+        let mut raw = String::new();
+
+        for attempt in 1..=structured::MAX_ATTEMPTS {
+            let response_format = if self.response_format_supported.load(Ordering::Relaxed) {
+                Some(schema.response_format())
+            } else {
+                None
+            };
+
+            raw = match self.send(messages.clone(), response_format).await {
+                Ok(reply) => reply,
+                Err(GrokError::Api(e)) if e.contains("response_format") && self.response_format_supported.swap(false, Ordering::Relaxed) => {
+                    log::warn!("API rejected response_format, falling back to prompt-only JSON: {}", e);
+                    self.send(messages.clone(), None).await.map_err(|e| StructuredError::Request(e.to_string()))?
+                }
+                Err(e) => return Err(StructuredError::Request(e.to_string())),
+            };
+
+            match structured::parse::<T>(&raw) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    log::warn!("Invalid {} response (attempt {}): {}", schema.name, attempt, e);
+                    messages.push(GrokMessage::new("assistant", raw.clone()));
+                    messages.push(GrokMessage::new("user", self.prompts.get("structured_retry").render("user", &[("error", &e)])));
+                    last_error = e;
+                }
+            }
+        }
 
+        Err(StructuredError::Invalid { error: last_error, raw })
+    }
+
+    /// Stream a completion, calling `on_delta` with each piece of text as it arrives.
+    /// Stops early, keeping what was received, when `cancel` is notified.
+    pub async fn chat_stream<F: FnMut(&str)>(
+        &self,
+        messages: Vec<GrokMessage>,
+        response_format: Option<serde_json::Value>,
+        cancel: &Notify,
+        on_delta: F,
+    ) -> Result<StreamOutcome, GrokError> {
+        let response_format = response_format.filter(|_| self.response_format_supported.load(Ordering::Relaxed));
+        self.stream(messages, response_format, None, cancel, on_delta).await
+    }
+
+    /// One step of a tool-calling conversation, streamed like `chat_stream`: the reply
+    /// either has `tool_calls` to run and answer with `GrokMessage::tool_result`, or is
+    /// the final answer. With `allow_calls` false the model has to answer.
+    pub async fn chat_with_tools<F: FnMut(&str)>(
+        &self,
+        messages: Vec<GrokMessage>,
+        tools: &[serde_json::Value],
+        allow_calls: bool,
+        cancel: &Notify,
+        on_delta: F,
+    ) -> Result<StreamOutcome, GrokError> {
+        let choice = if allow_calls { "auto" } else { "none" };
+        self.stream(messages, None, Some((tools.to_vec(), choice)), cancel, on_delta).await
+    }
+
+    async fn stream<F: FnMut(&str)>(
+        &self,
+        messages: Vec<GrokMessage>,
+        response_format: Option<serde_json::Value>,
+        tools: Option<(Vec<serde_json::Value>, &'static str)>,
+        cancel: &Notify,
+        mut on_delta: F,
+    ) -> Result<StreamOutcome, GrokError> {
+        let (tools, tool_choice) = tools.unzip();
         let request = GrokRequest {
-            model: "grok-4-1-fast-reasoning".to_string(),
+            model: GROK_MODEL.to_string(),
             messages,
             temperature: 0.7,
             max_tokens: 2048,
+            response_format,
+            stream: true,
+            stream_options: Some(serde_json::json!({ "include_usage": true })),
+            tools,
+            tool_choice,
         };
 
-        let response = self.client
-            .post(GROK_API_URL)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
+        let started = Instant::now();
+        let (mut response, _permit) = self.post(&request).await?;
+        // The timeout applies to each gap between chunks rather than the whole reply
+        let idle_timeout = Duration::from_secs(self.settings.lock().unwrap().request_timeout_secs);
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(format!("API error {}: {}", status, error_text));
-        }
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        let mut usage = None;
+        // Bytes of an incomplete line; a chunk may end mid-line or mid-character
+        let mut pending: Vec<u8> = Vec::new();
+
+        let cancelled = 'stream: loop {
+            let chunk = tokio::select! {
+                chunk = tokio::time::timeout(idle_timeout, response.chunk()) => chunk
+                    .map_err(|_| GrokError::Network("Stream timed out".to_string()))?
+                    .map_err(|e| GrokError::Network(format!("Stream failed: {}", e)))?,
+                _ = cancel.notified() => break true,
+            };
+
+            let Some(chunk) = chunk else { break false };
+            pending.extend_from_slice(&chunk);
+
+            while let Some(newline) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                match parse_sse_line(&String::from_utf8_lossy(&line[..line.len() - 1])) {
+                    Some(SseEvent::Delta(delta)) => {
+                        content.push_str(&delta);
+                        on_delta(&delta);
+                    }
+                    Some(SseEvent::ToolCalls(deltas)) => merge_tool_call_deltas(&mut tool_calls, deltas),
+                    Some(SseEvent::Usage(u)) => usage = Some(u),
+                    Some(SseEvent::Done) => break 'stream false,
+                    None => {}
+                }
+            }
+        };
+
+        self.record_usage(usage.as_ref(), started);
+        Ok(StreamOutcome { content, tool_calls, cancelled })
+    }
 
+    async fn send(
+        &self,
+        messages: Vec<GrokMessage>,
+        response_format: Option<serde_json::Value>,
+    ) -> Result<String, GrokError> {
+        let request = GrokRequest {
+            model: GROK_MODEL.to_string(),
+            messages,
+            temperature: 0.7,
+            max_tokens: 2048,
+            response_format,
+            stream: false,
+            stream_options: None,
+            tools: None,
+            tool_choice: None,
+        };
+
+        let started = Instant::now();
+        let (response, _permit) = self.post(&request).await?;
         let grok_response: GrokResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
+            .map_err(|e| GrokError::Parse(e.to_string()))?;
+        self.record_usage(grok_response.usage.as_ref(), started);
 
         grok_response
             .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .ok_or_else(|| "No response from Grok".to_string())
+            .into_iter()
+            .next()
+            .map(|c| c.message.content)
+            .ok_or_else(|| GrokError::Parse("No response from Grok".to_string()))
     }
 
-    pub async fn classify_changes(&self, files_changed: &str, diff_summary: &str) -> Result<String, String> {
-        let system_prompt = r#"你係一個開發進度追蹤助手。你嘅任務係分析 git 改動，然後分類呢啲改動屬於邊種類型。
+    /// Send a request, retrying rate limits, server and network errors with backoff.
+    /// The returned permit holds a concurrency slot until the body has been read.
+    async fn post(&self, request: &GrokRequest) -> Result<(reqwest::Response, OwnedSemaphorePermit), GrokError> {
+        let api_key = {
+            let key = self.api_key.lock().await;
+            key.clone().ok_or_else(|| GrokError::Auth("API key not set".to_string()))?
+        };
 
-分類選項：
-- feature: 新功能
-- bugfix: 修復 bug
-- refactor: 重構代碼
-- ui: UI/UX 改動
-- docs: 文檔更新
-- test: 測試相關
-- chore: 雜項（配置、依賴等）
-- other: 其他
+        let settings = self.settings.lock().unwrap().clone();
+        self.circuit.lock().unwrap().check(Instant::now())?;
 
-請只回覆分類名稱，唔好解釋。"#;
+        let permits = self.permits.lock().unwrap().clone();
+        let permit = permits.acquire_owned().await.map_err(|e| GrokError::Network(e.to_string()))?;
+        let timeout = Duration::from_secs(settings.request_timeout_secs);
 
-        let user_prompt = format!(
-            "改動嘅檔案：\n{}\n\n改動摘要：\n{}\n\n呢啲改動屬於邊種分類？",
-            files_changed, diff_summary
-        );
+        let mut attempt = 0;
+        loop {
+            let mut builder = self.client
+                .post(GROK_API_URL)
+                .header("Authorization", format!("Bearer {}", api_key))
+                .header("Content-Type", "application/json")
+                .json(request);
+            // A streamed reply can take longer than the timeout in total, so it only bounds the headers
+            if !request.stream {
+                builder = builder.timeout(timeout);
+            }
 
-        let messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: system_prompt.to_string(),
-            },
-            GrokMessage {
-                role: "user".to_string(),
-                content: user_prompt,
-            },
-        ];
+            let result = match tokio::time::timeout(timeout, builder.send()).await {
+                Ok(result) => result.map_err(|e| format!("Request failed: {}", e)),
+                Err(_) => Err(format!("Request timed out after {}s", settings.request_timeout_secs)),
+            };
 
-        self.chat(messages).await
-    }
+            let (error, retryable, wait) = match result {
+                Ok(response) if response.status().is_success() => {
+                    self.circuit.lock().unwrap().record(true, &settings, Instant::now());
+                    return Ok((response, permit));
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let wait = retry_after(response.headers());
+                    let body = response.text().await.unwrap_or_default();
+                    let (error, retryable) = classify_status(status, &body);
+                    (error, retryable, wait)
+                }
+                Err(e) => (GrokError::Network(e), true, None),
+            };
 
-    pub async fn generate_summary(&self, files_changed: &str, context: &str) -> Result<String, String> {
-        let system_prompt = r#"你係一個開發進度追蹤助手。你嘅任務係根據 git 改動生成簡潔嘅中文摘要。
-
-要求：
-- 用廣東話書寫
-- 簡潔明瞭，一到兩句話
-- 描述主要改動嘅目的同內容
-- 唔好列出具體檔案名"#;
+            if !retryable || attempt >= settings.max_retries {
+                if matches!(error, GrokError::Network(_)) {
+                    self.circuit.lock().unwrap().record(false, &settings, Instant::now());
+                }
+                return Err(error);
+            }
 
-        let user_prompt = format!(
-            "改動嘅檔案：\n{}\n\n背景：\n{}\n\n請生成一個簡潔嘅摘要。",
-            files_changed, context
-        );
+            let delay = backoff_delay(attempt, wait);
+            log::warn!("AI request failed ({}), retrying in {}s", error, delay.as_secs());
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
 
-        let messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: system_prompt.to_string(),
-            },
-            GrokMessage {
-                role: "user".to_string(),
-                content: user_prompt,
-            },
-        ];
+    /// Classify changes into a log category. `examples` is prepended to the question,
+    /// typically the user's past corrections; pass "" for none.
+    pub async fn classify_changes(
+        &self,
+        cache: &ResponseCache<'_>,
+        files_changed: &str,
+        diff_summary: &str,
+        examples: &str,
+    ) -> Result<String, String> {
+        let template = self.prompts.get("classify");
+        let messages = template.messages(&[
+            ("examples", examples),
+            ("files_changed", files_changed),
+            ("diff_summary", diff_summary),
+        ]);
 
-        self.chat(messages).await
+        cache
+            .get_or_fetch(AiPurpose::Classify, &template.version_id(), &messages, || self.chat(messages.clone()))
+            .await
+            .map_err(|e| e.to_string())
     }
 
-    pub async fn chat_with_context(&self, user_message: &str, project_context: Option<&str>) -> Result<String, String> {
-        let system_prompt = format!(
-            r#"你係 Sixarms，一個 AI 開發進度追蹤助手。你用廣東話同用戶溝通。
-
-你嘅職責：
-1. 幫用戶記錄每日嘅開發進度
-2. 分析 git 改動並提供分類建議
-3. 管理 TODO 清單
-4. 追蹤項目進度
+    pub async fn generate_summary(
+        &self,
+        cache: &ResponseCache<'_>,
+        files_changed: &str,
+        context: &str,
+    ) -> Result<String, String> {
+        let template = self.prompts.get("summary");
+        let messages = template.messages(&[("files_changed", files_changed), ("context", context)]);
 
-{}
+        cache
+            .get_or_fetch(AiPurpose::Summary, &template.version_id(), &messages, || self.chat(messages.clone()))
+            .await
+            .map_err(|e| e.to_string())
+    }
 
-請用友善、專業嘅語氣回應。"#,
-            project_context.map(|c| format!("當前項目背景：\n{}", c)).unwrap_or_default()
-        );
+    pub async fn chat_with_context(&self, user_message: &str, project_context: Option<&str>) -> Result<String, String> {
+        let system_prompt = chat_system_prompt(&self.prompts.get("chat"), project_context, &[]);
 
         let messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: system_prompt,
-            },
-            GrokMessage {
-                role: "user".to_string(),
-                content: user_message.to_string(),
-            },
+            GrokMessage::new("system", system_prompt),
+            GrokMessage::new("user", user_message),
         ];
 
-        self.chat(messages).await
+        self.chat(messages).await.map_err(|e| e.to_string())
     }
 
     /// Truncate history to fit within token limit
@@ -221,44 +807,34 @@ impl GrokClient {
         project_context: Option<&str>,
         max_history_tokens: usize,
     ) -> Result<String, String> {
-        let system_prompt = format!(
-            r#"你係 Sixarms，一個 AI 開發進度追蹤助手。你用廣東話同用戶溝通。
-
-你嘅職責：
-1. 幫用戶記錄每日嘅開發進度
-2. 分析 git 改動並提供分類建議
-3. 管理 TODO 清單
-4. 追蹤項目進度
-
-{}
+        let messages = self.history_messages(user_message, history, project_context, max_history_tokens);
+        self.chat(messages).await.map_err(|e| e.to_string())
+    }
 
-請用友善、專業嘅語氣回應。"#,
-            project_context.map(|c| format!("當前項目背景：\n{}", c)).unwrap_or_default()
-        );
+    /// Build the messages for a plain chat turn: system prompt, truncated history, user message
+    pub fn history_messages(
+        &self,
+        user_message: &str,
+        history: Vec<ChatHistoryItem>,
+        project_context: Option<&str>,
+        max_history_tokens: usize,
+    ) -> Vec<GrokMessage> {
+        let system_prompt = chat_system_prompt(&self.prompts.get("chat"), project_context, &[]);
 
         let mut messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: system_prompt,
-            },
+            GrokMessage::new("system", system_prompt),
         ];
 
         // Add truncated history
         let truncated = self.truncate_history(history, max_history_tokens);
         for item in truncated {
-            messages.push(GrokMessage {
-                role: item.role,
-                content: item.content,
-            });
+            messages.push(GrokMessage::new(item.role, item.content));
         }
 
         // Add current user message
-        messages.push(GrokMessage {
-            role: "user".to_string(),
-            content: user_message.to_string(),
-        });
+        messages.push(GrokMessage::new("user", user_message));
 
-        self.chat(messages).await
+        messages
     }
 
     /// Chat with intent detection - returns structured response with actions
@@ -267,105 +843,133 @@ impl GrokClient {
         user_message: &str,
         history: Vec<ChatHistoryItem>,
         project_context: Option<&str>,
-        _project_id: Option<&str>,
+        project_id: Option<&str>,
     ) -> Result<AiResponseWithActions, String> {
-        let system_prompt = format!(
-            r#"你係 Sixarms，一個 AI 開發進度追蹤助手。你用廣東話同用戶溝通。
-
-你嘅職責：
-1. 幫用戶記錄每日嘅開發進度
-2. 識別用戶意圖並建議相應動作
-3. 管理 TODO 清單
-4. 追蹤項目進度
-
-{}
-
-重要：你需要分析用戶訊息，識別以下意圖：
-- create_todo: 用戶想創建待辦事項（關鍵詞：要做、待辦、todo、記住、提醒、之後要）
-- log_progress: 用戶描述完成嘅工作（關鍵詞：完成、做咗、實現咗、修復咗、搞掂）
-- create_inbox_item: 需要跟進或確認嘅事項
-- general_chat: 一般對話
-
-回覆格式必須係 JSON：
-{{
-  "message": "你嘅回覆內容（用廣東話，友善專業）",
-  "detected_actions": [
-    {{
-      "intent": "create_todo|log_progress|create_inbox_item|general_chat",
-      "confidence": 0.0-1.0,
-      "data": {{
-        "type": "todo|progress|inbox|none",
-        ...
-      }},
-      "confirmed": false
-    }}
-  ]
-}}
-
-data 格式：
-- create_todo: {{"type": "todo", "title": "任務標題", "priority": "low|medium|high|urgent", "due_date": null}}
-- log_progress: {{"type": "progress", "summary": "進度摘要", "category": "feature|bugfix|refactor|ui|docs|test|chore|other", "date": null}}
-- create_inbox_item: {{"type": "inbox", "question": "需要跟進嘅問題", "item_type": "todo_followup|planning"}}
-- general_chat: {{"type": "none"}}
-
-請用友善、專業嘅語氣回應，並準確識別用戶意圖。只輸出 JSON，唔好有其他內容。"#,
-            project_context.map(|c| format!("當前項目背景：\n{}", c)).unwrap_or_default()
-        );
+        let (messages, prompt_version) = self.intent_messages(user_message, history, project_context);
+
+        match self.for_purpose(AiPurpose::Intent, project_id).chat_structured::<AiResponseWithActions>(messages, &intent_schema()).await {
+            Ok(parsed) => Ok(AiResponseWithActions {
+                prompt_version: Some(prompt_version),
+                ..parsed
+            }),
+            // The reply is still meant for the user; show what can be read of it without actions
+            Err(StructuredError::Invalid { error, raw }) => {
+                log::warn!("AI response had no usable actions: {}", error);
+                Ok(AiResponseWithActions {
+                    message: readable_message(&raw, Locale::new(self.prompts.language())),
+                    detected_actions: vec![],
+                    prompt_version: Some(prompt_version),
+                })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Build the messages for a chat turn whose reply carries detected actions as JSON,
+    /// along with the version of the template used
+    pub fn intent_messages(
+        &self,
+        user_message: &str,
+        history: Vec<ChatHistoryItem>,
+        project_context: Option<&str>,
+    ) -> (Vec<GrokMessage>, String) {
+        let template = self.prompts.get("intent");
+        let system_prompt = chat_system_prompt(&template, project_context, &[]);
 
         let mut messages = vec![
-            GrokMessage {
-                role: "system".to_string(),
-                content: system_prompt,
-            },
+            GrokMessage::new("system", system_prompt),
         ];
 
         // Add truncated history
         let truncated = self.truncate_history(history, 3000);
         for item in truncated {
-            messages.push(GrokMessage {
-                role: item.role,
-                content: item.content,
-            });
+            messages.push(GrokMessage::new(item.role, item.content));
         }
 
         // Add current user message
-        messages.push(GrokMessage {
-            role: "user".to_string(),
-            content: user_message.to_string(),
-        });
+        messages.push(GrokMessage::new("user", user_message));
 
-        let response = self.chat(messages).await?;
-
-        // Try to extract JSON from response (handle markdown code blocks)
-        let json_str = if response.contains("```json") {
-            response
-                .split("```json")
-                .nth(1)
-                .and_then(|s| s.split("```").next())
-                .unwrap_or(&response)
-                .trim()
-        } else if response.contains("```") {
-            response
-                .split("```")
-                .nth(1)
-                .unwrap_or(&response)
-                .trim()
-        } else {
-            response.trim()
-        };
+        (messages, template.version_id())
+    }
 
-        // Try to parse as structured response
-        match serde_json::from_str::<AiResponseWithActions>(json_str) {
-            Ok(parsed) => Ok(parsed),
-            Err(e) => {
-                log::warn!("Failed to parse AI response as JSON: {}, response: {}", e, &response);
-                // Fallback: wrap plain text response
-                Ok(AiResponseWithActions {
-                    message: response,
-                    detected_actions: vec![],
-                })
-            }
+    /// Build the messages for a turn of the tool-calling agent, along with the version of
+    /// the template used. `vars` fill in its other placeholders, such as today's date.
+    pub fn agent_messages(
+        &self,
+        user_message: &str,
+        history: Vec<ChatHistoryItem>,
+        project_context: Option<&str>,
+        vars: &[(&str, &str)],
+    ) -> (Vec<GrokMessage>, String) {
+        let template = self.prompts.get("agent");
+        let mut messages = vec![GrokMessage::new("system", chat_system_prompt(&template, project_context, vars))];
+
+        let truncated = self.truncate_history(history, 3000);
+        messages.extend(truncated.into_iter().map(|item| GrokMessage::new(item.role, item.content)));
+        messages.push(GrokMessage::new("user", user_message));
+
+        (messages, template.version_id())
+    }
+}
+
+/// System prompt of a chat template, with its `project_context` section filled in when there is one
+/// and `vars` for any other placeholders
+fn chat_system_prompt(template: &PromptTemplate, project_context: Option<&str>, vars: &[(&str, &str)]) -> String {
+    let context = project_context
+        .map(|c| template.render("project_context", &[("context", c)]))
+        .unwrap_or_default();
+    let mut vars = vars.to_vec();
+    vars.push(("project_context", &context));
+    template.render("system", &vars)
+}
+
+impl Validate for AiResponseWithActions {
+    fn validate(&self) -> Result<(), String> {
+        if self.message.trim().is_empty() {
+            return Err("message is empty".to_string());
         }
+        Ok(())
+    }
+}
+
+/// The message field of an intent reply that never parsed, or an apology when none can be
+/// read out of it
+fn readable_message(raw: &str, locale: Locale) -> String {
+    structured::partial_string_field(raw, "message")
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| locale.t("chat.unreadable_reply", &[]))
+}
+
+/// Schema for replies to `chat_with_intent_detection`
+pub fn intent_schema() -> JsonSchema {
+    JsonSchema {
+        name: "chat_with_actions",
+        schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "message": { "type": "string" },
+                "detected_actions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "intent": {
+                                "type": "string",
+                                "enum": [
+                                    "create_todo", "update_todo", "delete_todo", "log_progress", "create_inbox_item",
+                                    "update_milestone", "update_project", "general_chat"
+                                ]
+                            },
+                            "confidence": { "type": "number" },
+                            "data": { "type": "object" },
+                            "confirmed": { "type": "boolean" }
+                        },
+                        "required": ["intent", "confidence", "data", "confirmed"]
+                    }
+                }
+            },
+            "required": ["message", "detected_actions"]
+        }),
     }
 }
 
@@ -374,3 +978,117 @@ impl Default for GrokClient {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_error_kind() {
+        let error = AiCommandError::from(GrokError::Quota("429 Too Many Requests".to_string()));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "kind": "quota", "message": "Quota exceeded: 429 Too Many Requests" })
+        );
+        assert_eq!(AiCommandError::from("database is locked".to_string()).kind, AiErrorKind::Other);
+    }
+
+    #[test]
+    fn test_parse_sse_line() {
+        assert_eq!(
+            parse_sse_line(r#"data: {"choices":[{"delta":{"content":"你好"}}]}"#),
+            Some(SseEvent::Delta("你好".to_string()))
+        );
+        assert_eq!(parse_sse_line("data: [DONE]\r"), Some(SseEvent::Done));
+        // Role-only deltas, comments and blank keep-alives carry no text
+        assert_eq!(parse_sse_line(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#), None);
+        assert_eq!(parse_sse_line(": keep-alive"), None);
+        assert_eq!(parse_sse_line(""), None);
+        assert_eq!(
+            parse_sse_line(r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#),
+            Some(SseEvent::Usage(GrokUsage { prompt_tokens: 12, completion_tokens: 3 }))
+        );
+    }
+
+    #[test]
+    fn test_streamed_tool_calls() {
+        let lines = [
+            r#"data: {"choices":[{"delta":{"role":"assistant","tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"list_todos","arguments":""}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"status\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_2","function":{"name":"get_stats","arguments":"{}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"pending\"}"}}]}}]}"#,
+        ];
+        let mut calls = Vec::new();
+        for line in lines {
+            let Some(SseEvent::ToolCalls(deltas)) = parse_sse_line(line) else { panic!("no tool calls in {}", line) };
+            merge_tool_call_deltas(&mut calls, deltas);
+        }
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].function.name, "list_todos");
+        assert_eq!(calls[0].function.arguments, r#"{"status":"pending"}"#);
+        assert_eq!(calls[1].function.name, "get_stats");
+
+        let message = StreamOutcome { content: String::new(), tool_calls: calls, cancelled: false }.into_message();
+        assert_eq!(message.role, "assistant");
+        assert_eq!(message.tool_calls.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_readable_message() {
+        let locale = Locale::new("en");
+        assert_eq!(
+            readable_message(r#"{"message": "Added it to your list", "detected_actions": [{"intent": "#, locale),
+            "Added it to your list"
+        );
+        assert_eq!(readable_message(r#"{"detected_actions": []"#, locale), locale.t("chat.unreadable_reply", &[]));
+        assert_eq!(readable_message(r#"{"message": ""#, locale), locale.t("chat.unreadable_reply", &[]));
+    }
+
+    #[test]
+    fn test_tool_call_messages() {
+        let reply: GrokMessage = serde_json::from_str(r#"{
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "list_todos", "arguments": "{}"}}]
+        }"#).unwrap();
+        assert_eq!(reply.content, "");
+        assert_eq!(reply.tool_calls.unwrap()[0].function.name, "list_todos");
+
+        let result = serde_json::to_value(GrokMessage::tool_result("call_1", "[]")).unwrap();
+        assert_eq!(result, serde_json::json!({ "role": "tool", "content": "[]", "tool_call_id": "call_1" }));
+        let plain = serde_json::to_value(GrokMessage::new("user", "hi")).unwrap();
+        assert_eq!(plain, serde_json::json!({ "role": "user", "content": "hi" }));
+    }
+
+    #[test]
+    fn test_classify_status_and_backoff() {
+        assert!(matches!(classify_status(StatusCode::UNAUTHORIZED, ""), (GrokError::Auth(_), false)));
+        assert!(matches!(classify_status(StatusCode::TOO_MANY_REQUESTS, ""), (GrokError::Quota(_), true)));
+        assert!(matches!(classify_status(StatusCode::BAD_GATEWAY, ""), (GrokError::Network(_), true)));
+        assert!(matches!(classify_status(StatusCode::BAD_REQUEST, ""), (GrokError::Api(_), false)));
+
+        assert_eq!(backoff_delay(0, None), Duration::from_secs(1));
+        assert_eq!(backoff_delay(3, None), Duration::from_secs(8));
+        assert_eq!(backoff_delay(0, Some(Duration::from_secs(20))), Duration::from_secs(20));
+        assert_eq!(backoff_delay(10, None), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_circuit_opens_after_repeated_failures() {
+        let settings = AiSettings { circuit_failure_threshold: 2, circuit_cooldown_secs: 60, ..AiSettings::default() };
+        let now = Instant::now();
+        let mut circuit = Circuit::default();
+
+        circuit.record(false, &settings, now);
+        assert!(circuit.check(now).is_ok());
+        circuit.record(false, &settings, now);
+        assert!(matches!(circuit.check(now), Err(GrokError::Network(_))));
+        assert!(circuit.check(now + Duration::from_secs(61)).is_ok());
+
+        circuit.record(true, &settings, now);
+        assert_eq!(circuit.failures, 0);
+        assert!(circuit.check(now).is_ok());
+    }
+}
@@ -1,8 +1,15 @@
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
+use crate::actions;
+use crate::ai_agent::{format_correction_examples, MAX_CORRECTION_EXAMPLES};
+use crate::ai_cache::ResponseCache;
 use crate::db::Database;
-use crate::grok::{ChatHistoryItem, GrokClient, GrokMessage};
+use crate::grok::{intent_schema, AiCommandError, ChatHistoryItem, ChatStreams, GrokClient, GrokMessage};
 use crate::keychain::Keychain;
-use crate::models::{AiResponseWithActions, DetectedAction, ActionData, Todo, DailyLog, InboxItem, InboxItemType, LogCategory, Milestone, MilestoneStatus, MilestoneSource};
+use crate::models::{ActionTrigger, AiPurpose, AiResponseWithActions, ChatMessage, ChatRole, DetectedAction};
+use crate::prompts::PromptTemplate;
+use crate::scheduler::load_settings;
+use crate::structured;
+use crate::targets;
 
 #[tauri::command]
 pub async fn set_api_key(grok: State<'_, GrokClient>, keychain: State<'_, Keychain>, key: String) -> Result<(), String> {
@@ -54,19 +61,38 @@ pub async fn chat_with_grok_history(
 #[tauri::command]
 pub async fn classify_with_grok(
     grok: State<'_, GrokClient>,
+    db: State<'_, Database>,
     files_changed: String,
     diff_summary: String,
+    project_id: Option<String>,
+    force_refresh: Option<bool>,
 ) -> Result<String, String> {
-    grok.classify_changes(&files_changed, &diff_summary).await
+    // Learn from the project's past corrections when we know which project this is
+    let examples = match &project_id {
+        Some(pid) => {
+            let corrections = db.get_corrected_logs(pid, MAX_CORRECTION_EXAMPLES).map_err(|e| e.to_string())?;
+            format_correction_examples(grok.prompts(), &corrections)
+        }
+        None => String::new(),
+    };
+    let cache = ResponseCache::new(&db, &load_settings(&db).ai, force_refresh.unwrap_or(false));
+    grok.for_purpose(AiPurpose::Classify, project_id.as_deref())
+        .classify_changes(&cache, &files_changed, &diff_summary, &examples)
+        .await
 }
 
 #[tauri::command]
 pub async fn generate_summary_with_grok(
     grok: State<'_, GrokClient>,
+    db: State<'_, Database>,
     files_changed: String,
     context: String,
+    force_refresh: Option<bool>,
 ) -> Result<String, String> {
-    grok.generate_summary(&files_changed, &context).await
+    let cache = ResponseCache::new(&db, &load_settings(&db).ai, force_refresh.unwrap_or(false));
+    grok.for_purpose(AiPurpose::Summary, None)
+        .generate_summary(&cache, &files_changed, &context)
+        .await
 }
 
 #[tauri::command]
@@ -74,143 +100,128 @@ pub async fn send_grok_messages(
     grok: State<'_, GrokClient>,
     messages: Vec<GrokMessage>,
 ) -> Result<String, String> {
-    grok.chat(messages).await
+    grok.chat(messages).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn chat_with_intent(
     grok: State<'_, GrokClient>,
+    db: State<'_, Database>,
     message: String,
     history: Vec<ChatHistoryItem>,
     project_context: Option<String>,
     project_id: Option<String>,
 ) -> Result<AiResponseWithActions, String> {
-    grok.chat_with_intent_detection(
+    let mut response = grok.chat_with_intent_detection(
         &message,
         history,
         project_context.as_deref(),
         project_id.as_deref(),
-    ).await
+    ).await?;
+    response.detected_actions = targets::resolve_actions(&db, response.detected_actions, project_id.as_deref());
+    Ok(response)
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ChatDelta<'a> {
+    request_id: &'a str,
+    delta: &'a str,
+}
+
+#[derive(serde::Serialize)]
+pub struct ChatStreamResult {
+    pub message: ChatMessage,
+    pub detected_actions: Vec<DetectedAction>,
+    pub cancelled: bool,
+}
+
+/// Chat with intent detection, emitting `chat:delta` events as the reply arrives.
+/// The reply is saved to chat history once complete, or as far as it got if cancelled.
+/// Fails with an `AiCommandError`, whose `kind` tells what went wrong.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn chat_stream(
+    grok: State<'_, GrokClient>,
+    db: State<'_, Database>,
+    streams: State<'_, ChatStreams>,
+    app_handle: AppHandle,
+    request_id: String,
+    message: String,
+    history: Vec<ChatHistoryItem>,
+    project_context: Option<String>,
+    project_id: Option<String>,
+) -> Result<ChatStreamResult, AiCommandError> {
+    let cancel = streams.register(&request_id).await;
+    let (messages, prompt_version) = grok.intent_messages(&message, history, project_context.as_deref());
+    let grok = grok.for_purpose(AiPurpose::Intent, project_id.as_deref());
+
+    // The reply is a JSON object; only the text of its `message` field is streamed
+    let mut message_field = structured::StreamedField::new("message");
+    let outcome = grok.chat_stream(
+        messages,
+        Some(intent_schema().response_format()),
+        &cancel,
+        |delta| {
+            if let Some(text) = message_field.push(delta) {
+                let _ = app_handle.emit("chat:delta", ChatDelta { request_id: &request_id, delta: &text });
+            }
+        },
+    ).await;
+    streams.finish(&request_id).await;
+    let outcome = outcome?;
+
+    let (text, detected_actions) = match structured::parse::<AiResponseWithActions>(&outcome.content) {
+        Ok(parsed) => (parsed.message, targets::resolve_actions(&db, parsed.detected_actions, project_id.as_deref())),
+        Err(e) => {
+            if !outcome.cancelled {
+                log::warn!("Streamed AI response had no usable actions: {}", e);
+            }
+            let text = structured::partial_string_field(&outcome.content, "message")
+                .unwrap_or(outcome.content);
+            (text, vec![])
+        }
+    };
+
+    let mut message = ChatMessage::new(ChatRole::Assistant, text, project_id);
+    message.prompt_version = Some(prompt_version);
+    db.create_chat_message(&message).map_err(|e| e.to_string())?;
+
+    Ok(ChatStreamResult {
+        message,
+        detected_actions,
+        cancelled: outcome.cancelled,
+    })
+}
+
+#[tauri::command]
+pub async fn cancel_chat_stream(streams: State<'_, ChatStreams>, request_id: String) -> Result<bool, String> {
+    Ok(streams.cancel(&request_id).await)
 }
 
+/// Apply a detected action once the user confirms it; `trigger` says where it was
+/// confirmed (chat when unset) and is recorded in the undo journal
 #[tauri::command]
 pub async fn execute_detected_action(
     db: State<'_, Database>,
     action: DetectedAction,
     project_id: Option<String>,
+    trigger: Option<ActionTrigger>,
 ) -> Result<serde_json::Value, String> {
-    match action.data {
-        ActionData::Todo { title, priority, due_date } => {
-            let mut todo = Todo::new(title.clone(), project_id);
-            if let Some(p) = priority {
-                todo.priority = match p.as_str() {
-                    "low" => crate::models::TodoPriority::Low,
-                    "high" => crate::models::TodoPriority::High,
-                    "urgent" => crate::models::TodoPriority::Urgent,
-                    _ => crate::models::TodoPriority::Medium,
-                };
-            }
-            todo.due_date = due_date;
-
-            db.create_todo(&todo).map_err(|e| e.to_string())?;
-
-            Ok(serde_json::json!({
-                "type": "todo_created",
-                "data": {
-                    "id": todo.id,
-                    "title": title,
-                    "priority": format!("{:?}", todo.priority).to_lowercase()
-                }
-            }))
-        }
-        ActionData::Progress { summary, category, date } => {
-            let project_id = project_id.ok_or("Project ID required for logging progress")?;
-            let date = date.unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
-            let log_category = match category.as_str() {
-                "feature" => LogCategory::Feature,
-                "bugfix" => LogCategory::Bugfix,
-                "refactor" => LogCategory::Refactor,
-                "ui" => LogCategory::Ui,
-                "docs" => LogCategory::Docs,
-                "test" => LogCategory::Test,
-                "chore" => LogCategory::Chore,
-                _ => LogCategory::Other,
-            };
-
-            let log = DailyLog {
-                id: uuid::Uuid::new_v4().to_string(),
-                project_id: project_id.clone(),
-                date: date.clone(),
-                summary: summary.clone(),
-                category: log_category,
-                files_changed: vec![],
-                ai_classification: Some(category.clone()),
-                user_override: None,
-                created_at: chrono::Utc::now(),
-            };
-
-            db.create_daily_log(&log).map_err(|e| e.to_string())?;
-
-            Ok(serde_json::json!({
-                "type": "progress_logged",
-                "data": {
-                    "id": log.id,
-                    "summary": summary,
-                    "date": date
-                }
-            }))
-        }
-        ActionData::Inbox { question, item_type } => {
-            let inbox_type = match item_type.as_str() {
-                "todo_followup" => InboxItemType::TodoFollowup,
-                "planning" => InboxItemType::Planning,
-                _ => InboxItemType::TodoFollowup,
-            };
-
-            let item = InboxItem::new(inbox_type, question.clone(), project_id);
-            db.create_inbox_item(&item).map_err(|e| e.to_string())?;
-
-            Ok(serde_json::json!({
-                "type": "inbox_created",
-                "data": {
-                    "id": item.id,
-                    "question": question
-                }
-            }))
-        }
-        ActionData::Milestone { title, description, version, git_tag } => {
-            let project_id = project_id.ok_or("Project ID required for creating milestone")?;
-
-            let milestone = Milestone {
-                id: uuid::Uuid::new_v4().to_string(),
-                project_id: project_id.clone(),
-                title: title.clone(),
-                description,
-                version: version.clone(),
-                git_tag,
-                status: MilestoneStatus::Planned,
-                source: MilestoneSource::Ai,
-                target_date: None,
-                completed_at: None,
-                created_at: chrono::Utc::now(),
-            };
-
-            db.create_milestone(&milestone).map_err(|e| e.to_string())?;
-
-            Ok(serde_json::json!({
-                "type": "milestone_created",
-                "data": {
-                    "id": milestone.id,
-                    "title": title,
-                    "version": version
-                }
-            }))
-        }
-        ActionData::None => {
-            Ok(serde_json::json!({
-                "type": "no_action",
-                "data": null
-            }))
-        }
-    }
+    actions::execute(&db, action, project_id, trigger.unwrap_or(ActionTrigger::Chat))
+}
+
+#[tauri::command]
+pub async fn get_prompt_templates(grok: State<'_, GrokClient>) -> Result<Vec<PromptTemplate>, String> {
+    Ok(grok.prompts().list())
+}
+
+/// Replace a built-in prompt with `source`, saved to the app data dir
+#[tauri::command]
+pub async fn save_prompt_template(grok: State<'_, GrokClient>, name: String, source: String) -> Result<PromptTemplate, String> {
+    grok.prompts().save_override(&name, &source)
+}
+
+#[tauri::command]
+pub async fn reset_prompt_template(grok: State<'_, GrokClient>, name: String) -> Result<PromptTemplate, String> {
+    grok.prompts().reset(&name)
 }
@@ -0,0 +1,134 @@
+// I18n module - message catalogs for the text the backend writes for the user
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::prompts::interpolate;
+
+/// Languages with a catalog and a full set of prompts, in the order the settings list them
+pub const LANGUAGES: &[&str] = &["zh-HK", "zh-TW", "en", "ja"];
+
+/// Used for unknown languages, and for keys another catalog is missing
+pub const DEFAULT_LANGUAGE: &str = "zh-HK";
+
+const CATALOGS: &[(&str, &str)] = &[
+    ("zh-HK", include_str!("../locales/zh-HK.json")),
+    ("zh-TW", include_str!("../locales/zh-TW.json")),
+    ("en", include_str!("../locales/en.json")),
+    ("ja", include_str!("../locales/ja.json")),
+];
+
+fn catalogs() -> &'static HashMap<&'static str, HashMap<String, String>> {
+    static PARSED: OnceLock<HashMap<&'static str, HashMap<String, String>>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        CATALOGS.iter()
+            .map(|(language, source)| {
+                let messages = serde_json::from_str(source).expect("built-in catalog is valid");
+                (*language, messages)
+            })
+            .collect()
+    })
+}
+
+/// Map a language setting onto a supported language: `en-US` is `en`, `zh-Hant` is
+/// `zh-TW`, and anything unknown falls back to the default
+pub fn normalize(language: &str) -> &'static str {
+    let language = language.trim();
+    if let Some(supported) = LANGUAGES.iter().find(|l| l.eq_ignore_ascii_case(language)) {
+        return supported;
+    }
+
+    let lower = language.to_ascii_lowercase();
+    let primary = lower.split(['-', '_']).next().unwrap_or_default();
+    match primary {
+        "en" => "en",
+        "ja" => "ja",
+        "zh" if lower.contains("tw") || lower.contains("hant") => "zh-TW",
+        _ => DEFAULT_LANGUAGE,
+    }
+}
+
+/// The language user-facing text is written in, from `UserSettings.language`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    language: &'static str,
+}
+
+impl Locale {
+    pub fn new(language: &str) -> Self {
+        Locale {
+            language: normalize(language),
+        }
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// Look up a message and fill in its `{{name}}` placeholders. A key missing from
+    /// this catalog falls back to the default one, then to the key itself.
+    pub fn t(&self, key: &str, vars: &[(&str, &str)]) -> String {
+        let catalogs = catalogs();
+        let message = [self.language, DEFAULT_LANGUAGE].iter()
+            .find_map(|language| catalogs.get(language).and_then(|c| c.get(key)))
+            .map(String::as_str)
+            .unwrap_or_else(|| {
+                log::warn!("Missing message \"{}\" for {}", key, self.language);
+                key
+            });
+        interpolate(message, vars)
+    }
+
+    /// Join list items with this language's separator
+    pub fn join(&self, items: &[String]) -> String {
+        items.join(&self.t("list.separator", &[]))
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new(DEFAULT_LANGUAGE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message.split("{{").skip(1).filter_map(|s| s.split_once("}}")).map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_catalogs_have_the_same_keys_and_placeholders() {
+        let catalogs = catalogs();
+        assert_eq!(catalogs.len(), LANGUAGES.len());
+        let default = &catalogs[DEFAULT_LANGUAGE];
+
+        for language in LANGUAGES {
+            let catalog = &catalogs[language];
+            for (key, message) in default {
+                let translated = catalog.get(key).unwrap_or_else(|| panic!("{} is missing \"{}\"", language, key));
+                assert_eq!(placeholders(translated), placeholders(message), "{} \"{}\"", language, key);
+            }
+            for key in catalog.keys() {
+                assert!(default.contains_key(key), "{} has unknown key \"{}\"", language, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_and_translate() {
+        assert_eq!(normalize("en-US"), "en");
+        assert_eq!(normalize("JA"), "ja");
+        assert_eq!(normalize("zh-Hant"), "zh-TW");
+        assert_eq!(normalize("zh_tw"), "zh-TW");
+        assert_eq!(normalize("zh-HK"), "zh-HK");
+        assert_eq!(normalize("fr"), DEFAULT_LANGUAGE);
+
+        let en = Locale::new("en");
+        assert_eq!(en.t("slash.add", &[("task", "Ship it")]), "Added TODO: Ship it");
+        assert_eq!(en.join(&["a".to_string(), "b".to_string()]), "a, b");
+        assert_eq!(en.t("no.such.key", &[]), "no.such.key");
+        assert_eq!(Locale::new("zh-TW").t("weekday.0", &[]), "星期一");
+    }
+}
@@ -0,0 +1,214 @@
+// Inbox module - posts inbox items, folding repeats together, moves them through snooze and
+// expiry, and acts on the answers they get
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use crate::actions;
+use crate::db::Database;
+use crate::grok::GrokClient;
+use crate::i18n::Locale;
+use crate::targets;
+use crate::models::{
+    ActionData, ActionTrigger, ConversationIntent, DetectedAction, InboxItem, VersionSettings,
+};
+
+/// Post `item`, or update the open item with the same dedupe key. Nothing is posted when
+/// the user already closed that item. Auto mode applies the actions it covers only when the
+/// item is new, once per dedupe key. Returns whether a new item was created.
+pub fn post(db: &Database, settings: &VersionSettings, mut item: InboxItem) -> Result<bool, String> {
+    let existing = match &item.dedupe_key {
+        Some(key) => db.get_inbox_item_by_key(key).map_err(|e| e.to_string())?,
+        None => None,
+    };
+    if let Some(existing) = &existing {
+        if !existing.status.is_open() {
+            return Ok(false);
+        }
+        merge_repeat(existing, &mut item);
+    }
+
+    if existing.is_none() {
+        actions::auto_apply(db, settings, &mut item);
+    }
+
+    match existing {
+        Some(_) => db.update_inbox_item(&item).map_err(|e| e.to_string())?,
+        None => db.create_inbox_item(&item).map_err(|e| e.to_string())?,
+    }
+    Ok(existing.is_none())
+}
+
+/// Make `item` the new version of `existing`: same id, status and priority the user may have
+/// set, and its actions already applied stay applied
+pub fn merge_repeat(existing: &InboxItem, item: &mut InboxItem) {
+    item.id = existing.id.clone();
+    item.status = existing.status.clone();
+    item.priority = existing.priority;
+    item.snoozed_until = existing.snoozed_until;
+    item.created_at = existing.created_at;
+
+    for action in item.detected_actions.iter_mut() {
+        if existing.detected_actions.iter().any(|a| a.confirmed && same_action(a, action)) {
+            action.confirmed = true;
+        }
+    }
+}
+
+/// Re-analysis rewords todo titles, so new todos count as the same when their titles match
+fn same_action(a: &DetectedAction, b: &DetectedAction) -> bool {
+    match (&a.data, &b.data) {
+        (ActionData::Todo { title: a, .. }, ActionData::Todo { title: b, .. }) => targets::same_title(a, b),
+        _ => serde_json::to_value(&a.data).ok() == serde_json::to_value(&b.data).ok(),
+    }
+}
+
+/// Wake snoozed items that are due and expire stale ones. Returns how many of each.
+pub fn refresh(db: &Database) -> Result<(usize, usize), String> {
+    let now = Utc::now();
+    let woken = db.wake_snoozed_inbox_items(now).map_err(|e| e.to_string())?;
+    let expired = db.expire_inbox_items(now).map_err(|e| e.to_string())?;
+    if woken + expired > 0 {
+        log::info!("Inbox: {} snoozed items due, {} expired", woken, expired);
+    }
+    Ok((woken, expired))
+}
+
+/// What answering an inbox item did
+#[derive(Debug, Clone, Serialize)]
+pub struct AnswerOutcome {
+    /// Changes made by the chosen action, each as returned by `actions::execute`
+    pub results: Vec<Value>,
+    /// Actions read out of a free-text answer, added to the item for the user to confirm
+    pub follow_ups: Vec<DetectedAction>,
+}
+
+/// Run what the suggested action `action_id` stands for, then close the item: skipped for
+/// skip and dismiss, otherwise answered with the action's label
+pub fn choose_action(db: &Database, item: &InboxItem, action_id: &str, locale: Locale) -> Result<Vec<Value>, String> {
+    let label = item.suggested_actions.iter()
+        .find(|a| a.id == action_id)
+        .map(|a| a.label.clone())
+        .ok_or_else(|| format!("Inbox item has no action \"{}\"", action_id))?;
+
+    let mut results = Vec::new();
+    match action_id {
+        "skip" | "dismiss" => {
+            db.skip_inbox_item(&item.id).map_err(|e| e.to_string())?;
+            return Ok(results);
+        }
+        "create_milestone" => {
+            let date = item.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string();
+            let milestone = ActionData::Milestone {
+                title: locale.t("inbox.major_update.milestone", &[("date", &date)]),
+                description: item.context.clone(),
+                version: None,
+                git_tag: None,
+            };
+            results.push(execute(db, item, ConversationIntent::CreateMilestone, milestone)?);
+        }
+        "pause_project" | "archive_project" => {
+            let project = ActionData::UpdateProject {
+                project_id: Some(item.project_id.clone().ok_or("Inbox item has no project")?),
+                name: String::new(),
+                status: if action_id == "pause_project" { "paused" } else { "archived" }.to_string(),
+            };
+            results.push(execute(db, item, ConversationIntent::UpdateProject, project)?);
+        }
+        // The todo the item already proposes, unless it was applied already
+        "create_resume_todo" => {
+            results.extend(apply_detected(db, item, |data| matches!(data, ActionData::Todo { .. }))?);
+        }
+        id if id.starts_with("todo_") => {
+            results.extend(apply_detected(db, item, |data| {
+                matches!(data, ActionData::Todo { title, .. } if *title == label)
+            })?);
+        }
+        // review, acknowledge and answer_review only record the choice
+        _ => {}
+    }
+
+    db.answer_inbox_item(&item.id, &label).map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
+fn execute(db: &Database, item: &InboxItem, intent: ConversationIntent, data: ActionData) -> Result<Value, String> {
+    let action = DetectedAction { intent, confidence: 1.0, data, confirmed: true };
+    actions::execute(db, action, item.project_id.clone(), ActionTrigger::Inbox)
+}
+
+/// Apply the first of the item's detected actions matching `wanted` that isn't applied yet
+fn apply_detected(db: &Database, item: &InboxItem, wanted: impl Fn(&ActionData) -> bool) -> Result<Option<Value>, String> {
+    let Some(index) = item.detected_actions.iter().position(|a| !a.confirmed && wanted(&a.data)) else {
+        return Ok(None);
+    };
+    let mut results = actions::apply_inbox_actions(db, &item.id, &[index])?;
+    let mut result = results.pop().unwrap_or_default();
+    match result.get("error").and_then(Value::as_str) {
+        Some(e) => Err(e.to_string()),
+        None => Ok(result.get_mut("result").map(Value::take)),
+    }
+}
+
+/// Ask the AI which actions a free-text answer calls for. Failures are logged and give none.
+pub async fn interpret_answer(grok: &GrokClient, db: &Database, item: &InboxItem, answer: &str) -> Vec<DetectedAction> {
+    let context = match &item.context {
+        Some(context) => format!("{}\n{}", item.question, context),
+        None => item.question.clone(),
+    };
+
+    match grok.chat_with_intent_detection(answer, vec![], Some(&context), item.project_id.as_deref()).await {
+        Ok(response) => {
+            let found = response.detected_actions.into_iter()
+                .filter(|a| !matches!(a.data, ActionData::None))
+                .collect();
+            crate::targets::resolve_actions(db, found, item.project_id.as_deref())
+        }
+        Err(e) => {
+            log::warn!("Failed to interpret inbox answer: {}", e);
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InboxItemType, InboxPriority, InboxStatus};
+
+    fn todo_action(title: &str, confirmed: bool) -> DetectedAction {
+        DetectedAction {
+            intent: ConversationIntent::CreateTodo,
+            confidence: 0.85,
+            data: ActionData::Todo { title: title.to_string(), priority: None, due_date: None },
+            confirmed,
+        }
+    }
+
+    #[test]
+    fn test_merge_repeat() {
+        let mut existing = InboxItem::new(InboxItemType::DailySummary, "Morning".to_string(), Some("p1".to_string()));
+        existing.dedupe_by_day("2026-10-18");
+        existing.status = InboxStatus::Snoozed;
+        existing.priority = InboxPriority::High;
+        existing.detected_actions = vec![todo_action("Write docs", true), todo_action("Add tests", false)];
+
+        let mut item = InboxItem::new(InboxItemType::DailySummary, "Evening".to_string(), Some("p1".to_string()));
+        item.dedupe_by_day("2026-10-18");
+        item.detected_actions = vec![todo_action("Add tests", false), todo_action("Write docs", false)];
+        assert_eq!(item.dedupe_key, existing.dedupe_key);
+        assert_eq!(item.dedupe_key.as_deref(), Some("daily_summary:p1:2026-10-18"));
+
+        merge_repeat(&existing, &mut item);
+        assert_eq!(item.id, existing.id);
+        assert_eq!(item.question, "Evening");
+        assert_eq!(item.status, InboxStatus::Snoozed);
+        assert_eq!(item.priority, InboxPriority::High);
+        assert!(!item.detected_actions[0].confirmed);
+        assert!(item.detected_actions[1].confirmed);
+
+        item.detected_actions = vec![todo_action("Write the docs", false), todo_action("Fix login redirect", false)];
+        merge_repeat(&existing, &mut item);
+        assert!(item.detected_actions[0].confirmed);
+        assert!(!item.detected_actions[1].confirmed);
+    }
+}
@@ -0,0 +1,56 @@
+// Journal module - records changes made by the AI or slash commands so they can be undone
+use chrono::Utc;
+use crate::db::Database;
+use crate::models::{ActionTrigger, JournalEntry, UndoOperation};
+
+/// Journal a change that has just been made. A failed write is logged rather than
+/// failing the change, which has already happened; it just can't be undone.
+pub fn record(
+    db: &Database,
+    trigger: ActionTrigger,
+    action_type: &str,
+    description: &str,
+    project_id: Option<&str>,
+    undo: UndoOperation,
+) -> Option<String> {
+    let entry = JournalEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        trigger,
+        action_type: action_type.to_string(),
+        description: description.to_string(),
+        project_id: project_id.map(str::to_string),
+        undo,
+        created_at: Utc::now(),
+        undone_at: None,
+    };
+
+    match db.create_journal_entry(&entry) {
+        Ok(()) => Some(entry.id),
+        Err(e) => {
+            log::error!("Failed to journal {}: {}", action_type, e);
+            None
+        }
+    }
+}
+
+/// Reverse a journaled change and mark it undone. Records deleted since are left deleted.
+pub fn undo(db: &Database, entry: &JournalEntry) -> Result<(), String> {
+    if entry.undone_at.is_some() {
+        return Err(format!("\"{}\" has already been undone", entry.description));
+    }
+
+    match &entry.undo {
+        UndoOperation::DeleteTodo { id } => db.delete_todo(id),
+        UndoOperation::DeleteDailyLog { id } => db.delete_daily_log(id),
+        UndoOperation::DeleteInboxItem { id } => db.delete_inbox_item(id),
+        UndoOperation::DeleteMilestone { id } => db.delete_milestone(id),
+        UndoOperation::RecreateTodo { todo } => db.create_todo(todo),
+        UndoOperation::RestoreTodo { todo } => db.update_todo(todo),
+        UndoOperation::RestoreDailyLog { log } => db.restore_daily_log(log),
+        UndoOperation::RestoreMilestone { milestone } => db.update_milestone(milestone),
+        UndoOperation::RestoreProject { project } => db.update_project_status(&project.id, project.status.clone()),
+    }
+    .map_err(|e| e.to_string())?;
+
+    db.mark_journal_entry_undone(&entry.id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,33 @@
+use tauri::State;
+use crate::db::Database;
+use crate::journal;
+use crate::models::JournalEntry;
+
+/// Journaled changes, most recent first
+#[tauri::command]
+pub fn get_action_journal(db: State<Database>, limit: Option<i32>) -> Result<Vec<JournalEntry>, String> {
+    db.get_journal_entries(limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+/// Undo the most recent chat or inbox change that hasn't been undone; None when there is
+/// nothing left. Scheduler changes are undone with `undo_action`.
+#[tauri::command]
+pub fn undo_last_action(db: State<Database>) -> Result<Option<JournalEntry>, String> {
+    let Some(entry) = db.get_last_undoable_entry().map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    journal::undo(&db, &entry)?;
+    db.get_journal_entry(&entry.id).map_err(|e| e.to_string())
+}
+
+/// Undo one journaled change by id
+#[tauri::command]
+pub fn undo_action(db: State<Database>, id: String) -> Result<JournalEntry, String> {
+    let entry = db.get_journal_entry(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Journal entry not found: {}", id))?;
+    journal::undo(&db, &entry)?;
+    db.get_journal_entry(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Journal entry not found: {}", id))
+}
@@ -3,6 +3,7 @@ use security_framework::passwords::{delete_generic_password, get_generic_passwor
 
 const SERVICE_NAME: &str = "com.sixarms.app";
 const ACCOUNT_NAME: &str = "grok_api_key";
+const EMBEDDINGS_ACCOUNT_NAME: &str = "embeddings_api_key";
 
 pub struct Keychain;
 
@@ -11,18 +12,43 @@ impl Keychain {
         Keychain
     }
 
-    #[cfg(target_os = "macos")]
     pub fn save_api_key(&self, key: &str) -> Result<(), String> {
+        self.save(ACCOUNT_NAME, key)
+    }
+
+    pub fn get_api_key(&self) -> Result<Option<String>, String> {
+        self.get(ACCOUNT_NAME)
+    }
+
+    pub fn delete_api_key(&self) -> Result<(), String> {
+        self.delete(ACCOUNT_NAME)
+    }
+
+    /// Key sent to the embeddings endpoint, for hosted ones that need it
+    pub fn save_embeddings_api_key(&self, key: &str) -> Result<(), String> {
+        self.save(EMBEDDINGS_ACCOUNT_NAME, key)
+    }
+
+    pub fn get_embeddings_api_key(&self) -> Result<Option<String>, String> {
+        self.get(EMBEDDINGS_ACCOUNT_NAME)
+    }
+
+    pub fn delete_embeddings_api_key(&self) -> Result<(), String> {
+        self.delete(EMBEDDINGS_ACCOUNT_NAME)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn save(&self, account: &str, key: &str) -> Result<(), String> {
         // Try to delete existing key first (ignore errors)
-        let _ = delete_generic_password(SERVICE_NAME, ACCOUNT_NAME);
+        let _ = delete_generic_password(SERVICE_NAME, account);
 
-        set_generic_password(SERVICE_NAME, ACCOUNT_NAME, key.as_bytes())
+        set_generic_password(SERVICE_NAME, account, key.as_bytes())
             .map_err(|e| format!("Failed to save API key: {}", e))
     }
 
     #[cfg(target_os = "macos")]
-    pub fn get_api_key(&self) -> Result<Option<String>, String> {
-        match get_generic_password(SERVICE_NAME, ACCOUNT_NAME) {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        match get_generic_password(SERVICE_NAME, account) {
             Ok(data) => {
                 let key = String::from_utf8(data)
                     .map_err(|e| format!("Invalid UTF-8 in stored key: {}", e))?;
@@ -40,24 +66,24 @@ impl Keychain {
     }
 
     #[cfg(target_os = "macos")]
-    pub fn delete_api_key(&self) -> Result<(), String> {
-        delete_generic_password(SERVICE_NAME, ACCOUNT_NAME)
+    fn delete(&self, account: &str) -> Result<(), String> {
+        delete_generic_password(SERVICE_NAME, account)
             .map_err(|e| format!("Failed to delete API key: {}", e))
     }
 
     // Fallback implementations for non-macOS platforms
     #[cfg(not(target_os = "macos"))]
-    pub fn save_api_key(&self, _key: &str) -> Result<(), String> {
+    fn save(&self, _account: &str, _key: &str) -> Result<(), String> {
         Err("Keychain is only supported on macOS".to_string())
     }
 
     #[cfg(not(target_os = "macos"))]
-    pub fn get_api_key(&self) -> Result<Option<String>, String> {
+    fn get(&self, _account: &str) -> Result<Option<String>, String> {
         Err("Keychain is only supported on macOS".to_string())
     }
 
     #[cfg(not(target_os = "macos"))]
-    pub fn delete_api_key(&self) -> Result<(), String> {
+    fn delete(&self, _account: &str) -> Result<(), String> {
         Err("Keychain is only supported on macOS".to_string())
     }
 }
@@ -1,21 +1,47 @@
+mod actions;
+mod agent;
+mod agent_commands;
 mod ai_agent;
+mod ai_cache;
+mod analytics;
+mod anomaly;
+mod backfill;
+mod backfill_commands;
+mod classifier;
 mod commands;
+mod cron;
 mod db;
+mod embeddings;
+mod embeddings_commands;
+mod evidence;
 mod grok;
 mod grok_commands;
+mod i18n;
+mod inbox;
+mod journal;
+mod journal_commands;
 mod keychain;
 mod models;
 mod notification;
+mod patterns;
+mod prompts;
 mod quick_entry;
+mod retrieval;
 mod scheduler;
 mod slash_commands;
+mod structured;
+mod targets;
 mod scheduler_commands;
 mod scanner;
 mod scanner_commands;
+mod usage;
+mod weekly_review;
 
 use ai_agent::AiAgent;
+use backfill::Backfill;
 use db::Database;
-use grok::GrokClient;
+use embeddings::EmbeddingsKey;
+use grok::{ChatStreams, GrokClient};
 use keychain::Keychain;
 use notification::NotificationService;
 use scheduler::Scheduler;
@@ -33,11 +59,18 @@ pub fn run() {
                 .app_data_dir()
                 .expect("Failed to get app data directory");
 
-            let db = Database::new(app_data_dir).expect("Failed to initialize database");
+            let db = Database::new(app_data_dir.clone()).expect("Failed to initialize database");
             app.manage(db);
 
             // Initialize Grok client
             let grok_client = GrokClient::new();
+            grok_client.prompts().load(app_data_dir.join("prompts"));
+            if let Ok(Some(json)) = app.state::<Database>().get_setting("user_settings") {
+                if let Ok(settings) = serde_json::from_str::<models::UserSettings>(&json) {
+                    grok_client.configure(&settings.ai);
+                    grok_client.prompts().set_language(&settings.language);
+                }
+            }
 
             // Try to load API key from keychain
             let keychain = Keychain::new();
@@ -48,7 +81,14 @@ pub fn run() {
                 });
             }
 
+            usage::start_recording(app.handle().clone(), &grok_client);
             app.manage(grok_client);
+            app.manage(ChatStreams::new());
+            let embeddings_key = EmbeddingsKey::default();
+            if let Ok(Some(key)) = keychain.get_embeddings_api_key() {
+                embeddings_key.set(Some(key));
+            }
+            app.manage(embeddings_key);
             app.manage(keychain);
 
             // Initialize scanner
@@ -67,6 +107,10 @@ pub fn run() {
             let notification_service = NotificationService::new();
             app.manage(notification_service);
 
+            // Initialize history backfill runner
+            let backfill = Backfill::new();
+            app.manage(backfill.clone());
+
             // Run startup scan if enabled
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -86,10 +130,13 @@ pub fn run() {
                             log::error!("Startup scan failed: {}", e);
                         }
                     }
-
-                    // Start periodic scheduler
-                    scheduler.start(app_handle.clone(), settings.scan).await;
                 }
+
+                // Start periodic scheduler
+                scheduler.start(app_handle.clone()).await;
+
+                // Pick up backfills interrupted by the app quitting
+                backfill.resume_interrupted(&app_handle).await;
             });
 
             // Setup logging in debug mode
@@ -140,6 +187,10 @@ pub fn run() {
             commands::delete_project,
             commands::get_daily_logs,
             commands::create_daily_log,
+            commands::get_daily_log_revisions,
+            commands::override_log_category,
+            commands::edit_log,
+            commands::get_classification_accuracy,
             commands::get_milestones,
             commands::create_milestone,
             commands::update_milestone_status,
@@ -152,12 +203,20 @@ pub fn run() {
             commands::get_inbox_items,
             commands::answer_inbox_item,
             commands::create_inbox_item,
+            commands::apply_inbox_actions,
+            commands::skip_inbox_item,
+            commands::snooze_inbox_item,
+            commands::set_inbox_item_priority,
+            commands::get_weekly_reviews,
+            commands::answer_weekly_review,
             commands::get_chat_messages,
             commands::create_chat_message,
             commands::get_settings,
             commands::save_settings,
             commands::get_activity_stats,
             commands::get_category_distribution,
+            commands::get_analytics,
+            commands::get_ai_usage,
             commands::health_check,
             // Grok commands
             grok_commands::set_api_key,
@@ -169,7 +228,23 @@ pub fn run() {
             grok_commands::generate_summary_with_grok,
             grok_commands::send_grok_messages,
             grok_commands::chat_with_intent,
+            grok_commands::chat_stream,
+            grok_commands::cancel_chat_stream,
             grok_commands::execute_detected_action,
+            grok_commands::get_prompt_templates,
+            grok_commands::save_prompt_template,
+            grok_commands::reset_prompt_template,
+            // Agent commands
+            agent_commands::chat_agent,
+            // Action journal commands
+            journal_commands::get_action_journal,
+            journal_commands::undo_last_action,
+            journal_commands::undo_action,
+            // Semantic search commands
+            embeddings_commands::semantic_search,
+            embeddings_commands::set_embeddings_api_key,
+            embeddings_commands::has_embeddings_api_key,
+            embeddings_commands::delete_embeddings_api_key,
             // Scanner commands
             scanner_commands::scan_today,
             scanner_commands::scan_range,
@@ -186,6 +261,13 @@ pub fn run() {
             scheduler_commands::stop_scheduler,
             scheduler_commands::get_scheduler_status,
             scheduler_commands::trigger_manual_scan,
+            scheduler_commands::trigger_job,
+            // Backfill commands
+            backfill_commands::start_backfill,
+            backfill_commands::pause_backfill,
+            backfill_commands::resume_backfill,
+            backfill_commands::cancel_backfill,
+            backfill_commands::get_backfill_jobs,
             // Quick Entry commands
             quick_entry::show_quick_entry,
             quick_entry::hide_quick_entry,
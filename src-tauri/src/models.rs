@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -41,9 +41,41 @@ pub struct DailyLog {
     pub ai_classification: Option<String>,
     pub user_override: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Prompt template that produced the summary, e.g. `analysis@1`; None when written offline or by hand
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+    /// The summary was written or edited by the user, so scans keep it
+    #[serde(default)]
+    pub summary_by_user: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Parse a `YYYY-MM-DD` date, as stored on logs, todos and milestones
+pub fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date \"{}\", expected YYYY-MM-DD", date))
+}
+
+/// A previous version of a daily log, kept when a later scan rewrites it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyLogRevision {
+    pub id: String,
+    pub log_id: String,
+    pub summary: String,
+    pub category: LogCategory,
+    pub files_changed: Vec<FileChange>,
+    pub ai_classification: Option<String>,
+    pub revised_at: DateTime<Utc>,
+}
+
+/// How often a project's AI categories survived user review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationAccuracy {
+    pub project_id: String,
+    pub ai_labeled: u32,
+    pub corrected: u32,
+    pub accuracy: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum LogCategory {
     Feature,
@@ -114,6 +146,18 @@ impl Default for TodoStatus {
     }
 }
 
+impl TodoStatus {
+    /// Name as stored in the database; serde spells `InProgress` without the underscore
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoStatus::Pending => "pending",
+            TodoStatus::InProgress => "in_progress",
+            TodoStatus::Completed => "completed",
+            TodoStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
 // ============================================
 // Inbox Models
 // ============================================
@@ -126,11 +170,25 @@ pub struct InboxItem {
     pub question: String,
     pub context: Option<String>,
     pub suggested_actions: Vec<SuggestedAction>,
-    pub detected_actions: Vec<DetectedAction>,  // Executable AI-detected actions
+    pub detected_actions: Vec<DetectedAction>,  // Executable AI-detected actions; `confirmed` once applied
     pub status: InboxStatus,
     pub answer: Option<String>,
     pub created_at: DateTime<Utc>,
     pub answered_at: Option<DateTime<Utc>>,
+    /// Prompt template that produced the question, when AI wrote it
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+    #[serde(default)]
+    pub priority: InboxPriority,
+    /// An open item with the same key is updated instead of posting another
+    #[serde(default)]
+    pub dedupe_key: Option<String>,
+    /// Hidden from the pending list until then
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// Left unanswered past this, the item expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -155,12 +213,50 @@ pub struct SuggestedAction {
     pub icon: Option<String>,
 }
 
+impl InboxItemType {
+    /// Name as stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InboxItemType::DailySummary => "daily_summary",
+            InboxItemType::Classification => "classification",
+            InboxItemType::TodoFollowup => "todo_followup",
+            InboxItemType::Planning => "planning",
+            InboxItemType::StaleProject => "stale_project",
+            InboxItemType::AnomalyDetection => "anomaly_detection",
+            InboxItemType::WeeklyReview => "weekly_review",
+            InboxItemType::PatternInsight => "pattern_insight",
+            InboxItemType::MajorUpdate => "major_update",
+        }
+    }
+
+    pub fn default_priority(&self) -> InboxPriority {
+        match self {
+            InboxItemType::AnomalyDetection | InboxItemType::MajorUpdate => InboxPriority::High,
+            InboxItemType::PatternInsight => InboxPriority::Low,
+            _ => InboxPriority::Normal,
+        }
+    }
+
+    /// Days an unanswered item stays relevant; None for items that wait for the user
+    pub fn lifetime_days(&self) -> Option<i64> {
+        match self {
+            InboxItemType::DailySummary => Some(3),
+            InboxItemType::Classification | InboxItemType::AnomalyDetection => Some(7),
+            InboxItemType::WeeklyReview | InboxItemType::PatternInsight | InboxItemType::MajorUpdate => Some(14),
+            InboxItemType::StaleProject => Some(30),
+            InboxItemType::TodoFollowup | InboxItemType::Planning => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum InboxStatus {
     Pending,
     Answered,
     Skipped,
+    Snoozed,
+    Expired,
 }
 
 impl Default for InboxStatus {
@@ -169,6 +265,43 @@ impl Default for InboxStatus {
     }
 }
 
+impl InboxStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InboxStatus::Pending => "pending",
+            InboxStatus::Answered => "answered",
+            InboxStatus::Skipped => "skipped",
+            InboxStatus::Snoozed => "snoozed",
+            InboxStatus::Expired => "expired",
+        }
+    }
+
+    /// Still waiting on the user, now or after a snooze
+    pub fn is_open(&self) -> bool {
+        matches!(self, InboxStatus::Pending | InboxStatus::Snoozed)
+    }
+}
+
+/// Orders the inbox, highest first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InboxPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl InboxPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InboxPriority::Low => "low",
+            InboxPriority::Normal => "normal",
+            InboxPriority::High => "high",
+        }
+    }
+}
+
 // ============================================
 // Chat Models
 // ============================================
@@ -181,6 +314,12 @@ pub struct ChatMessage {
     pub content: String,
     pub actions: Option<ChatAction>,
     pub created_at: DateTime<Utc>,
+    /// Prompt template that produced an assistant reply
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+    /// Records an assistant reply was grounded in and cites
+    #[serde(default)]
+    pub citations: Vec<Citation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -205,6 +344,60 @@ pub enum ChatActionType {
     ProjectUpdated,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    Log,
+    Commit,
+    Todo,
+    Milestone,
+    Chat,
+}
+
+impl SourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceKind::Log => "log",
+            SourceKind::Commit => "commit",
+            SourceKind::Todo => "todo",
+            SourceKind::Milestone => "milestone",
+            SourceKind::Chat => "chat",
+        }
+    }
+}
+
+/// A record's text as the semantic index embeds it
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingDocument {
+    pub kind: SourceKind,
+    pub id: String,
+    pub project_id: Option<String>,
+    pub date: String, // YYYY-MM-DD
+    pub text: String,
+}
+
+/// A record found by `semantic_search`, with its cosine similarity to the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticMatch {
+    pub kind: SourceKind,
+    pub id: String, // row id, or the abbreviated hash of a commit
+    pub date: String, // YYYY-MM-DD
+    pub project_id: Option<String>,
+    pub text: String,
+    pub score: f32,
+}
+
+/// A stored record given to the AI to answer from, cited in its reply as `[label]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Citation {
+    pub label: String, // S1, S2, ...
+    pub kind: SourceKind,
+    pub id: String, // row id, or the abbreviated hash of a commit
+    pub date: String, // YYYY-MM-DD
+    pub project_id: Option<String>,
+    pub text: String,
+}
+
 // ============================================
 // Settings Models
 // ============================================
@@ -214,6 +407,18 @@ pub struct UserSettings {
     pub notifications: NotificationSettings,
     pub scan: ScanSettings,
     pub version: VersionSettings,
+    #[serde(default)]
+    pub anomaly: AnomalySettings,
+    #[serde(default)]
+    pub review: ReviewSettings,
+    #[serde(default)]
+    pub insights: InsightSettings,
+    #[serde(default)]
+    pub scheduler: SchedulerSettings,
+    #[serde(default)]
+    pub ai: AiSettings,
+    #[serde(default)]
+    pub embeddings: EmbeddingSettings,
     pub theme: String,
     pub language: String,
 }
@@ -224,17 +429,55 @@ impl Default for UserSettings {
             notifications: NotificationSettings::default(),
             scan: ScanSettings::default(),
             version: VersionSettings::default(),
+            anomaly: AnomalySettings::default(),
+            review: ReviewSettings::default(),
+            insights: InsightSettings::default(),
+            scheduler: SchedulerSettings::default(),
+            ai: AiSettings::default(),
+            embeddings: EmbeddingSettings::default(),
             theme: "dark".to_string(),
             language: "zh-HK".to_string(),
         }
     }
 }
 
+impl UserSettings {
+    /// Move settings saved by older versions to where they live now. Returns the updated
+    /// JSON, or None when there was nothing to move.
+    pub fn migrate_json(json: &str) -> Option<String> {
+        let mut value: serde_json::Value = serde_json::from_str(json).ok()?;
+
+        // The weekly review schedule moved from review.weekday/time to scheduler.jobs
+        let review = value.get_mut("review")?.as_object_mut()?;
+        let (weekday, time) = (review.remove("weekday"), review.remove("time"));
+        if weekday.is_none() && time.is_none() {
+            return None;
+        }
+        let weekday = weekday.as_ref().and_then(|v| v.as_str()).unwrap_or("fri");
+        let time = time.as_ref().and_then(|v| v.as_str()).unwrap_or("17:00");
+        // The old default is also the job's default, so only a custom schedule is kept
+        if (weekday, time) != ("fri", "17:00") {
+            let scheduler = value.as_object_mut()?
+                .entry("scheduler")
+                .or_insert_with(|| serde_json::json!({}));
+            scheduler.as_object_mut()?
+                .entry("jobs")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()?
+                .entry("weekly_review")
+                .or_insert_with(|| format!("{} {}", weekday, time).into());
+        }
+        serde_json::to_string(&value).ok()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NotificationSettings {
     pub daily_summary: bool,
     pub todo_reminder: bool,
     pub stale_project: bool,
+    pub stale_project_days: u32, // Idle days before an active project counts as stale
 }
 
 impl Default for NotificationSettings {
@@ -243,6 +486,7 @@ impl Default for NotificationSettings {
             daily_summary: true,
             todo_reminder: true,
             stale_project: false,
+            stale_project_days: 30,
         }
     }
 }
@@ -269,6 +513,7 @@ impl Default for ScanSettings {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VersionSettings {
     pub auto_refresh: bool,
     pub refresh_minutes: u32,
@@ -276,6 +521,8 @@ pub struct VersionSettings {
     pub auto_major_updates: bool,
     pub major_update_threshold: MajorUpdateThreshold,
     pub ai_create_mode: AiCreateMode,
+    pub auto_apply_types: Vec<String>, // ActionData types applied without asking in Auto mode
+    pub auto_apply_confidence: f32,    // Applied only above this confidence
 }
 
 impl Default for VersionSettings {
@@ -287,6 +534,8 @@ impl Default for VersionSettings {
             auto_major_updates: true,
             major_update_threshold: MajorUpdateThreshold::default(),
             ai_create_mode: AiCreateMode::Suggest,
+            auto_apply_types: vec!["todo".to_string()],
+            auto_apply_confidence: 0.8,
         }
     }
 }
@@ -315,6 +564,155 @@ pub enum AiCreateMode {
     Auto,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalySettings {
+    pub enabled: bool,
+    pub use_ai_phrasing: bool,       // Let the LLM reword rule findings
+    pub baseline_weeks: u32,         // Weeks of history forming a project's baseline
+    pub low_activity_ratio: f64,     // This week below baseline * ratio is flagged
+    pub deletion_ratio: f64,         // Deletions per addition that counts as deletion-heavy
+    pub min_deleted_lines: u32,
+    pub late_night_start_hour: u32,  // Local hour, inclusive
+    pub late_night_end_hour: u32,    // Local hour, exclusive
+    pub late_night_min_commits: u32,
+    pub category_switch_window: u32, // Number of recent logs inspected
+    pub lockfile_churn_lines: u32,
+    pub binary_files: u32,
+}
+
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        AnomalySettings {
+            enabled: true,
+            use_ai_phrasing: false,
+            baseline_weeks: 8,
+            low_activity_ratio: 0.25,
+            deletion_ratio: 3.0,
+            min_deleted_lines: 300,
+            late_night_start_hour: 0,
+            late_night_end_hour: 5,
+            late_night_min_commits: 3,
+            category_switch_window: 7,
+            lockfile_churn_lines: 2000,
+            binary_files: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewSettings {
+    pub weekly_review: bool,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        ReviewSettings {
+            weekly_review: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerSettings {
+    /// Schedule overrides keyed by job name; jobs not listed use their default schedule
+    pub jobs: std::collections::BTreeMap<String, String>,
+    pub backup_keep: u32, // number of database backups to keep
+}
+
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        SchedulerSettings {
+            jobs: std::collections::BTreeMap::new(),
+            backup_keep: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiSettings {
+    pub request_timeout_secs: u64,
+    pub max_retries: u32,             // retries for rate limits, server and network errors
+    pub max_concurrent_requests: u32,
+    pub circuit_failure_threshold: u32, // consecutive failed calls before AI work is paused
+    pub circuit_cooldown_secs: u64,
+    pub prompt_price_per_million: f64,     // USD per million prompt tokens
+    pub completion_price_per_million: f64, // USD per million completion tokens
+    pub monthly_budget_usd: f64,           // 0 means no budget
+    pub cache_ttl_days: u32,               // 0 turns the response cache off
+    pub cache_max_entries: u32,
+    pub agent_max_steps: u32, // tool-calling rounds per chat message before the agent must answer
+    pub interpret_answers: bool, // read follow-up actions out of free-text inbox answers
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        AiSettings {
+            request_timeout_secs: 60,
+            max_retries: 3,
+            max_concurrent_requests: 2,
+            circuit_failure_threshold: 5,
+            circuit_cooldown_secs: 300,
+            prompt_price_per_million: 0.2,
+            completion_price_per_million: 0.5,
+            monthly_budget_usd: 0.0,
+            cache_ttl_days: 30,
+            cache_max_entries: 2000,
+            agent_max_steps: 6,
+            interpret_answers: true,
+        }
+    }
+}
+
+/// The optional semantic index. Vectors come from an OpenAI-compatible `/embeddings`
+/// endpoint, e.g. a local Ollama or llama.cpp server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingSettings {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub model: String,
+    pub batch_size: u32,  // texts sent per request
+    pub commit_days: u32, // how far back commit messages are indexed
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        EmbeddingSettings {
+            enabled: false,
+            endpoint: "http://localhost:11434/v1/embeddings".to_string(),
+            model: "nomic-embed-text".to_string(),
+            batch_size: 32,
+            commit_days: 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InsightSettings {
+    pub enabled: bool,
+    pub lookback_days: u32,
+    pub min_samples: u32,      // commits needed before time-of-week patterns are reported
+    pub abandon_gap_days: u32, // silence after a milestone that counts as abandonment
+    pub repeat_days: u32,      // an insight is not re-posted within this many days
+}
+
+impl Default for InsightSettings {
+    fn default() -> Self {
+        InsightSettings {
+            enabled: true,
+            lookback_days: 180,
+            min_samples: 30,
+            abandon_gap_days: 21,
+            repeat_days: 90,
+        }
+    }
+}
+
 // ============================================
 // Scanner Models
 // ============================================
@@ -336,6 +734,13 @@ pub struct GitTag {
     pub message: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitInfo {
+    pub hash: String, // abbreviated
+    pub date: String, // YYYY-MM-DD, committer date
+    pub subject: String,
+}
+
 // ============================================
 // Milestone Models
 // ============================================
@@ -364,6 +769,18 @@ pub enum MilestoneStatus {
     Cancelled,
 }
 
+impl MilestoneStatus {
+    /// Name as stored in the database; serde spells `InProgress` without the underscore
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MilestoneStatus::Planned => "planned",
+            MilestoneStatus::InProgress => "in_progress",
+            MilestoneStatus::Completed => "completed",
+            MilestoneStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
 impl Default for MilestoneStatus {
     fn default() -> Self {
         MilestoneStatus::Planned
@@ -454,6 +871,178 @@ pub struct TagSyncResult {
     pub new_tags: Vec<CachedGitTag>,
 }
 
+// ============================================
+// Weekly Review Models
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyReview {
+    pub id: String,
+    pub inbox_item_id: String,
+    pub week_start: String, // Monday, YYYY-MM-DD format
+    pub summary: WeeklySummary,
+    pub questions: Vec<String>,
+    pub answers: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub answered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WeeklySummary {
+    pub projects: Vec<ProjectWeekSummary>,
+    pub completed_todos: Vec<String>,
+    pub milestones: Vec<String>,
+    pub tags: Vec<String>,
+    pub slipped_todos: Vec<SlippedTodo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectWeekSummary {
+    pub project_id: String,
+    pub project_name: String,
+    pub log_count: u32,
+    pub categories: Vec<String>,
+    pub highlights: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlippedTodo {
+    pub todo_id: String,
+    pub title: String,
+    pub due_date: Option<String>,
+}
+
+// ============================================
+// Analytics Models
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsReport {
+    pub period: String,
+    pub since: String, // YYYY-MM-DD format
+    pub until: String, // YYYY-MM-DD format
+    pub streaks: StreakStats,
+    pub throughput: Vec<WeeklyThroughput>,
+    pub mean_cycle_time_hours: Option<f64>,
+    pub category_trend: Vec<CategoryTrendPoint>,
+    pub project_momentum: Vec<ProjectMomentum>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StreakStats {
+    pub current: u32,
+    pub longest: u32,
+    pub longest_start: Option<String>,
+    pub longest_end: Option<String>,
+    pub active_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeeklyThroughput {
+    pub week_start: String, // Monday, YYYY-MM-DD format
+    pub created: u32,
+    pub completed: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTrendPoint {
+    pub week_start: String,
+    pub counts: std::collections::BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectMomentum {
+    pub project_id: String,
+    pub project_name: String,
+    pub weekly_activity: Vec<u32>, // Active days per week, oldest first
+    pub slope: f64,                // Change in active days per week
+    pub trend: MomentumTrend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MomentumTrend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+// ============================================
+// Backfill Models
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackfillStatus {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// A history backfill for one project. `days` lists the active days found in the
+/// range, oldest first; `processed` is how many of them have been handled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillJob {
+    pub project_id: String,
+    pub since: String, // YYYY-MM-DD format
+    pub until: String, // YYYY-MM-DD format
+    pub use_ai: bool,
+    pub status: BackfillStatus,
+    pub days: Vec<String>,
+    pub processed: u32,
+    pub logs_created: u32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// ============================================
+// AI Usage Models
+// ============================================
+
+/// What an AI call was for; calls made without one are counted as chat
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AiPurpose {
+    Classify,
+    Summary,
+    Analysis,
+    Intent,
+    Anomaly,
+    /// Steps of the tool-calling chat agent
+    Agent,
+    #[default]
+    Chat,
+}
+
+/// One completed AI call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsage {
+    pub id: String,
+    pub model: String,
+    pub purpose: AiPurpose,
+    pub project_id: Option<String>,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub latency_ms: u64,
+    pub cost_usd: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Usage totals for one day and purpose
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsageSummary {
+    pub date: String, // YYYY-MM-DD format
+    pub purpose: AiPurpose,
+    pub calls: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    pub avg_latency_ms: u64,
+}
+
 // ============================================
 // AI Conversation Action Models
 // ============================================
@@ -465,6 +1054,10 @@ pub enum ConversationIntent {
     LogProgress,
     CreateInboxItem,
     CreateMilestone,
+    UpdateTodo,
+    DeleteTodo,
+    UpdateMilestone,
+    UpdateProject,
     AskQuestion,
     GeneralChat,
 }
@@ -504,14 +1097,110 @@ pub enum ActionData {
         version: Option<String>,
         git_tag: Option<String>,
     },
+    #[serde(rename = "carry_over_todo")]
+    CarryOverTodo {
+        todo_id: String,
+        title: String,
+        due_date: Option<String>,
+    },
+    /// Targets without an id are found by fuzzy title match
+    #[serde(rename = "update_todo")]
+    UpdateTodo {
+        todo_id: Option<String>,
+        title: String,
+        status: Option<String>,
+        priority: Option<String>,
+        due_date: Option<String>,
+        column: Option<String>,
+    },
+    #[serde(rename = "delete_todo")]
+    DeleteTodo {
+        todo_id: Option<String>,
+        title: String,
+    },
+    #[serde(rename = "update_milestone")]
+    UpdateMilestone {
+        milestone_id: Option<String>,
+        title: String,
+        status: Option<String>,
+        target_date: Option<String>,
+    },
+    #[serde(rename = "update_project")]
+    UpdateProject {
+        project_id: Option<String>,
+        name: String,
+        status: String,
+    },
     #[serde(rename = "none")]
     None,
 }
 
+impl ActionData {
+    /// The `type` tag it is serialized with
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ActionData::Todo { .. } => "todo",
+            ActionData::Progress { .. } => "progress",
+            ActionData::Inbox { .. } => "inbox",
+            ActionData::Milestone { .. } => "milestone",
+            ActionData::CarryOverTodo { .. } => "carry_over_todo",
+            ActionData::UpdateTodo { .. } => "update_todo",
+            ActionData::DeleteTodo { .. } => "delete_todo",
+            ActionData::UpdateMilestone { .. } => "update_milestone",
+            ActionData::UpdateProject { .. } => "update_project",
+            ActionData::None => "none",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiResponseWithActions {
     pub message: String,
     pub detected_actions: Vec<DetectedAction>,
+    /// Set by us, not the model
+    #[serde(default)]
+    pub prompt_version: Option<String>,
+}
+
+// ============================================
+// Action Journal Models
+// ============================================
+
+/// Where a journaled change came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionTrigger {
+    Chat,
+    Inbox,
+    Scheduler,
+}
+
+/// How to reverse a journaled change. Restores carry the record as it was before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UndoOperation {
+    DeleteTodo { id: String },
+    DeleteDailyLog { id: String },
+    DeleteInboxItem { id: String },
+    DeleteMilestone { id: String },
+    RecreateTodo { todo: Todo },
+    RestoreTodo { todo: Todo },
+    RestoreDailyLog { log: DailyLog },
+    RestoreMilestone { milestone: Milestone },
+    RestoreProject { project: Project },
+}
+
+/// A change made by the AI or a slash command, kept so it can be undone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub trigger: ActionTrigger,
+    pub action_type: String, // todo_created, progress_logged, ...
+    pub description: String,
+    pub project_id: Option<String>,
+    pub undo: UndoOperation,
+    pub created_at: DateTime<Utc>,
+    pub undone_at: Option<DateTime<Utc>>,
 }
 
 // ============================================
@@ -561,6 +1250,38 @@ impl Project {
     }
 }
 
+impl DailyLog {
+    /// The category the log counts as, preferring the user's correction
+    pub fn effective_category(&self) -> String {
+        self.user_override.clone().unwrap_or_else(|| {
+            serde_json::to_string(&self.category).unwrap().trim_matches('"').to_string()
+        })
+    }
+
+    /// This log as rewritten by `log` for the same day. The row's id, creation time and
+    /// `user_override` stay. A summary the user wrote is only replaced by another one the
+    /// user wrote; a scan over it refreshes just the files.
+    pub fn rewritten_with(&self, log: &DailyLog) -> DailyLog {
+        if self.summary_by_user && !log.summary_by_user {
+            return DailyLog {
+                files_changed: log.files_changed.clone(),
+                ..self.clone()
+            };
+        }
+        DailyLog {
+            id: self.id.clone(),
+            user_override: self.user_override.clone(),
+            created_at: self.created_at,
+            ..log.clone()
+        }
+    }
+
+    /// Lines added plus deleted across the files this log covers
+    pub fn lines_changed(&self) -> i64 {
+        self.files_changed.iter().map(|f| (f.additions + f.deletions) as i64).sum()
+    }
+}
+
 impl Todo {
     pub fn new(title: String, project_id: Option<String>) -> Self {
         Todo {
@@ -583,7 +1304,6 @@ impl InboxItem {
     pub fn new(item_type: InboxItemType, question: String, project_id: Option<String>) -> Self {
         InboxItem {
             id: Uuid::new_v4().to_string(),
-            item_type,
             project_id,
             question,
             context: None,
@@ -593,8 +1313,24 @@ impl InboxItem {
             answer: None,
             created_at: Utc::now(),
             answered_at: None,
+            prompt_version: None,
+            priority: item_type.default_priority(),
+            dedupe_key: None,
+            snoozed_until: None,
+            expires_at: item_type.lifetime_days().map(|days| Utc::now() + chrono::Duration::days(days)),
+            item_type,
         }
     }
+
+    /// Key the item by its type, project and `date`, so later scans that day update it
+    pub fn dedupe_by_day(&mut self, date: &str) {
+        self.dedupe_key = Some(format!(
+            "{}:{}:{}",
+            self.item_type.as_str(),
+            self.project_id.as_deref().unwrap_or("-"),
+            date,
+        ));
+    }
 }
 
 impl ChatMessage {
@@ -606,6 +1342,90 @@ impl ChatMessage {
             content,
             actions: None,
             created_at: Utc::now(),
+            prompt_version: None,
+            citations: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(summary: &str, files: usize, summary_by_user: bool) -> DailyLog {
+        DailyLog {
+            id: Uuid::new_v4().to_string(),
+            project_id: "p1".to_string(),
+            date: "2024-03-01".to_string(),
+            summary: summary.to_string(),
+            category: LogCategory::Feature,
+            files_changed: (0..files)
+                .map(|i| FileChange { path: format!("src/{}.rs", i), additions: 10, deletions: 0 })
+                .collect(),
+            ai_classification: None,
+            user_override: None,
+            created_at: Utc::now(),
+            prompt_version: None,
+            summary_by_user,
         }
     }
+
+    #[test]
+    fn test_migrate_weekly_review_schedule() {
+        // Settings as saved before the schedule moved, when review carried its own weekday and time
+        let saved = |weekday: &str, time: &str| {
+            let mut value = serde_json::to_value(UserSettings::default()).unwrap();
+            value["review"] = serde_json::json!({ "weekly_review": true, "weekday": weekday, "time": time });
+            value
+        };
+        let migrate = |value: &serde_json::Value| -> UserSettings {
+            serde_json::from_str(&UserSettings::migrate_json(&value.to_string()).unwrap()).unwrap()
+        };
+
+        let settings = migrate(&saved("mon", "09:30"));
+        assert!(settings.review.weekly_review);
+        assert_eq!(settings.scheduler.jobs.get("weekly_review").map(String::as_str), Some("mon 09:30"));
+        assert_eq!(UserSettings::migrate_json(&serde_json::to_string(&settings).unwrap()), None);
+
+        assert!(migrate(&saved("fri", "17:00")).scheduler.jobs.is_empty());
+
+        let mut overridden = saved("mon", "09:30");
+        overridden["scheduler"]["jobs"]["weekly_review"] = "sun 20:00".into();
+        assert_eq!(migrate(&overridden).scheduler.jobs.get("weekly_review").map(String::as_str), Some("sun 20:00"));
+    }
+
+    #[test]
+    fn test_scan_rewrites_scanned_log() {
+        let mut existing = log("Morning work", 1, false);
+        existing.user_override = Some("docs".to_string());
+        let mut scanned = log("Whole day", 3, false);
+        scanned.category = LogCategory::Bugfix;
+
+        let stored = existing.rewritten_with(&scanned);
+        assert_eq!(stored.id, existing.id);
+        assert_eq!(stored.user_override.as_deref(), Some("docs"));
+        assert_eq!(stored.summary, "Whole day");
+        assert_eq!(stored.category, LogCategory::Bugfix);
+        assert_eq!(stored.files_changed.len(), 3);
+    }
+
+    #[test]
+    fn test_scan_keeps_user_summary() {
+        let existing = log("Wrote the release notes by hand", 0, true);
+        let mut scanned = log("Updated docs", 4, false);
+        scanned.category = LogCategory::Docs;
+        scanned.prompt_version = Some("analysis@1".to_string());
+
+        let stored = existing.rewritten_with(&scanned);
+        assert_eq!(stored.summary, "Wrote the release notes by hand");
+        assert_eq!(stored.category, LogCategory::Feature);
+        assert_eq!(stored.prompt_version, None);
+        assert!(stored.summary_by_user);
+        assert_eq!(stored.files_changed.len(), 4);
+
+        // The user writing the day again replaces their own summary
+        let rewritten = existing.rewritten_with(&log("Release notes and changelog", 0, true));
+        assert_eq!(rewritten.summary, "Release notes and changelog");
+        assert_eq!(rewritten.id, existing.id);
+    }
 }
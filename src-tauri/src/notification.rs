@@ -1,5 +1,6 @@
 use tauri::AppHandle;
 use tauri_plugin_notification::NotificationExt;
+use crate::i18n::Locale;
 
 /// Notification service for system notifications
 #[derive(Debug, Clone)]
@@ -11,17 +12,18 @@ impl NotificationService {
     }
 
     /// Send a scan complete notification
-    pub fn notify_scan_complete(&self, app: &AppHandle, projects_scanned: usize, items_created: usize) {
-        let title = "Sixarms 掃描完成";
+    pub fn notify_scan_complete(&self, app: &AppHandle, locale: Locale, projects_scanned: usize, items_created: usize) {
+        let title = locale.t("notify.scan_complete.title", &[]);
+        let projects = projects_scanned.to_string();
         let body = if items_created > 0 {
-            format!("已掃描 {} 個專案，建立 {} 個收件箱項目", projects_scanned, items_created)
+            locale.t("notify.scan_complete.body", &[("projects", &projects), ("items", &items_created.to_string())])
         } else {
-            format!("已掃描 {} 個專案，無新改動", projects_scanned)
+            locale.t("notify.scan_complete.body_empty", &[("projects", &projects)])
         };
 
         if let Err(e) = app.notification()
             .builder()
-            .title(title)
+            .title(&title)
             .body(&body)
             .show()
         {
@@ -30,9 +32,9 @@ impl NotificationService {
     }
 
     /// Send an anomaly detected notification
-    pub fn notify_anomaly(&self, app: &AppHandle, project_name: &str, description: &str) {
-        let title = format!("Sixarms 偵測到異常");
-        let body = format!("【{}】{}", project_name, description);
+    pub fn notify_anomaly(&self, app: &AppHandle, locale: Locale, project_name: &str, description: &str) {
+        let title = locale.t("notify.anomaly.title", &[]);
+        let body = locale.t("notify.project_body", &[("project", project_name), ("text", description)]);
 
         if let Err(e) = app.notification()
             .builder()
@@ -45,9 +47,9 @@ impl NotificationService {
     }
 
     /// Send a daily summary notification
-    pub fn notify_daily_summary(&self, app: &AppHandle, project_name: &str, summary: &str) {
-        let title = format!("Sixarms 每日摘要");
-        let body = format!("【{}】{}", project_name, summary);
+    pub fn notify_daily_summary(&self, app: &AppHandle, locale: Locale, project_name: &str, summary: &str) {
+        let title = locale.t("notify.daily_summary.title", &[]);
+        let body = locale.t("notify.project_body", &[("project", project_name), ("text", summary)]);
 
         if let Err(e) = app.notification()
             .builder()
@@ -0,0 +1,415 @@
+// Patterns module - mines stored history for recurring habits worth surfacing
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, Timelike};
+use crate::evidence::{Evidence, Evidenced};
+use crate::i18n::Locale;
+use crate::models::{
+    parse_date, DailyLog, InboxItem, InboxItemType, InsightSettings, Milestone, Project, SuggestedAction,
+};
+
+/// Hours are grouped into blocks this wide when looking for productive times
+const HOUR_BLOCK: u32 = 3;
+
+/// A bucket must hold this many times its fair share to count as a pattern
+const CONCENTRATION_FACTOR: f64 = 1.5;
+
+/// Minimum observed transitions before a category sequence is reported
+const MIN_TRANSITION_SUPPORT: u32 = 5;
+
+/// A category sequence must be at least this likely, and this much more likely than chance
+const MIN_TRANSITION_CONFIDENCE: f64 = 0.4;
+const MIN_TRANSITION_LIFT: f64 = 1.5;
+
+/// Milestones observed per project before abandonment is reported
+const MIN_MILESTONES_OBSERVED: u32 = 2;
+
+#[derive(Debug, Clone)]
+pub struct Insight {
+    /// Stable identity used to avoid repeating the same insight
+    pub key: String,
+    pub project_id: Option<String>,
+    pub summary: String,
+    pub evidence: Vec<Evidence>,
+}
+
+impl Insight {
+    fn new(key: String, project_id: Option<String>, summary: String) -> Self {
+        Insight {
+            key,
+            project_id,
+            summary,
+            evidence: Vec::new(),
+        }
+    }
+
+    pub fn to_inbox_item(&self, locale: Locale) -> InboxItem {
+        let mut item = InboxItem::new(
+            InboxItemType::PatternInsight,
+            locale.t("inbox.insight.question", &[("summary", &self.summary)]),
+            self.project_id.clone(),
+        );
+        item.context = Some(self.evidence_text(locale));
+        item.suggested_actions = vec![
+            SuggestedAction {
+                id: "acknowledge".to_string(),
+                label: locale.t("action.acknowledge", &[]),
+                icon: Some("check".to_string()),
+            },
+            SuggestedAction {
+                id: "dismiss".to_string(),
+                label: locale.t("action.dismiss", &[]),
+                icon: Some("x".to_string()),
+            },
+        ];
+        item
+    }
+}
+
+impl Evidenced for Insight {
+    fn evidence(&self) -> &[Evidence] {
+        &self.evidence
+    }
+
+    fn evidence_mut(&mut self) -> &mut Vec<Evidence> {
+        &mut self.evidence
+    }
+}
+
+/// Everything the miners look at
+pub struct PatternInput<'a> {
+    pub today: NaiveDate,
+    pub projects: &'a [Project],
+    pub logs: &'a [DailyLog],
+    pub milestones: &'a [Milestone],
+    pub commit_times: &'a [DateTime<FixedOffset>],
+    /// Language the insights are written in
+    pub locale: Locale,
+}
+
+/// Run every miner and collect the insights found
+pub fn mine(input: &PatternInput, settings: &InsightSettings) -> Vec<Insight> {
+    let mut insights = Vec::new();
+
+    insights.extend(productive_weekday(input.commit_times, settings.min_samples, input.locale));
+    insights.extend(productive_hours(input.commit_times, settings.min_samples, input.locale));
+    insights.extend(category_sequence(input.logs, input.locale));
+    insights.extend(milestone_abandonment(input, settings.abandon_gap_days));
+
+    insights
+}
+
+fn percent(part: u32, total: u32) -> u32 {
+    (part as f64 / total as f64 * 100.0).round() as u32
+}
+
+/// The bucket with the most samples, if it is clearly above an even spread
+fn dominant_bucket(counts: &[u32]) -> Option<(usize, u32, u32)> {
+    let total: u32 = counts.iter().sum();
+    let (index, &max) = counts.iter().enumerate().max_by_key(|(i, c)| (**c, std::cmp::Reverse(*i)))?;
+    let fair_share = total as f64 / counts.len() as f64;
+    if total == 0 || (max as f64) < fair_share * CONCENTRATION_FACTOR {
+        return None;
+    }
+    Some((index, max, total))
+}
+
+/// Weekday with the most commits
+pub fn productive_weekday(commit_times: &[DateTime<FixedOffset>], min_samples: u32, locale: Locale) -> Option<Insight> {
+    if (commit_times.len() as u32) < min_samples {
+        return None;
+    }
+
+    let mut counts = [0u32; 7];
+    for t in commit_times {
+        counts[t.with_timezone(&Local).weekday().num_days_from_monday() as usize] += 1;
+    }
+
+    let (day, max, total) = dominant_bucket(&counts)?;
+    let weekday = locale.t(&format!("weekday.{}", day), &[]);
+    let commits = |count: String| locale.t("insight.commits", &[("count", &count)]);
+    Some(
+        Insight::new(
+            format!("productive_weekday:{}", day),
+            None,
+            locale.t("insight.productive_weekday", &[
+                ("weekday", &weekday),
+                ("percent", &percent(max, total).to_string()),
+            ]),
+        )
+        .with(weekday, commits(max.to_string()))
+        .with(locale.t("insight.total_commits", &[]), total)
+        .with(locale.t("insight.productive_weekday.daily_average", &[]), commits(format!("{:.1}", total as f64 / 7.0))),
+    )
+}
+
+/// Block of hours with the most commits
+pub fn productive_hours(commit_times: &[DateTime<FixedOffset>], min_samples: u32, locale: Locale) -> Option<Insight> {
+    if (commit_times.len() as u32) < min_samples {
+        return None;
+    }
+
+    let mut counts = vec![0u32; (24 / HOUR_BLOCK) as usize];
+    for t in commit_times {
+        counts[(t.with_timezone(&Local).hour() / HOUR_BLOCK) as usize] += 1;
+    }
+
+    let (block, max, total) = dominant_bucket(&counts)?;
+    let start = block as u32 * HOUR_BLOCK;
+    let end = start + HOUR_BLOCK;
+    let hours = format!("{:02}:00–{:02}:00", start, end);
+    Some(
+        Insight::new(
+            format!("productive_hours:{}", start),
+            None,
+            locale.t("insight.productive_hours", &[
+                ("hours", &hours),
+                ("percent", &percent(max, total).to_string()),
+            ]),
+        )
+        .with(hours, locale.t("insight.commits", &[("count", &max.to_string())]))
+        .with(locale.t("insight.total_commits", &[]), total),
+    )
+}
+
+/// Most frequent category per project per day, in date order
+fn daily_categories(logs: &[DailyLog]) -> BTreeMap<&str, Vec<(NaiveDate, String)>> {
+    let mut counts: BTreeMap<(&str, NaiveDate), BTreeMap<String, u32>> = BTreeMap::new();
+    for log in logs {
+        if let Ok(date) = parse_date(&log.date) {
+            *counts.entry((log.project_id.as_str(), date))
+                .or_default()
+                .entry(log.effective_category())
+                .or_default() += 1;
+        }
+    }
+
+    let mut days: BTreeMap<&str, Vec<(NaiveDate, String)>> = BTreeMap::new();
+    for ((project_id, date), cats) in counts {
+        if let Some((category, _)) = cats.into_iter().max_by_key(|(_, n)| *n) {
+            days.entry(project_id).or_default().push((date, category));
+        }
+    }
+    days
+}
+
+/// Category that most reliably follows another on the next active day of a project
+pub fn category_sequence(logs: &[DailyLog], locale: Locale) -> Option<Insight> {
+    let mut pairs: HashMap<(String, String), u32> = HashMap::new();
+    let mut from_counts: HashMap<String, u32> = HashMap::new();
+    let mut to_counts: HashMap<String, u32> = HashMap::new();
+    let mut total = 0u32;
+
+    for days in daily_categories(logs).values() {
+        for window in days.windows(2) {
+            let (from, to) = (&window[0].1, &window[1].1);
+            *pairs.entry((from.clone(), to.clone())).or_default() += 1;
+            *from_counts.entry(from.clone()).or_default() += 1;
+            *to_counts.entry(to.clone()).or_default() += 1;
+            total += 1;
+        }
+    }
+
+    // Highest confidence wins; ties go to the better supported, then alphabetical, pair
+    let mut best: Option<(f64, &(String, String), u32)> = None;
+    for (pair, &count) in &pairs {
+        if pair.0 == pair.1 || count < MIN_TRANSITION_SUPPORT {
+            continue;
+        }
+        let confidence = count as f64 / from_counts[&pair.0] as f64;
+        let baseline = to_counts[&pair.1] as f64 / total as f64;
+        if confidence < MIN_TRANSITION_CONFIDENCE || confidence / baseline < MIN_TRANSITION_LIFT {
+            continue;
+        }
+        let better = match best {
+            Some((c, p, n)) => confidence > c || (confidence == c && (count, std::cmp::Reverse(pair)) > (n, std::cmp::Reverse(p))),
+            None => true,
+        };
+        if better {
+            best = Some((confidence, pair, count));
+        }
+    }
+
+    let ((from, to), count) = best.map(|(_, pair, count)| (pair.clone(), count))?;
+    let from_total = from_counts[&from];
+    let baseline = percent(to_counts[&to], total);
+
+    Some(
+        Insight::new(
+            format!("category_sequence:{}>{}", from, to),
+            None,
+            locale.t("insight.category_sequence", &[
+                ("from", &from),
+                ("to", &to),
+                ("percent", &percent(count, from_total).to_string()),
+            ]),
+        )
+        .with(
+            format!("{} → {}", from, to),
+            locale.t("insight.category_sequence.count", &[("count", &count.to_string()), ("total", &from_total.to_string())]),
+        )
+        .with(locale.t("insight.category_sequence.baseline", &[("category", &to)]), format!("{}%", baseline)),
+    )
+}
+
+/// Projects that usually go quiet for `gap_days` after completing a milestone
+pub fn milestone_abandonment(input: &PatternInput, gap_days: u32) -> Vec<Insight> {
+    let gap = Duration::days(gap_days as i64);
+
+    let mut log_dates: HashMap<&str, Vec<NaiveDate>> = HashMap::new();
+    for log in input.logs {
+        if let Ok(date) = parse_date(&log.date) {
+            log_dates.entry(log.project_id.as_str()).or_default().push(date);
+        }
+    }
+
+    let mut insights = Vec::new();
+    for project in input.projects {
+        let dates = log_dates.get(project.id.as_str()).map(Vec::as_slice).unwrap_or(&[]);
+
+        let mut observed = 0u32;
+        let mut abandoned = 0u32;
+        for milestone in input.milestones.iter().filter(|m| m.project_id == project.id) {
+            let Some(completed) = milestone.completed_at.map(|c| c.with_timezone(&Local).date_naive()) else {
+                continue;
+            };
+            // Only judge milestones whose follow-up window has fully passed
+            if completed + gap > input.today {
+                continue;
+            }
+            observed += 1;
+            if !dates.iter().any(|&d| d > completed && d <= completed + gap) {
+                abandoned += 1;
+            }
+        }
+
+        if observed < MIN_MILESTONES_OBSERVED || abandoned * 2 < observed {
+            continue;
+        }
+
+        insights.push(
+            Insight::new(
+                format!("milestone_abandonment:{}", project.id),
+                Some(project.id.clone()),
+                input.locale.t("insight.milestone_abandonment", &[("project", &project.name)]),
+            )
+            .with(input.locale.t("insight.milestone_abandonment.completed", &[]), observed)
+            .with(
+                input.locale.t("insight.milestone_abandonment.quiet", &[("days", &gap_days.to_string())]),
+                input.locale.t("insight.milestone_abandonment.count", &[
+                    ("count", &abandoned.to_string()),
+                    ("percent", &percent(abandoned, observed).to_string()),
+                ]),
+            ),
+        );
+    }
+
+    insights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use crate::models::{LogCategory, MilestoneSource, MilestoneStatus};
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn log(project_id: &str, date: &str, category: LogCategory) -> DailyLog {
+        DailyLog {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: project_id.to_string(),
+            date: date.to_string(),
+            summary: String::new(),
+            category,
+            files_changed: vec![],
+            ai_classification: None,
+            user_override: None,
+            created_at: Utc::now(),
+            prompt_version: None,
+            summary_by_user: false,
+        }
+    }
+
+    fn local_time(date: &str, hour: u32) -> DateTime<FixedOffset> {
+        let naive = d(date).and_hms_opt(hour, 0, 0).unwrap();
+        Local.from_local_datetime(&naive).unwrap().fixed_offset()
+    }
+
+    #[test]
+    fn test_productive_weekday_and_hours() {
+        // 2024-01-19 is a Friday
+        let mut times: Vec<_> = (0..12).map(|_| local_time("2024-01-19", 22)).collect();
+        times.extend(["2024-01-15", "2024-01-16", "2024-01-17", "2024-01-18"].iter().map(|d| local_time(d, 10)));
+
+        let weekday = productive_weekday(&times, 10, Locale::default()).unwrap();
+        assert_eq!(weekday.key, "productive_weekday:4");
+        assert!(weekday.summary.contains("星期五"));
+
+        let hours = productive_hours(&times, 10, Locale::new("en")).unwrap();
+        assert_eq!(hours.key, "productive_hours:21");
+        assert_eq!(hours.evidence_text(Locale::new("en")), "21:00–24:00: 12 commits\nTotal commits: 16");
+
+        assert!(productive_weekday(&times, 100, Locale::default()).is_none());
+    }
+
+    #[test]
+    fn test_even_spread_is_not_a_pattern() {
+        let times: Vec<_> = (15..22).map(|day| local_time(&format!("2024-01-{}", day), (day as u32 - 15) * 3)).collect();
+        assert!(productive_weekday(&times, 1, Locale::default()).is_none());
+        assert!(productive_hours(&times, 1, Locale::default()).is_none());
+    }
+
+    #[test]
+    fn test_category_sequence_finds_feature_then_bugfix() {
+        let mut logs = Vec::new();
+        for week in 0..6 {
+            let base = d("2024-01-01") + Duration::days(week * 7);
+            logs.push(log("p1", &base.format("%Y-%m-%d").to_string(), LogCategory::Feature));
+            logs.push(log("p1", &(base + Duration::days(1)).format("%Y-%m-%d").to_string(), LogCategory::Bugfix));
+            if week % 2 == 0 {
+                logs.push(log("p1", &(base + Duration::days(2)).format("%Y-%m-%d").to_string(), LogCategory::Docs));
+            }
+        }
+
+        let insight = category_sequence(&logs, Locale::default()).unwrap();
+        assert_eq!(insight.key, "category_sequence:feature>bugfix");
+    }
+
+    #[test]
+    fn test_milestone_abandonment() {
+        let mut project = Project::new("app".to_string(), "/tmp/app".to_string());
+        project.id = "p1".to_string();
+        let milestone = |date: &str| Milestone {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: "p1".to_string(),
+            title: "v1".to_string(),
+            description: None,
+            version: None,
+            git_tag: None,
+            status: MilestoneStatus::Completed,
+            source: MilestoneSource::Manual,
+            target_date: None,
+            completed_at: Some(Local.from_local_datetime(&d(date).and_hms_opt(12, 0, 0).unwrap()).unwrap().with_timezone(&Utc)),
+            created_at: Utc::now(),
+        };
+        let milestones = vec![milestone("2024-01-01"), milestone("2024-03-01"), milestone("2024-05-20")];
+        // Activity only follows the second milestone; the third is too recent to judge
+        let logs = vec![log("p1", "2024-03-05", LogCategory::Feature)];
+        let projects = vec![project];
+
+        let input = PatternInput {
+            today: d("2024-06-01"),
+            projects: &projects,
+            logs: &logs,
+            milestones: &milestones,
+            commit_times: &[],
+            locale: Locale::default(),
+        };
+
+        let insights = milestone_abandonment(&input, 21);
+        assert_eq!(insights.len(), 1);
+        assert_eq!(insights[0].key, "milestone_abandonment:p1");
+    }
+}
@@ -0,0 +1,409 @@
+// Prompts module - named, versioned prompt templates, overridable from the app data dir
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use serde::Serialize;
+use crate::grok::GrokMessage;
+use crate::i18n::{self, LANGUAGES};
+
+/// Each built-in template in every language of `i18n::LANGUAGES`, in that order
+macro_rules! builtin {
+    ($($name:literal),* $(,)?) => {
+        &[$(($name, [
+            include_str!(concat!("../prompts/zh-HK/", $name, ".prompt")),
+            include_str!(concat!("../prompts/zh-TW/", $name, ".prompt")),
+            include_str!(concat!("../prompts/en/", $name, ".prompt")),
+            include_str!(concat!("../prompts/ja/", $name, ".prompt")),
+        ])),*]
+    };
+}
+
+/// Templates shipped with the app. An override is a file of the same name plus
+/// `.prompt` in the language's folder of the prompts directory, in the same format.
+const BUILTIN: &[(&str, [&str; 4])] = builtin![
+    "agent",
+    "analysis",
+    "analysis_batch",
+    "anomaly_detect",
+    "anomaly_phrase",
+    "chat",
+    "classify",
+    "corrections",
+    "intent",
+    "persona",
+    "structured_retry",
+    "summary",
+];
+
+/// A prompt made of named sections. Chat prompts use `system` and `user`; other
+/// sections hold fragments the caller fills in and passes on as variables.
+///
+/// In a section, `{{name}}` is replaced by the variable `name` and `{{>other}}` by
+/// the section of the same name in template `other`. Unknown variables are left as
+/// written so mistakes in a custom template show up in its output.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub language: String,
+    pub version: u32,
+    /// Loaded from a user override rather than the built-in default
+    pub custom: bool,
+    pub source: String,
+    #[serde(skip)]
+    sections: Vec<(String, String)>,
+}
+
+impl PromptTemplate {
+    /// Parse a template: `version: N`, then sections each starting with `=== name ===`
+    pub fn parse(language: &str, name: &str, source: &str, custom: bool) -> Result<Self, String> {
+        let mut version = None;
+        let mut sections: Vec<(String, String)> = Vec::new();
+
+        for line in source.lines() {
+            let header = line.trim()
+                .strip_prefix("===")
+                .and_then(|l| l.strip_suffix("==="))
+                .map(str::trim)
+                .filter(|h| !h.is_empty());
+
+            if let Some(section) = header {
+                if sections.iter().any(|(s, _)| s == section) {
+                    return Err(format!("Section \"{}\" appears twice", section));
+                }
+                sections.push((section.to_string(), String::new()));
+            } else if let Some((_, text)) = sections.last_mut() {
+                text.push_str(line);
+                text.push('\n');
+            } else if let Some(v) = line.trim().strip_prefix("version:") {
+                version = Some(v.trim().parse::<u32>().map_err(|_| format!("Invalid version \"{}\"", v.trim()))?);
+            } else if !line.trim().is_empty() {
+                return Err(format!("Unexpected line before the first section: {}", line));
+            }
+        }
+
+        let version = version.ok_or("Missing \"version: N\" header")?;
+        if sections.is_empty() {
+            return Err("Template has no sections".to_string());
+        }
+        for (_, text) in sections.iter_mut() {
+            *text = text.trim().to_string();
+        }
+
+        Ok(PromptTemplate {
+            name: name.to_string(),
+            language: language.to_string(),
+            version,
+            custom,
+            source: source.to_string(),
+            sections,
+        })
+    }
+
+    /// What stored AI outputs record, e.g. `en/classify@2`, or `en/classify@2-custom`
+    /// for an override. Cached replies are keyed by it, so each language caches its own.
+    pub fn version_id(&self) -> String {
+        if self.custom {
+            format!("{}/{}@{}-custom", self.language, self.name, self.version)
+        } else {
+            format!("{}/{}@{}", self.language, self.name, self.version)
+        }
+    }
+
+    fn section(&self, section: &str) -> Option<&str> {
+        self.sections.iter().find(|(s, _)| s == section).map(|(_, text)| text.as_str())
+    }
+
+    /// Fill in a section; a section the template doesn't have renders empty
+    pub fn render(&self, section: &str, vars: &[(&str, &str)]) -> String {
+        interpolate(self.section(section).unwrap_or_default(), vars)
+    }
+
+    /// The system and user messages of a one-shot prompt
+    pub fn messages(&self, vars: &[(&str, &str)]) -> Vec<GrokMessage> {
+        ["system", "user"].iter()
+            .filter(|role| self.section(role).is_some())
+            .map(|role| GrokMessage::new(*role, self.render(role, vars)))
+            .collect()
+    }
+}
+
+pub(crate) fn interpolate(text: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let name = &after[..end];
+            let is_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            let value = vars.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+            value.filter(|_| is_name).map(|v| (v, end + 2))
+        });
+
+        match value {
+            Some((value, len)) => {
+                out.push_str(value);
+                rest = &after[len..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Replace `{{>name}}` with the same section of another template. Includes are not
+/// followed further, so a template can't include itself through a cycle.
+fn resolve_includes(templates: &mut HashMap<String, PromptTemplate>) {
+    let unresolved = templates.clone();
+    for template in templates.values_mut() {
+        for (section, text) in template.sections.iter_mut() {
+            let mut from = 0;
+            while let Some(start) = text[from..].find("{{>").map(|i| from + i) {
+                let Some(len) = text[start..].find("}}") else { break };
+                let other = text[start + 3..start + len].trim();
+                let included = unresolved.get(other)
+                    .and_then(|t| t.section(section))
+                    .unwrap_or_else(|| {
+                        log::warn!("Prompt \"{}\" includes unknown section {}/{}", template.name, other, section);
+                        ""
+                    })
+                    .to_string();
+                text.replace_range(start..start + len + 2, &included);
+                from = start + included.len();
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Registry {
+    dir: Option<PathBuf>,
+    /// Language of the templates `get` and `list` return
+    language: &'static str,
+    /// Templates by language, then name
+    templates: HashMap<&'static str, HashMap<String, PromptTemplate>>,
+}
+
+/// The templates in use: built-ins, replaced by any valid overrides found in the
+/// prompts directory. Cheap to clone; clones share the same templates and language.
+#[derive(Debug, Clone)]
+pub struct Prompts {
+    registry: Arc<RwLock<Registry>>,
+}
+
+impl Prompts {
+    pub fn new() -> Self {
+        let prompts = Prompts {
+            registry: Arc::new(RwLock::new(Registry {
+                dir: None,
+                language: i18n::DEFAULT_LANGUAGE,
+                templates: HashMap::new(),
+            })),
+        };
+        prompts.reload();
+        prompts
+    }
+
+    /// Start reading overrides from `dir`
+    pub fn load(&self, dir: PathBuf) {
+        self.registry.write().unwrap().dir = Some(dir);
+        self.reload();
+    }
+
+    /// Switch the templates `get` returns to `language` (from `UserSettings.language`)
+    pub fn set_language(&self, language: &str) {
+        self.registry.write().unwrap().language = i18n::normalize(language);
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.registry.read().unwrap().language
+    }
+
+    /// Only built-in templates can be overridden, which also keeps names from reaching
+    /// outside the prompts directory
+    fn check_builtin(name: &str) -> Result<(), String> {
+        if BUILTIN.iter().any(|(n, _)| *n == name) {
+            Ok(())
+        } else {
+            Err(format!("Unknown prompt template \"{}\"", name))
+        }
+    }
+
+    fn override_path(dir: &std::path::Path, language: &str, name: &str) -> PathBuf {
+        dir.join(language).join(format!("{}.prompt", name))
+    }
+
+    fn reload(&self) {
+        let dir = self.registry.read().unwrap().dir.clone();
+        let mut all = HashMap::new();
+
+        for (i, language) in LANGUAGES.iter().enumerate() {
+            let mut templates = HashMap::new();
+            for (name, sources) in BUILTIN {
+                let custom = dir.as_ref()
+                    .and_then(|dir| std::fs::read_to_string(Self::override_path(dir, language, name)).ok())
+                    .and_then(|source| match PromptTemplate::parse(language, name, &source, true) {
+                        Ok(template) => Some(template),
+                        Err(e) => {
+                            log::warn!("Ignoring invalid prompt override \"{}/{}\": {}", language, name, e);
+                            None
+                        }
+                    });
+
+                let template = match custom {
+                    Some(template) => template,
+                    None => PromptTemplate::parse(language, name, sources[i], false).expect("built-in prompt is valid"),
+                };
+                templates.insert(name.to_string(), template);
+            }
+
+            resolve_includes(&mut templates);
+            all.insert(*language, templates);
+        }
+
+        self.registry.write().unwrap().templates = all;
+    }
+
+    /// A template in the current language
+    pub fn get(&self, name: &str) -> PromptTemplate {
+        let registry = self.registry.read().unwrap();
+        let language = registry.language;
+        registry.templates.get(language).and_then(|t| t.get(name)).cloned().unwrap_or_else(|| {
+            log::error!("Unknown prompt template \"{}\"", name);
+            PromptTemplate {
+                name: name.to_string(),
+                language: language.to_string(),
+                version: 0,
+                custom: false,
+                source: String::new(),
+                sections: Vec::new(),
+            }
+        })
+    }
+
+    /// Every template in the current language
+    pub fn list(&self) -> Vec<PromptTemplate> {
+        let registry = self.registry.read().unwrap();
+        let mut templates: Vec<_> = registry.templates.get(registry.language)
+            .map(|t| t.values().cloned().collect())
+            .unwrap_or_default();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+
+    /// Write an override for a built-in template in the current language and start using it
+    pub fn save_override(&self, name: &str, source: &str) -> Result<PromptTemplate, String> {
+        Self::check_builtin(name)?;
+        let language = self.language();
+        PromptTemplate::parse(language, name, source, true)?;
+
+        let dir = self.registry.read().unwrap().dir.clone().ok_or("Prompts directory not set")?;
+        let path = Self::override_path(&dir, language, name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, source).map_err(|e| e.to_string())?;
+
+        self.reload();
+        Ok(self.get(name))
+    }
+
+    /// Delete a template's override in the current language, going back to the built-in default
+    pub fn reset(&self, name: &str) -> Result<PromptTemplate, String> {
+        Self::check_builtin(name)?;
+        if let Some(dir) = self.registry.read().unwrap().dir.clone() {
+            let path = Self::override_path(&dir, self.language(), name);
+            if path.exists() {
+                std::fs::remove_file(path).map_err(|e| e.to_string())?;
+            }
+        }
+
+        self.reload();
+        Ok(self.get(name))
+    }
+}
+
+impl Default for Prompts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_prompts_parse_and_resolve() {
+        let prompts = Prompts::new();
+        assert_eq!(prompts.list().len(), BUILTIN.len());
+
+        let chat = prompts.get("chat");
+        assert_eq!(chat.version_id(), "zh-HK/chat@1");
+        let system = chat.render("system", &[("project_context", "")]);
+        assert!(system.starts_with("你係 Sixarms"));
+        assert!(!system.contains("{{"));
+
+        // JSON examples in a template are not placeholders
+        let intent = prompts.get("intent").render("system", &[("project_context", "")]);
+        assert!(intent.contains("\"message\""));
+        assert!(!intent.contains("{{"));
+
+        prompts.set_language("en");
+        let chat = prompts.get("chat");
+        assert_eq!(chat.version_id(), "en/chat@1");
+        assert!(chat.render("system", &[("project_context", "")]).starts_with("You are Sixarms"));
+    }
+
+    #[test]
+    fn test_overrides_only_for_builtin_names() {
+        let prompts = Prompts::new();
+        assert!(prompts.reset("../../settings").unwrap_err().starts_with("Unknown prompt template"));
+        assert!(prompts.save_override("../chat", "version: 1\n=== system ===\nhi").unwrap_err().starts_with("Unknown prompt template"));
+        assert_eq!(prompts.reset("chat").unwrap().version_id(), "zh-HK/chat@1");
+    }
+
+    fn variables(text: &str) -> std::collections::BTreeSet<&str> {
+        text.split("{{").skip(1).filter_map(|s| s.split_once("}}")).map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_every_language_has_matching_templates() {
+        for (name, sources) in BUILTIN {
+            let default = PromptTemplate::parse(LANGUAGES[0], name, sources[0], false).unwrap();
+            for (language, source) in LANGUAGES.iter().zip(sources).skip(1) {
+                let template = PromptTemplate::parse(language, name, source, false).unwrap();
+                assert_eq!(template.version, default.version, "{}/{}", language, name);
+
+                let sections: Vec<_> = template.sections.iter().map(|(s, _)| s).collect();
+                assert_eq!(sections, default.sections.iter().map(|(s, _)| s).collect::<Vec<_>>(), "{}/{}", language, name);
+                for ((section, text), (_, default_text)) in template.sections.iter().zip(&default.sections) {
+                    assert_eq!(variables(text), variables(default_text), "{}/{} {}", language, name, section);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let source = "version: 3\n\n=== system ===\nHi {{name}}, {{unknown}} {\"a\": 1}\n\n=== user ===\n{{ name }}\n";
+        let template = PromptTemplate::parse("en", "greet", source, true).unwrap();
+        assert_eq!(template.version_id(), "en/greet@3-custom");
+
+        let messages = template.messages(&[("name", "Ada")]);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "Hi Ada, {{unknown}} {\"a\": 1}");
+        assert_eq!(messages[1].content, "{{ name }}");
+        assert_eq!(template.render("missing", &[]), "");
+
+        assert!(PromptTemplate::parse("en", "x", "=== user ===\nhi", false).is_err());
+        assert!(PromptTemplate::parse("en", "x", "version: 1\nhi", false).is_err());
+        assert!(PromptTemplate::parse("en", "x", "version: 1\n=== a ===\n=== a ===", false).is_err());
+    }
+}
@@ -0,0 +1,426 @@
+// Retrieval module - gathers the stored records a chat question is about, so answers cite real data
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use regex::Regex;
+use crate::db::Database;
+use crate::models::{Citation, Project, ProjectStatus, SemanticMatch, SourceKind};
+use crate::scanner::GitScanner;
+
+/// Most records put in front of the model for one question
+const MAX_SOURCES: usize = 30;
+/// Semantic matches below this similarity aren't worth the model's attention
+const MIN_SEMANTIC_SCORE: f32 = 0.3;
+/// Searched when the question names no time; only records matching its words are used
+const DEFAULT_LOOKBACK_DAYS: i64 = 30;
+
+const STOPWORDS: &[&str] = &[
+    "about", "all", "and", "any", "are", "can", "did", "does", "done", "for", "from", "have",
+    "how", "last", "month", "much", "many", "past", "project", "show", "tell", "that", "the",
+    "this", "today", "was", "week", "were", "what", "when", "where", "which", "who", "why",
+    "with", "work", "worked", "working", "yesterday", "you", "your",
+];
+
+/// A record considered for the answer, before it gets a label
+struct Candidate {
+    kind: SourceKind,
+    id: String,
+    date: String,
+    project_id: Option<String>,
+    text: String,
+}
+
+/// Records relevant to `question`, labelled S1, S2, ... oldest first. A question naming a
+/// time gets everything from that time; otherwise only records sharing its words count.
+pub fn gather(
+    db: &Database,
+    scanner: &GitScanner,
+    question: &str,
+    current_project: Option<&str>,
+) -> Result<Vec<Citation>, String> {
+    let today = Local::now().date_naive();
+    let projects = db.get_projects().map_err(|e| e.to_string())?;
+    let project = mentioned_project(question, &projects)
+        .or_else(|| current_project.and_then(|id| projects.iter().find(|p| p.id == id)));
+    let range = time_range(question, today);
+    let (since, until) = range.unwrap_or((today - Duration::days(DEFAULT_LOOKBACK_DAYS), today));
+    let (since_str, until_str) = (since.to_string(), until.to_string());
+    let in_scope = |project_id: Option<&str>| project.is_none_or(|p| project_id == Some(p.id.as_str()));
+    let in_range = |date: &str| date >= since_str.as_str() && date <= until_str.as_str();
+
+    let mut candidates = Vec::new();
+
+    for log in db.get_daily_logs_since(&since_str).map_err(|e| e.to_string())? {
+        if log.date <= until_str && in_scope(Some(&log.project_id)) {
+            candidates.push(Candidate {
+                kind: SourceKind::Log,
+                id: log.id,
+                date: log.date,
+                project_id: Some(log.project_id),
+                text: log.summary,
+            });
+        }
+    }
+
+    let repos = projects.iter()
+        .filter(|p| p.status != ProjectStatus::Archived && in_scope(Some(&p.id)));
+    for repo in repos {
+        // Projects that aren't git repositories just have no commits
+        let Ok(commits) = scanner.get_commits(Path::new(&repo.path), &since_str, &until_str) else { continue };
+        candidates.extend(commits.into_iter().map(|c| Candidate {
+            kind: SourceKind::Commit,
+            id: c.hash,
+            date: c.date,
+            project_id: Some(repo.id.clone()),
+            text: c.subject,
+        }));
+    }
+
+    for todo in db.get_todos(None).map_err(|e| e.to_string())? {
+        if !in_scope(todo.project_id.as_deref()) {
+            continue;
+        }
+        // Dated by what happened to it in the range, else when it was created
+        let completed = todo.completed_at.map(|d| d.with_timezone(&Local).date_naive().to_string());
+        let created = todo.created_at.with_timezone(&Local).date_naive().to_string();
+        let date = [completed, todo.due_date.clone(), Some(created.clone())].into_iter()
+            .flatten()
+            .find(|d| in_range(d))
+            .unwrap_or(created);
+        candidates.push(Candidate {
+            kind: SourceKind::Todo,
+            id: todo.id,
+            date,
+            project_id: todo.project_id,
+            text: format!("{} ({})", todo.title, todo.status.as_str()),
+        });
+    }
+
+    for milestone in db.get_milestones(None).map_err(|e| e.to_string())? {
+        if !in_scope(Some(&milestone.project_id)) {
+            continue;
+        }
+        let completed = milestone.completed_at.map(|d| d.with_timezone(&Local).date_naive().to_string());
+        let created = milestone.created_at.with_timezone(&Local).date_naive().to_string();
+        let date = [completed, milestone.target_date.clone(), Some(created.clone())].into_iter()
+            .flatten()
+            .find(|d| in_range(d))
+            .unwrap_or(created);
+        let title = match &milestone.version {
+            Some(version) => format!("{} {}", milestone.title, version),
+            None => milestone.title,
+        };
+        candidates.push(Candidate {
+            kind: SourceKind::Milestone,
+            id: milestone.id,
+            date,
+            project_id: Some(milestone.project_id),
+            text: format!("{} ({})", title, milestone.status.as_str()),
+        });
+    }
+
+    let mut terms = search_terms(question);
+    if let Some(p) = project {
+        let name = p.name.to_lowercase();
+        terms.retain(|t| !name.contains(t.as_str()));
+    }
+    Ok(select(candidates, &terms, range.is_some().then_some((since_str.as_str(), until_str.as_str()))))
+}
+
+/// Rank candidates by the question's words and label the best. With a time range only
+/// records dated in it are kept, all of them if they fit; without one, only matching records.
+fn select(candidates: Vec<Candidate>, terms: &[String], range: Option<(&str, &str)>) -> Vec<Citation> {
+    let mut scored: Vec<(usize, Candidate)> = candidates.into_iter()
+        .filter(|c| range.is_none_or(|(since, until)| c.date.as_str() >= since && c.date.as_str() <= until))
+        .map(|c| {
+            let text = c.text.to_lowercase();
+            (terms.iter().filter(|t| text.contains(t.as_str())).count(), c)
+        })
+        .filter(|(score, _)| range.is_some() || *score > 0)
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then_with(|| b.date.cmp(&a.date)));
+    scored.truncate(MAX_SOURCES);
+    scored.sort_by(|(_, a), (_, b)| a.date.cmp(&b.date));
+
+    scored.into_iter()
+        .enumerate()
+        .map(|(i, (_, c))| Citation {
+            label: format!("S{}", i + 1),
+            kind: c.kind,
+            id: c.id,
+            date: c.date,
+            project_id: c.project_id,
+            text: c.text,
+        })
+        .collect()
+}
+
+/// Add records the semantic index found close to the question, up to the source limit,
+/// then relabel everything oldest first
+pub fn add_matches(sources: Vec<Citation>, matches: Vec<SemanticMatch>) -> Vec<Citation> {
+    let mut merged = sources;
+    for m in matches {
+        if merged.len() >= MAX_SOURCES {
+            break;
+        }
+        if m.score < MIN_SEMANTIC_SCORE || merged.iter().any(|c| c.kind == m.kind && c.id == m.id) {
+            continue;
+        }
+        merged.push(Citation {
+            label: String::new(),
+            kind: m.kind,
+            id: m.id,
+            date: m.date,
+            project_id: m.project_id,
+            text: m.text,
+        });
+    }
+
+    merged.sort_by(|a, b| a.date.cmp(&b.date));
+    for (i, source) in merged.iter_mut().enumerate() {
+        source.label = format!("S{}", i + 1);
+    }
+    merged
+}
+
+/// One line per source, as the model sees them
+pub fn format_sources(sources: &[Citation], projects: &[Project]) -> String {
+    let names: HashMap<&str, &str> = projects.iter().map(|p| (p.id.as_str(), p.name.as_str())).collect();
+    sources.iter()
+        .map(|s| {
+            let kind = serde_json::to_string(&s.kind).unwrap();
+            let project = s.project_id.as_deref().and_then(|id| names.get(id)).copied().unwrap_or("-");
+            format!("[{}] {} {} ({}): {}", s.label, s.date, kind.trim_matches('"'), project, s.text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The sources an answer cites as `[S1]` or `[S1, S3]`, in the order first cited
+pub fn cited(answer: &str, sources: &[Citation]) -> Vec<Citation> {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    let marker = MARKER.get_or_init(|| Regex::new(r"\[\s*(S\d+(?:\s*[,，、]\s*S\d+)*)\s*\]").unwrap());
+
+    let mut found: Vec<Citation> = Vec::new();
+    for group in marker.captures_iter(answer) {
+        for label in group[1].split([',', '，', '、']).map(str::trim) {
+            if found.iter().any(|c| c.label == label) {
+                continue;
+            }
+            if let Some(source) = sources.iter().find(|s| s.label == label) {
+                found.push(source.clone());
+            }
+        }
+    }
+    found
+}
+
+/// The project whose name appears in the question; the longest name wins
+pub fn mentioned_project<'a>(question: &str, projects: &'a [Project]) -> Option<&'a Project> {
+    let question = question.to_lowercase();
+    projects.iter()
+        .filter(|p| !p.name.trim().is_empty() && question.contains(&p.name.trim().to_lowercase()))
+        .max_by_key(|p| p.name.trim().len())
+}
+
+/// Words to match records against: longer latin words, and pairs of characters from CJK text
+pub fn search_terms(question: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in question.to_lowercase().split(|c: char| !c.is_alphanumeric()) {
+        if word.is_ascii() {
+            if word.len() >= 3 && !STOPWORDS.contains(&word) && !word.chars().all(|c| c.is_ascii_digit()) {
+                terms.push(word.to_string());
+            }
+        } else {
+            let chars: Vec<char> = word.chars().collect();
+            terms.extend(chars.windows(2).map(|pair| pair.iter().collect::<String>()));
+        }
+    }
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// The days a question asks about, inclusive: explicit dates, a weekday, "last N days",
+/// or today, yesterday, this or last week or month, in English, Chinese or Japanese
+pub fn time_range(question: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    static PATTERNS: OnceLock<[Regex; 5]> = OnceLock::new();
+    let [date, days, last_weekday, weekday, ja_weekday] = PATTERNS.get_or_init(|| [
+        Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap(),
+        Regex::new(r"(?:last|past)\s+(\d+)\s+days|(?:最近|過去|过去)\s*(\d+)\s*(?:天|日)").unwrap(),
+        Regex::new(r"last\s+(monday|tuesday|wednesday|thursday|friday|saturday|sunday)|上(?:個|个)?(?:星期|禮拜|礼拜|週|周)([一二三四五六日天])|先週の?([月火水木金土日])曜").unwrap(),
+        Regex::new(r"\b(?:on\s+)?(monday|tuesday|wednesday|thursday|friday|saturday|sunday)\b|(?:星期|禮拜|礼拜|週|周)([一二三四五六日天])").unwrap(),
+        Regex::new(r"([月火水木金土日])曜").unwrap(),
+    ]);
+    let q = question.to_lowercase();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+
+    let mut dates: Vec<NaiveDate> = date.find_iter(&q)
+        .filter_map(|m| NaiveDate::parse_from_str(m.as_str(), "%Y-%m-%d").ok())
+        .collect();
+    if !dates.is_empty() {
+        dates.sort();
+        return Some((dates[0], dates[dates.len() - 1]));
+    }
+
+    if let Some(caps) = days.captures(&q) {
+        let n: i64 = caps.iter().skip(1).flatten().next()?.as_str().parse().ok()?;
+        return Some((today - Duration::days(n.clamp(1, 366) - 1), today));
+    }
+
+    // A weekday of last week, then the latest such day up to today
+    if let Some(index) = last_weekday.captures(&q).and_then(|c| weekday_index(&c)) {
+        let day = monday - Duration::days(7 - index);
+        return Some((day, day));
+    }
+    if let Some(index) = weekday.captures(&q).and_then(|c| weekday_index(&c)).or_else(|| ja_weekday.captures(&q).and_then(|c| weekday_index(&c))) {
+        let mut day = monday + Duration::days(index);
+        if day > today {
+            day -= Duration::days(7);
+        }
+        return Some((day, day));
+    }
+
+    let has = |words: &[&str]| words.iter().any(|w| q.contains(w));
+    let first_of_month = today.with_day(1)?;
+    if has(&["yesterday", "昨日", "昨天", "尋日", "琴日"]) {
+        let day = today - Duration::days(1);
+        Some((day, day))
+    } else if has(&["today", "今日", "今天"]) {
+        Some((today, today))
+    } else if has(&["last week", "上星期", "上週", "上周", "上個禮拜", "上个礼拜", "上個星期", "上个星期", "先週"]) {
+        Some((monday - Duration::days(7), monday - Duration::days(1)))
+    } else if has(&["this week", "本週", "本周", "這週", "这周", "今週", "呢個禮拜", "呢個星期", "這星期", "這個星期", "本星期"]) {
+        Some((monday, today))
+    } else if has(&["last month", "上個月", "上个月", "上月", "先月"]) {
+        let end = first_of_month - Duration::days(1);
+        Some((end.with_day(1)?, end))
+    } else if has(&["this month", "本月", "今月", "呢個月", "這個月", "这个月"]) {
+        Some((first_of_month, today))
+    } else {
+        None
+    }
+}
+
+/// Days after Monday named by whichever group of a weekday pattern matched
+fn weekday_index(caps: &regex::Captures) -> Option<i64> {
+    let name = caps.iter().skip(1).flatten().next()?.as_str();
+    let index = match name {
+        "monday" | "一" | "月" => 0,
+        "tuesday" | "二" | "火" => 1,
+        "wednesday" | "三" | "水" => 2,
+        "thursday" | "四" | "木" => 3,
+        "friday" | "五" | "金" => 4,
+        "saturday" | "六" | "土" => 5,
+        _ => 6,
+    };
+    Some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn range(from: &str, to: &str) -> Option<(NaiveDate, NaiveDate)> {
+        Some((d(from), d(to)))
+    }
+
+    fn candidate(kind: SourceKind, id: &str, date: &str, text: &str) -> Candidate {
+        Candidate {
+            kind,
+            id: id.to_string(),
+            date: date.to_string(),
+            project_id: Some("p1".to_string()),
+            text: text.to_string(),
+        }
+    }
+
+    fn project(id: &str, name: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: name.to_string(),
+            path: String::new(),
+            status: ProjectStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_time_range() {
+        // A Thursday
+        let today = d("2024-01-18");
+        assert_eq!(time_range("What did I do on the API last Tuesday?", today), range("2024-01-09", "2024-01-09"));
+        assert_eq!(time_range("What did I do on Tuesday?", today), range("2024-01-16", "2024-01-16"));
+        assert_eq!(time_range("上星期二做咗乜？", today), range("2024-01-09", "2024-01-09"));
+        assert_eq!(time_range("先週の火曜日は何をした？", today), range("2024-01-09", "2024-01-09"));
+        assert_eq!(time_range("金曜日の作業", today), range("2024-01-12", "2024-01-12"));
+        assert_eq!(time_range("yesterday", today), range("2024-01-17", "2024-01-17"));
+        assert_eq!(time_range("今日做咗啲乜", today), range("2024-01-18", "2024-01-18"));
+        assert_eq!(time_range("summarise last week", today), range("2024-01-08", "2024-01-14"));
+        assert_eq!(time_range("本週進度", today), range("2024-01-15", "2024-01-18"));
+        assert_eq!(time_range("上個月", today), range("2023-12-01", "2023-12-31"));
+        assert_eq!(time_range("last 7 days", today), range("2024-01-12", "2024-01-18"));
+        assert_eq!(time_range("between 2024-01-03 and 2024-01-01", today), range("2024-01-01", "2024-01-03"));
+        assert_eq!(time_range("how is the login bug going", today), None);
+    }
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(search_terms("What did I do on the login bug?"), vec!["bug", "login"]);
+        assert_eq!(search_terms("登入問題"), vec!["入問", "問題", "登入"]);
+    }
+
+    #[test]
+    fn test_mentioned_project() {
+        let projects = vec![project("1", "API"), project("2", "Billing API")];
+        assert_eq!(mentioned_project("How's the billing api going?", &projects).unwrap().id, "2");
+        assert_eq!(mentioned_project("what did I do on the API", &projects).unwrap().id, "1");
+        assert!(mentioned_project("anything new?", &projects).is_none());
+    }
+
+    #[test]
+    fn test_select_and_cite() {
+        let candidates = || vec![
+            candidate(SourceKind::Log, "l1", "2024-01-09", "Fixed login redirect"),
+            candidate(SourceKind::Commit, "abc1234", "2024-01-09", "chore: bump deps"),
+            candidate(SourceKind::Todo, "t1", "2024-01-02", "Login rate limit (pending)"),
+            candidate(SourceKind::Log, "l2", "2024-01-16", "Webhooks"),
+        ];
+
+        // A time range keeps everything dated in it
+        let sources = select(candidates(), &[], Some(("2024-01-09", "2024-01-09")));
+        let ids: Vec<&str> = sources.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["l1", "abc1234"]);
+        assert_eq!(sources[0].label, "S1");
+
+        // Without one only matching records count, labelled oldest first
+        let sources = select(candidates(), &["login".to_string()], None);
+        let labelled: Vec<(&str, &str)> = sources.iter().map(|s| (s.label.as_str(), s.id.as_str())).collect();
+        assert_eq!(labelled, vec![("S1", "t1"), ("S2", "l1")]);
+
+        let answer = "You fixed the redirect [S2], and rate limiting is still open [S1，S2]. See [S9].";
+        let cited_ids: Vec<String> = cited(answer, &sources).into_iter().map(|c| c.id).collect();
+        assert_eq!(cited_ids, vec!["l1", "t1"]);
+
+        // Close semantic matches join them, relabelled by date
+        let matches = vec![
+            SemanticMatch { kind: SourceKind::Log, id: "l1".to_string(), date: "2024-01-09".to_string(), project_id: None, text: String::new(), score: 0.9 },
+            SemanticMatch { kind: SourceKind::Commit, id: "def5678".to_string(), date: "2024-01-05".to_string(), project_id: None, text: "auth: rotate tokens".to_string(), score: 0.8 },
+            SemanticMatch { kind: SourceKind::Todo, id: "t9".to_string(), date: "2024-01-01".to_string(), project_id: None, text: "Unrelated".to_string(), score: 0.1 },
+        ];
+        let merged = add_matches(sources.clone(), matches);
+        let labelled: Vec<(&str, &str)> = merged.iter().map(|s| (s.label.as_str(), s.id.as_str())).collect();
+        assert_eq!(labelled, vec![("S1", "t1"), ("S2", "def5678"), ("S3", "l1")]);
+
+        let projects = vec![project("p1", "API")];
+        assert_eq!(format_sources(&sources[..1], &projects), "[S1] 2024-01-02 todo (API): Login rate limit (pending)");
+    }
+}
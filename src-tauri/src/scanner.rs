@@ -1,7 +1,7 @@
 use std::path::Path;
 use std::process::Command;
 use regex::Regex;
-use crate::models::{FileChange, GitDiffResult, GitTag};
+use crate::models::{CommitInfo, FileChange, GitDiffResult, GitTag};
 
 pub struct GitScanner;
 
@@ -201,6 +201,191 @@ impl GitScanner {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
+    /// Get the local date of the most recent commit, if the repository has any
+    pub fn get_last_commit_date(&self, repo_path: &Path) -> Result<Option<chrono::NaiveDate>, String> {
+        let safe_path = self.validate_path(repo_path)?;
+
+        if !self.is_git_repo(&safe_path) {
+            return Err("Not a git repository".to_string());
+        }
+
+        let output = Command::new("git")
+            .args(["log", "-1", "--pretty=format:%cI"])
+            .current_dir(&safe_path)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+
+        // An empty repository has no HEAD, which git reports as a failure
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(chrono::DateTime::parse_from_rfc3339(stdout.trim())
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Local).date_naive()))
+    }
+
+    /// Get commit subjects within a date range, newest first
+    pub fn get_commit_subjects(
+        &self,
+        repo_path: &Path,
+        since: &str,
+        until: Option<&str>,
+    ) -> Result<Vec<String>, String> {
+        let safe_path = self.validate_path(repo_path)?;
+
+        if !self.is_git_repo(&safe_path) {
+            return Err("Not a git repository".to_string());
+        }
+
+        self.validate_date_param(since)?;
+        if let Some(u) = until {
+            self.validate_date_param(u)?;
+        }
+
+        let mut args = vec![
+            "log".to_string(),
+            format!("--since={}", since),
+            "--pretty=format:%s".to_string(),
+            "--no-merges".to_string(),
+        ];
+
+        if let Some(u) = until {
+            args.push(format!("--until={}", u));
+        }
+
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(&safe_path)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+
+        if !output.status.success() {
+            return Err("Git command failed".to_string());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect())
+    }
+
+    /// Get author timestamps of commits since the given date
+    pub fn get_commit_times(
+        &self,
+        repo_path: &Path,
+        since: &str,
+    ) -> Result<Vec<chrono::DateTime<chrono::FixedOffset>>, String> {
+        let safe_path = self.validate_path(repo_path)?;
+
+        if !self.is_git_repo(&safe_path) {
+            return Err("Not a git repository".to_string());
+        }
+
+        self.validate_date_param(since)?;
+
+        let output = Command::new("git")
+            .args([
+                "log",
+                &format!("--since={}", since),
+                "--pretty=format:%aI",
+                "--no-merges",
+            ])
+            .current_dir(&safe_path)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+
+        if !output.status.success() {
+            return Err("Git command failed".to_string());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .lines()
+            .filter_map(|line| chrono::DateTime::parse_from_rfc3339(line.trim()).ok())
+            .collect())
+    }
+
+    /// Get the distinct local days with at least one commit in a date range, oldest first
+    pub fn get_active_days(
+        &self,
+        repo_path: &Path,
+        since: &str,
+        until: &str,
+    ) -> Result<Vec<chrono::NaiveDate>, String> {
+        let safe_path = self.validate_path(repo_path)?;
+
+        if !self.is_git_repo(&safe_path) {
+            return Err("Not a git repository".to_string());
+        }
+
+        self.validate_date_param(since)?;
+        self.validate_date_param(until)?;
+
+        // Committer dates, to agree with how --since/--until pick commits
+        let output = Command::new("git")
+            .args([
+                "log",
+                &format!("--since={}", since),
+                &format!("--until={}", until),
+                "--pretty=format:%cI",
+                "--no-merges",
+            ])
+            .current_dir(&safe_path)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+
+        if !output.status.success() {
+            return Err("Git command failed".to_string());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let days: std::collections::BTreeSet<chrono::NaiveDate> = stdout
+            .lines()
+            .filter_map(|line| chrono::DateTime::parse_from_rfc3339(line.trim()).ok())
+            .map(|dt| dt.with_timezone(&chrono::Local).date_naive())
+            .collect();
+
+        Ok(days.into_iter().collect())
+    }
+
+    /// Get the commits of the days `since` to `until` inclusive, newest first
+    pub fn get_commits(&self, repo_path: &Path, since: &str, until: &str) -> Result<Vec<CommitInfo>, String> {
+        let safe_path = self.validate_path(repo_path)?;
+
+        if !self.is_git_repo(&safe_path) {
+            return Err("Not a git repository".to_string());
+        }
+
+        self.validate_date_param(since)?;
+        self.validate_date_param(until)?;
+
+        // A bare date as --until means that day at the current time, so stop at the next midnight
+        let until_date = chrono::NaiveDate::parse_from_str(until, "%Y-%m-%d").map_err(|e| e.to_string())?;
+        let before = format!("{} 00:00:00", until_date + chrono::Duration::days(1));
+
+        let output = Command::new("git")
+            .args([
+                "log",
+                &format!("--since={}", since),
+                &format!("--until={}", before),
+                "--pretty=format:%h%x09%cs%x09%s",
+                "--no-merges",
+            ])
+            .current_dir(&safe_path)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+
+        if !output.status.success() {
+            return Err("Git command failed".to_string());
+        }
+
+        Ok(self.parse_commit_log(&String::from_utf8_lossy(&output.stdout)))
+    }
+
     /// Get recent commit subjects (for context)
     pub fn get_recent_commits(&self, repo_path: &Path, count: usize) -> Result<Vec<String>, String> {
         let safe_path = self.validate_path(repo_path)?;
@@ -344,6 +529,24 @@ impl GitScanner {
             .collect()
     }
 
+    /// Parse `hash<TAB>date<TAB>subject` lines from git log
+    fn parse_commit_log(&self, output: &str) -> Vec<CommitInfo> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let hash = parts.next()?.trim();
+                let date = parts.next()?.trim();
+                let subject = parts.next()?.trim();
+                (!hash.is_empty()).then(|| CommitInfo {
+                    hash: hash.to_string(),
+                    date: date.to_string(),
+                    subject: subject.to_string(),
+                })
+            })
+            .collect()
+    }
+
     /// Format file changes for display
     pub fn format_changes_for_display(&self, files: &[FileChange]) -> String {
         files
@@ -407,6 +610,19 @@ mod tests {
         assert_eq!(files[0].path, "path with spaces.rs");
     }
 
+    #[test]
+    fn test_parse_commit_log() {
+        let scanner = GitScanner::new();
+        let output = "a1b2c3d\t2024-01-16\tfix: login redirect\tloop\n\nbadline\ne4f5a6b\t2024-01-15\tfeat: webhooks";
+        let commits = scanner.parse_commit_log(output);
+
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "a1b2c3d");
+        assert_eq!(commits[0].date, "2024-01-16");
+        assert_eq!(commits[0].subject, "fix: login redirect\tloop");
+        assert_eq!(commits[1].subject, "feat: webhooks");
+    }
+
     #[test]
     fn test_validate_date_param_valid() {
         let scanner = GitScanner::new();
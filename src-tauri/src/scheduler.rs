@@ -1,18 +1,188 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{interval, Duration};
+use chrono::{NaiveDateTime, TimeZone};
+use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager};
-use crate::ai_agent::AiAgent;
+use crate::ai_agent::{AiAgent, MAX_CORRECTION_EXAMPLES};
+use crate::ai_cache::ResponseCache;
+use crate::anomaly;
+use crate::classifier;
+use crate::cron::Schedule;
+use crate::embeddings::{self, EmbeddingsKey};
+use crate::patterns;
 use crate::db::Database;
 use crate::grok::GrokClient;
+use crate::i18n::Locale;
+use crate::inbox;
+use crate::journal;
+use crate::notification::NotificationService;
 use crate::scanner::GitScanner;
-use crate::models::{ScanSettings, UserSettings, LogCategory, DailyLog, Milestone, CachedGitTag};
+use crate::usage;
+use crate::weekly_review;
+use crate::models::{
+    ActionData, ActionTrigger, AnomalySettings, CachedGitTag, ConversationIntent, DailyLog, DetectedAction,
+    GitDiffResult, InboxItem, InboxItemType, InsightSettings, Milestone, Project,
+    SuggestedAction, UndoOperation, UserSettings,
+};
+
+/// How often the scheduler wakes up to check for due jobs
+const TICK_SECONDS: u64 = 60;
+
+/// A gap between ticks this many times longer than expected means the machine slept
+const WAKE_GAP_TICKS: i64 = 3;
+
+/// Most days a catch-up will backfill; longer gaps are left to a history backfill
+const MAX_CATCH_UP_DAYS: i64 = 14;
+
+/// Named jobs the scheduler runs, each on its own schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Job {
+    Scan,
+    TagSync,
+    DailySummary,
+    WeeklyReview,
+    StaleCheck,
+    Insights,
+    Backup,
+    Embeddings,
+}
+
+impl Job {
+    pub const ALL: [Job; 8] = [
+        Job::Scan,
+        Job::TagSync,
+        Job::DailySummary,
+        Job::WeeklyReview,
+        Job::StaleCheck,
+        Job::Insights,
+        Job::Backup,
+        Job::Embeddings,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Job::Scan => "scan",
+            Job::TagSync => "tag_sync",
+            Job::DailySummary => "daily_summary",
+            Job::WeeklyReview => "weekly_review",
+            Job::StaleCheck => "stale_check",
+            Job::Insights => "insights",
+            Job::Backup => "backup",
+            Job::Embeddings => "embeddings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Job> {
+        Job::ALL.into_iter().find(|job| job.name() == name)
+    }
+
+    /// Whether the feature behind the job is switched on
+    fn is_enabled(&self, settings: &UserSettings) -> bool {
+        match self {
+            Job::Scan => settings.scan.enabled,
+            Job::TagSync => settings.version.auto_refresh,
+            Job::DailySummary => settings.notifications.daily_summary,
+            Job::WeeklyReview => settings.review.weekly_review,
+            Job::StaleCheck => settings.notifications.stale_project,
+            Job::Insights => settings.insights.enabled,
+            Job::Backup => settings.scheduler.backup_keep > 0,
+            Job::Embeddings => settings.embeddings.enabled,
+        }
+    }
+
+    /// The job's schedule: an override from settings, else its default
+    fn schedule_spec(&self, settings: &UserSettings) -> String {
+        if let Some(spec) = settings.scheduler.jobs.get(self.name()) {
+            return spec.clone();
+        }
+
+        match self {
+            Job::Scan => format!("@every {}m", settings.scan.interval_minutes.max(1)),
+            Job::TagSync => format!("@every {}m", settings.version.refresh_minutes.max(1)),
+            Job::DailySummary => "18:00".to_string(),
+            Job::WeeklyReview => "fri 17:00".to_string(),
+            Job::StaleCheck => "09:00".to_string(),
+            Job::Insights => "mon 09:00".to_string(),
+            Job::Backup => "03:00".to_string(),
+            Job::Embeddings => "@every 60m".to_string(),
+        }
+    }
+
+    fn last_run_key(&self) -> String {
+        format!("job_last_run:{}", self.name())
+    }
+}
+
+/// Per-job state reported by `get_scheduler_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub enabled: bool,
+    pub schedule: String,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>,
+    pub running: bool,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct JobRuntime {
+    running: bool,
+    last_error: Option<String>,
+}
+
+/// When a job is next due. Interval jobs that have never run are due straight away;
+/// cron jobs that have never run wait for their first slot after the scheduler started.
+/// A slot missed while the app was closed makes the job due immediately.
+fn next_run(schedule: &Schedule, last_run: Option<NaiveDateTime>, started_at: NaiveDateTime) -> Option<NaiveDateTime> {
+    match (schedule, last_run) {
+        (_, Some(last)) => schedule.next_after(last),
+        (Schedule::Every(_), None) => Some(started_at),
+        (Schedule::Cron(_), None) => schedule.next_after(started_at),
+    }
+}
 
-/// Scheduler state for managing periodic scans
+/// Settings key holding the last time a project was successfully scanned
+fn scan_cursor_key(project_id: &str) -> String {
+    format!("scan_cursor:{}", project_id)
+}
+
+/// Whether a diff covers more than the log already written for its day
+fn diff_has_grown(existing: Option<&DailyLog>, diff: &GitDiffResult) -> bool {
+    match existing {
+        None => true,
+        Some(log) => {
+            (diff.total_additions + diff.total_deletions) as i64 > log.lines_changed()
+                || diff.files.len() > log.files_changed.len()
+        }
+    }
+}
+
+fn to_local_rfc3339(time: NaiveDateTime) -> Option<String> {
+    chrono::Local.from_local_datetime(&time).earliest().map(|t| t.to_rfc3339())
+}
+
+/// Load user settings, falling back to defaults
+pub fn load_settings(db: &Database) -> UserSettings {
+    db.get_setting("user_settings")
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Scheduler state for managing periodic jobs
 #[derive(Debug, Clone)]
 pub struct Scheduler {
     is_running: Arc<Mutex<bool>>,
     last_scan: Arc<Mutex<Option<chrono::DateTime<chrono::Utc>>>>,
+    started_at: Arc<Mutex<Option<NaiveDateTime>>>,
+    jobs: Arc<Mutex<HashMap<Job, JobRuntime>>>,
+    /// Set while a manual scan asks for fresh AI analysis instead of cached replies
+    force_refresh: Arc<AtomicBool>,
 }
 
 impl Scheduler {
@@ -20,16 +190,15 @@ impl Scheduler {
         Scheduler {
             is_running: Arc::new(Mutex::new(false)),
             last_scan: Arc::new(Mutex::new(None)),
+            started_at: Arc::new(Mutex::new(None)),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            force_refresh: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    /// Start the scheduler with the given settings
-    pub async fn start(&self, app_handle: AppHandle, settings: ScanSettings) {
-        if !settings.enabled {
-            log::info!("Scheduler disabled in settings");
-            return;
-        }
-
+    /// Start the scheduler loop. Settings are re-read on every tick, so schedule
+    /// changes apply without a restart.
+    pub async fn start(&self, app_handle: AppHandle) {
         let mut running = self.is_running.lock().await;
         if *running {
             log::warn!("Scheduler already running");
@@ -38,36 +207,34 @@ impl Scheduler {
         *running = true;
         drop(running);
 
-        log::info!("Starting scheduler with interval: {} minutes", settings.interval_minutes);
+        *self.started_at.lock().await = Some(chrono::Local::now().naive_local());
+        log::info!("Starting scheduler");
 
-        let is_running = self.is_running.clone();
-        let last_scan = self.last_scan.clone();
-        let interval_mins = settings.interval_minutes;
+        let scheduler = self.clone();
 
         tokio::spawn(async move {
-            let mut ticker = interval(Duration::from_secs((interval_mins as u64) * 60));
+            let mut ticker = interval(Duration::from_secs(TICK_SECONDS));
+            let mut last_tick = chrono::Local::now();
 
             loop {
                 ticker.tick().await;
 
-                let running = is_running.lock().await;
+                let running = scheduler.is_running.lock().await;
                 if !*running {
                     log::info!("Scheduler stopped");
                     break;
                 }
                 drop(running);
 
-                log::info!("Running scheduled scan...");
-
-                // Create a temporary scheduler to call the method
-                let temp_scheduler = Scheduler {
-                    is_running: is_running.clone(),
-                    last_scan: last_scan.clone(),
-                };
-
-                if let Err(e) = temp_scheduler.run_scheduled_scan(&app_handle).await {
-                    log::error!("Scheduled scan failed: {}", e);
+                // Wall-clock time keeps moving while the machine sleeps, the ticker doesn't
+                let now = chrono::Local::now();
+                if (now - last_tick).num_seconds() > TICK_SECONDS as i64 * WAKE_GAP_TICKS {
+                    log::info!("Wake from sleep detected, catching up missed days");
+                    scheduler.catch_up_missed_days(&app_handle).await;
                 }
+                last_tick = now;
+
+                scheduler.run_due_jobs(&app_handle).await;
             }
         });
     }
@@ -89,6 +256,131 @@ impl Scheduler {
         *self.last_scan.lock().await
     }
 
+    fn job_last_run(db: &Database, job: Job) -> Option<chrono::DateTime<chrono::Utc>> {
+        db.get_setting(&job.last_run_key())
+            .ok()
+            .flatten()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+    }
+
+    /// Run every enabled job whose next run has arrived
+    async fn run_due_jobs(&self, app_handle: &AppHandle) {
+        let db = app_handle.state::<Database>();
+        let settings = load_settings(&db);
+        let now = chrono::Local::now().naive_local();
+        let started_at = self.started_at.lock().await.unwrap_or(now);
+
+        for job in Job::ALL {
+            if !job.is_enabled(&settings) {
+                continue;
+            }
+
+            let schedule = match Schedule::parse(&job.schedule_spec(&settings)) {
+                Ok(schedule) => schedule,
+                Err(e) => {
+                    self.jobs.lock().await.entry(job).or_default().last_error = Some(e);
+                    continue;
+                }
+            };
+
+            let last_run = Self::job_last_run(&db, job).map(|t| t.with_timezone(&chrono::Local).naive_local());
+            let due = matches!(next_run(&schedule, last_run, started_at), Some(next) if next <= now);
+            if !due {
+                continue;
+            }
+
+            if let Err(e) = self.run_job(app_handle, job).await {
+                log::error!("Job {} failed: {}", job.name(), e);
+            }
+        }
+    }
+
+    /// Run a job now, recording its last run and outcome
+    pub async fn run_job(&self, app_handle: &AppHandle, job: Job) -> Result<(), String> {
+        {
+            let mut jobs = self.jobs.lock().await;
+            let state = jobs.entry(job).or_default();
+            if state.running {
+                return Err(format!("Job {} is already running", job.name()));
+            }
+            state.running = true;
+        }
+
+        log::info!("Running job {}", job.name());
+
+        let result = match job {
+            Job::Scan => self.run_scheduled_scan(app_handle).await,
+            Job::TagSync => self.run_tag_sync(app_handle).await,
+            Job::DailySummary => self.run_daily_summary(app_handle),
+            Job::WeeklyReview => self.run_weekly_review(app_handle),
+            Job::StaleCheck => self.run_stale_check(app_handle),
+            Job::Insights => self.run_pattern_insights(app_handle),
+            Job::Backup => self.run_backup(app_handle),
+            Job::Embeddings => self.run_embedding_index(app_handle).await,
+        };
+
+        // Record the attempt even on failure, so a broken job waits for its next slot
+        let db = app_handle.state::<Database>();
+        if let Err(e) = db.set_setting(&job.last_run_key(), &chrono::Utc::now().to_rfc3339()) {
+            log::warn!("Failed to record last run for {}: {}", job.name(), e);
+        }
+
+        let mut jobs = self.jobs.lock().await;
+        let state = jobs.entry(job).or_default();
+        state.running = false;
+        state.last_error = result.as_ref().err().cloned();
+
+        result
+    }
+
+    /// Run a scan whose AI analysis skips the response cache and re-analyzes today's
+    /// work even if it hasn't grown
+    pub async fn run_forced_scan(&self, app_handle: &AppHandle) -> Result<(), String> {
+        self.force_refresh.store(true, Ordering::Relaxed);
+        let result = self.run_job(app_handle, Job::Scan).await;
+        self.force_refresh.store(false, Ordering::Relaxed);
+        result
+    }
+
+    /// Schedule, last run and next run of every job
+    pub async fn job_statuses(&self, db: &Database) -> Vec<JobStatus> {
+        let settings = load_settings(db);
+        let now = chrono::Local::now().naive_local();
+        let started_at = self.started_at.lock().await.unwrap_or(now);
+        let jobs = self.jobs.lock().await;
+
+        Job::ALL
+            .into_iter()
+            .map(|job| {
+                let spec = job.schedule_spec(&settings);
+                let enabled = job.is_enabled(&settings);
+                let last_run = Self::job_last_run(db, job);
+                let runtime = jobs.get(&job).cloned().unwrap_or_default();
+
+                let (next, error) = match Schedule::parse(&spec) {
+                    Ok(schedule) => {
+                        let last_local = last_run.map(|t| t.with_timezone(&chrono::Local).naive_local());
+                        // Overdue jobs run on the next tick
+                        let next = next_run(&schedule, last_local, started_at).map(|n| n.max(now));
+                        (next, runtime.last_error)
+                    }
+                    Err(e) => (None, Some(e)),
+                };
+
+                JobStatus {
+                    name: job.name().to_string(),
+                    enabled,
+                    schedule: spec,
+                    last_run: last_run.map(|t| t.to_rfc3339()),
+                    next_run: if enabled { next.and_then(to_local_rfc3339) } else { None },
+                    running: runtime.running,
+                    last_error: error,
+                }
+            })
+            .collect()
+    }
+
     /// Run a scan on app startup
     pub async fn run_startup_scan(&self, app_handle: &AppHandle) -> Result<(), String> {
         log::info!("Running startup scan...");
@@ -110,6 +402,9 @@ impl Scheduler {
 
         log::info!("Found {} active projects to scan", active_projects.len());
 
+        // Backfill days missed while the app was closed
+        self.catch_up_missed_days(app_handle).await;
+
         let mut scan_results = Vec::new();
 
         for project in active_projects {
@@ -246,7 +541,663 @@ impl Scheduler {
         Ok((new_tags_count, milestones_created))
     }
 
-    /// Run a full scheduled scan with AI analysis
+    /// Run the anomaly rules for each project and raise an inbox item per new finding.
+    /// A finding is raised once per project, kind and window.
+    #[allow(clippy::too_many_arguments)]
+    async fn check_anomalies(
+        &self,
+        db: &Database,
+        scanner: &GitScanner,
+        grok: &GrokClient,
+        ai_agent: &AiAgent,
+        projects: &[&Project],
+        today_diffs: &HashMap<String, GitDiffResult>,
+        settings: &AnomalySettings,
+    ) -> usize {
+        let today = chrono::Local::now().date_naive();
+        let history_days = (settings.baseline_weeks as i32 + 1) * 7;
+        let user_settings = load_settings(db);
+        let locale = Locale::new(&user_settings.language);
+        let over_budget = usage::over_budget(db, &user_settings.ai);
+        let mut items_created = 0;
+
+        for project in projects {
+            let logs = match db.get_daily_logs(Some(&project.id), history_days) {
+                Ok(logs) => logs,
+                Err(e) => {
+                    log::error!("Failed to load logs for anomaly check on {}: {}", project.name, e);
+                    continue;
+                }
+            };
+
+            let path = std::path::Path::new(&project.path);
+            let commit_times = if scanner.is_git_repo(path) {
+                scanner.get_commit_times(path, "yesterday").unwrap_or_else(|e| {
+                    log::warn!("Failed to read commit times for {}: {}", project.name, e);
+                    Vec::new()
+                })
+            } else {
+                Vec::new()
+            };
+
+            let input = anomaly::AnomalyInput {
+                today,
+                logs: &logs,
+                today_diff: today_diffs.get(&project.id),
+                commit_times: &commit_times,
+                locale,
+            };
+
+            for found in anomaly::detect(&input, settings) {
+                let marker_key = format!("anomaly:{}:{}", project.id, found.kind_str());
+                if db.get_setting(&marker_key).ok().flatten().as_deref() == Some(found.window.as_str()) {
+                    continue;
+                }
+
+                let phrasing = if settings.use_ai_phrasing && !over_budget {
+                    match ai_agent.phrase_anomaly(grok, project, &found, locale).await {
+                        Ok((text, version)) if !text.is_empty() => Some((text, version)),
+                        Ok(_) => None,
+                        Err(e) => {
+                            log::warn!("AI phrasing failed, using rule description: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                let mut item = ai_agent.create_anomaly_inbox(locale, project, &found, phrasing.as_ref().map(|(text, _)| text.as_str()));
+                item.prompt_version = phrasing.map(|(_, version)| version);
+                if let Err(e) = db.create_inbox_item(&item) {
+                    log::error!("Failed to create anomaly inbox item: {}", e);
+                    continue;
+                }
+
+                if let Err(e) = db.set_setting(&marker_key, &found.window) {
+                    log::warn!("Failed to record anomaly marker for {}: {}", project.name, e);
+                }
+
+                items_created += 1;
+                log::info!("Anomaly {} detected for {}", found.kind_str(), project.name);
+            }
+        }
+
+        items_created
+    }
+
+    /// Raise a stale-project inbox item for each active project idle longer than
+    /// `threshold_days`. The last activity date seen when raising is remembered, so
+    /// the same idle stretch is only reported once.
+    fn check_stale_projects(
+        &self,
+        db: &Database,
+        scanner: &GitScanner,
+        projects: &[&Project],
+        threshold_days: u32,
+    ) -> usize {
+        let today = chrono::Local::now().date_naive();
+        let settings = load_settings(db);
+        let locale = Locale::new(&settings.language);
+        let mut items_created = 0;
+
+        for project in projects {
+            let path = std::path::Path::new(&project.path);
+
+            let last_commit = if scanner.is_git_repo(path) {
+                scanner.get_last_commit_date(path).unwrap_or_else(|e| {
+                    log::warn!("Failed to read last commit for {}: {}", project.name, e);
+                    None
+                })
+            } else {
+                None
+            };
+
+            let last_log = db.get_last_log_date(&project.id)
+                .ok()
+                .flatten()
+                .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+            let created = project.created_at.with_timezone(&chrono::Local).date_naive();
+            let last_activity = [last_commit, last_log]
+                .into_iter()
+                .flatten()
+                .max()
+                .unwrap_or(created);
+
+            let idle_days = (today - last_activity).num_days();
+            if idle_days < threshold_days as i64 {
+                continue;
+            }
+
+            // Suppress repeats until the project sees new activity
+            let marker_key = format!("stale_project:{}", project.id);
+            let last_activity_str = last_activity.format("%Y-%m-%d").to_string();
+            if db.get_setting(&marker_key).ok().flatten().as_deref() == Some(last_activity_str.as_str()) {
+                continue;
+            }
+
+            // The resume todo is a suggestion, so it waits for the user even in Auto mode
+            let item = Self::create_stale_project_inbox(locale, project, idle_days, last_commit, last_log);
+            if let Err(e) = db.create_inbox_item(&item) {
+                log::error!("Failed to create stale project inbox item: {}", e);
+                continue;
+            }
+
+            if let Err(e) = db.set_setting(&marker_key, &last_activity_str) {
+                log::warn!("Failed to record stale marker for {}: {}", project.name, e);
+            }
+
+            items_created += 1;
+            log::info!("Project {} is stale ({} idle days)", project.name, idle_days);
+        }
+
+        items_created
+    }
+
+    /// Build the stale-project inbox item with pause / archive / resume suggestions
+    fn create_stale_project_inbox(
+        locale: Locale,
+        project: &Project,
+        idle_days: i64,
+        last_commit: Option<chrono::NaiveDate>,
+        last_log: Option<chrono::NaiveDate>,
+    ) -> InboxItem {
+        let format_date = |d: Option<chrono::NaiveDate>| {
+            d.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| locale.t("inbox.stale.never", &[]))
+        };
+
+        let mut item = InboxItem::new(
+            InboxItemType::StaleProject,
+            locale.t("inbox.stale.question", &[("project", &project.name), ("days", &idle_days.to_string())]),
+            Some(project.id.clone()),
+        );
+
+        item.context = Some(locale.t("inbox.stale.context", &[
+            ("commit", &format_date(last_commit)),
+            ("log", &format_date(last_log)),
+        ]));
+
+        item.suggested_actions = vec![
+            SuggestedAction {
+                id: "pause_project".to_string(),
+                label: locale.t("action.pause_project", &[]),
+                icon: Some("pause".to_string()),
+            },
+            SuggestedAction {
+                id: "archive_project".to_string(),
+                label: locale.t("action.archive_project", &[]),
+                icon: Some("archive".to_string()),
+            },
+            SuggestedAction {
+                id: "create_resume_todo".to_string(),
+                label: locale.t("action.create_resume_todo", &[]),
+                icon: Some("check-circle".to_string()),
+            },
+            SuggestedAction {
+                id: "skip".to_string(),
+                label: locale.t("action.skip", &[]),
+                icon: Some("x".to_string()),
+            },
+        ];
+
+        item.detected_actions = vec![DetectedAction {
+            intent: ConversationIntent::CreateTodo,
+            confidence: 1.0,
+            data: ActionData::Todo {
+                title: locale.t("inbox.stale.resume_todo", &[("project", &project.name)]),
+                priority: Some("medium".to_string()),
+                due_date: None,
+            },
+            confirmed: false,
+        }];
+
+        item
+    }
+
+    /// Mine the stored history for patterns, posting insights that haven't been
+    /// posted within `repeat_days`
+    fn check_pattern_insights(
+        &self,
+        db: &Database,
+        scanner: &GitScanner,
+        projects: &[Project],
+        settings: &InsightSettings,
+    ) -> usize {
+        let today = chrono::Local::now().date_naive();
+        let days_since = |key: &str| {
+            db.get_setting(key)
+                .ok()
+                .flatten()
+                .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+                .map(|d| (today - d).num_days())
+        };
+
+        let since = (today - chrono::Duration::days(settings.lookback_days as i64))
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let logs = match db.get_daily_logs_since(&since) {
+            Ok(logs) => logs,
+            Err(e) => {
+                log::error!("Failed to load logs for pattern mining: {}", e);
+                return 0;
+            }
+        };
+        let milestones = db.get_milestones(None).unwrap_or_else(|e| {
+            log::warn!("Failed to load milestones for pattern mining: {}", e);
+            Vec::new()
+        });
+
+        let mut commit_times = Vec::new();
+        for project in projects {
+            let path = std::path::Path::new(&project.path);
+            if !scanner.is_git_repo(path) {
+                continue;
+            }
+            match scanner.get_commit_times(path, &since) {
+                Ok(times) => commit_times.extend(times),
+                Err(e) => log::warn!("Failed to read commit times for {}: {}", project.name, e),
+            }
+        }
+
+        let input = patterns::PatternInput {
+            today,
+            projects,
+            logs: &logs,
+            milestones: &milestones,
+            commit_times: &commit_times,
+            locale: Locale::new(&load_settings(db).language),
+        };
+
+        let today_str = today.format("%Y-%m-%d").to_string();
+        let mut items_created = 0;
+
+        for insight in patterns::mine(&input, settings) {
+            let marker_key = format!("pattern_insight:{}", insight.key);
+            if days_since(&marker_key).is_some_and(|days| days < settings.repeat_days as i64) {
+                continue;
+            }
+
+            if let Err(e) = db.create_inbox_item(&insight.to_inbox_item(input.locale)) {
+                log::error!("Failed to create pattern insight inbox item: {}", e);
+                continue;
+            }
+
+            if let Err(e) = db.set_setting(&marker_key, &today_str) {
+                log::warn!("Failed to record insight marker {}: {}", insight.key, e);
+            }
+
+            items_created += 1;
+            log::info!("Pattern insight posted: {}", insight.key);
+        }
+
+        items_created
+    }
+
+    /// Generate this week's review unless one already exists.
+    /// Returns true if a review was created.
+    fn check_weekly_review(&self, db: &Database) -> bool {
+        let today = chrono::Local::now().date_naive();
+        let monday = crate::analytics::week_start(today);
+
+        let marker_key = "weekly_review:last_week";
+        let week_str = monday.format("%Y-%m-%d").to_string();
+        if db.get_setting(marker_key).ok().flatten().as_deref() == Some(week_str.as_str()) {
+            return false;
+        }
+
+        if let Err(e) = weekly_review::generate(db, monday, today, Locale::new(&load_settings(db).language)) {
+            log::error!("Failed to generate weekly review: {}", e);
+            return false;
+        }
+
+        if let Err(e) = db.set_setting(marker_key, &week_str) {
+            log::warn!("Failed to record weekly review marker: {}", e);
+        }
+
+        log::info!("Generated weekly review for week of {}", week_str);
+        true
+    }
+
+    /// Backfill one daily log per day for days a project missed while the app was
+    /// closed or asleep. Each project's scan cursor marks how far it has been covered;
+    /// the cursor's own day is rescanned in full, since only its earlier part was seen.
+    /// Returns the number of logs written.
+    pub async fn catch_up_missed_days(&self, app_handle: &AppHandle) -> usize {
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+        let grok = app_handle.state::<GrokClient>();
+        let ai_agent = app_handle.state::<AiAgent>();
+
+        let settings = load_settings(&db);
+        if !settings.scan.auto_summarize {
+            return 0;
+        }
+
+        let projects = match db.get_projects() {
+            Ok(projects) => projects,
+            Err(e) => {
+                log::error!("Failed to get projects for catch-up: {}", e);
+                return 0;
+            }
+        };
+
+        let today = chrono::Local::now().date_naive();
+        let mut logs_written = 0;
+
+        for project in projects.iter().filter(|p| p.status == crate::models::ProjectStatus::Active) {
+            let path = std::path::Path::new(&project.path);
+            if !scanner.is_git_repo(path) {
+                continue;
+            }
+
+            let cursor_key = scan_cursor_key(&project.id);
+            let cursor = db.get_setting(&cursor_key)
+                .ok()
+                .flatten()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&chrono::Local).date_naive());
+
+            // A project never scanned before has nothing to catch up on
+            let Some(cursor) = cursor else {
+                let _ = db.set_setting(&cursor_key, &chrono::Local::now().to_rfc3339());
+                continue;
+            };
+
+            if cursor >= today {
+                continue;
+            }
+
+            let first_day = cursor.max(today - chrono::Duration::days(MAX_CATCH_UP_DAYS));
+            let mut completed = true;
+
+            for day in first_day.iter_days().take_while(|d| *d < today) {
+                let day_str = day.format("%Y-%m-%d").to_string();
+                let since = format!("{} 00:00:00", day_str);
+                let until = format!("{} 23:59:59", day_str);
+
+                let mut diff = match scanner.get_diff_for_range(path, &since, Some(&until)) {
+                    Ok(diff) => diff,
+                    Err(e) => {
+                        log::error!("Catch-up scan of {} on {} failed: {}", project.name, day_str, e);
+                        completed = false;
+                        break;
+                    }
+                };
+
+                if diff.total_additions == 0 && diff.total_deletions == 0 {
+                    continue;
+                }
+
+                diff.project_id = project.id.clone();
+                diff.date = day_str.clone();
+
+                let subjects = scanner.get_commit_subjects(path, &since, Some(&until)).unwrap_or_default();
+                if self.write_day_log(&db, &grok, &ai_agent, project, &diff, &subjects).await {
+                    logs_written += 1;
+                }
+            }
+
+            // Only advance past days that were fully scanned
+            if completed {
+                if let Some(midnight) = today.and_hms_opt(0, 0, 0).and_then(to_local_rfc3339) {
+                    let _ = db.set_setting(&cursor_key, &midnight);
+                }
+            }
+        }
+
+        if logs_written > 0 {
+            log::info!("Catch-up wrote {} daily logs", logs_written);
+            let _ = app_handle.emit("data:daily-log-updated", ());
+        }
+
+        logs_written
+    }
+
+    /// Write the daily log for a past day, using AI analysis when available and the
+    /// offline classifier otherwise. An existing log is only rewritten if the day's
+    /// diff has grown since.
+    async fn write_day_log(
+        &self,
+        db: &Database,
+        grok: &GrokClient,
+        ai_agent: &AiAgent,
+        project: &Project,
+        diff: &GitDiffResult,
+        subjects: &[String],
+    ) -> bool {
+        let existing = db.get_daily_log_for_date(&project.id, &diff.date).ok().flatten();
+        if !diff_has_grown(existing.as_ref(), diff) {
+            return false;
+        }
+
+        let settings = load_settings(db);
+        let (ai_settings, locale) = (settings.ai, Locale::new(&settings.language));
+        let ai_allowed = !usage::over_budget(db, &ai_settings);
+        let analysis = if ai_allowed && grok.has_api_key().await && !grok.is_paused() {
+            let corrections = db.get_corrected_logs(&project.id, MAX_CORRECTION_EXAMPLES).unwrap_or_default();
+            let cache = ResponseCache::new(db, &ai_settings, false);
+            match ai_agent.analyze_daily_work(grok, &cache, project, diff, &corrections).await {
+                Ok(analysis) => Some(analysis),
+                Err(e) => {
+                    log::warn!("AI analysis failed for {} on {}, using offline summary: {}", project.name, diff.date, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let (summary, category, ai_classification, prompt_version) = match analysis {
+            Some(a) => (a.summary, classifier::parse_category(&a.category), Some(a.category), a.prompt_version),
+            None => (
+                classifier::summarize_offline(locale, subjects, diff),
+                classifier::classify_offline(subjects, &diff.files),
+                None,
+                None,
+            ),
+        };
+
+        let log = DailyLog {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: project.id.clone(),
+            date: diff.date.clone(),
+            summary,
+            category,
+            files_changed: diff.files.clone(),
+            ai_classification,
+            user_override: None,
+            created_at: chrono::Utc::now(),
+            prompt_version,
+            summary_by_user: false,
+        };
+
+        match db.upsert_daily_log(&log) {
+            Ok(_) => true,
+            Err(e) => {
+                log::error!("Failed to write catch-up log for {} on {}: {}", project.name, diff.date, e);
+                false
+            }
+        }
+    }
+
+    /// Sync git tags for all active projects
+    async fn run_tag_sync(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+        let settings = load_settings(&db);
+
+        let projects = db.get_projects().map_err(|e| format!("Failed to get projects: {}", e))?;
+        let active_projects: Vec<_> = projects.iter()
+            .filter(|p| p.status == crate::models::ProjectStatus::Active)
+            .collect();
+
+        let mut tags_synced = 0;
+        let mut milestones_created = 0;
+
+        for project in &active_projects {
+            let path = std::path::Path::new(&project.path);
+            if !scanner.is_git_repo(path) {
+                continue;
+            }
+
+            match self.sync_project_tags(
+                &db,
+                &scanner,
+                &project.id,
+                &project.path,
+                settings.version.auto_milestones_from_tags,
+            ).await {
+                Ok((new_tags, new_milestones)) => {
+                    tags_synced += new_tags;
+                    milestones_created += new_milestones;
+                }
+                Err(e) => {
+                    log::error!("Failed to sync tags for {}: {}", project.name, e);
+                }
+            }
+        }
+
+        if tags_synced > 0 || milestones_created > 0 {
+            log::info!("Tag sync: {} new tags, {} milestones created", tags_synced, milestones_created);
+
+            // Emit tag sync event
+            if let Err(e) = app_handle.emit("scheduler:tags-synced", serde_json::json!({
+                "new_tags": tags_synced,
+                "milestones_created": milestones_created
+            })) {
+                log::error!("Failed to emit tags-synced event: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send an end-of-day notification per project summarizing today's logs
+    fn run_daily_summary(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        let notifications = app_handle.state::<NotificationService>();
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+        let logs = db.get_daily_logs_since(&today).map_err(|e| e.to_string())?;
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let projects = db.get_projects().map_err(|e| e.to_string())?;
+        let locale = Locale::new(&load_settings(&db).language);
+        let mut by_project: HashMap<&str, Vec<&str>> = HashMap::new();
+        for log in &logs {
+            by_project.entry(log.project_id.as_str()).or_default().push(log.summary.as_str());
+        }
+
+        for project in &projects {
+            if let Some(summaries) = by_project.get(project.id.as_str()) {
+                let summary = summaries.join(&locale.t("list.clause_separator", &[]));
+                notifications.notify_daily_summary(app_handle, locale, &project.name, &summary);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_weekly_review(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        if self.check_weekly_review(&db) {
+            let _ = app_handle.emit("data:inbox-updated", ());
+        }
+        Ok(())
+    }
+
+    fn run_stale_check(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+        let settings = load_settings(&db);
+
+        let projects = db.get_projects().map_err(|e| format!("Failed to get projects: {}", e))?;
+        let active_projects: Vec<_> = projects.iter()
+            .filter(|p| p.status == crate::models::ProjectStatus::Active)
+            .collect();
+
+        let created = self.check_stale_projects(
+            &db,
+            &scanner,
+            &active_projects,
+            settings.notifications.stale_project_days,
+        );
+        if created > 0 {
+            let _ = app_handle.emit("data:inbox-updated", ());
+        }
+        Ok(())
+    }
+
+    fn run_pattern_insights(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+        let settings = load_settings(&db);
+
+        let projects = db.get_projects().map_err(|e| format!("Failed to get projects: {}", e))?;
+        let created = self.check_pattern_insights(&db, &scanner, &projects, &settings.insights);
+        if created > 0 {
+            let _ = app_handle.emit("data:inbox-updated", ());
+        }
+        Ok(())
+    }
+
+    /// Embed records added or changed since the last run into the semantic index
+    async fn run_embedding_index(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        let scanner = app_handle.state::<GitScanner>();
+        let settings = load_settings(&db);
+
+        let embedder = embeddings::embedder(&settings, &app_handle.state::<EmbeddingsKey>())?;
+        let stats = embeddings::reindex(&db, &scanner, embedder.as_ref(), &settings.embeddings).await?;
+        if stats.embedded > 0 || stats.removed > 0 {
+            log::info!("Semantic index: {} embedded, {} removed", stats.embedded, stats.removed);
+        }
+        Ok(())
+    }
+
+    /// Copy the database into the backups folder, keeping the newest `backup_keep` copies
+    fn run_backup(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let db = app_handle.state::<Database>();
+        let settings = load_settings(&db);
+
+        let backup_dir = app_handle.path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?
+            .join("backups");
+        std::fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+
+        let file_name = format!("sixarms-{}.db", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        db.backup_to(&backup_dir.join(&file_name)).map_err(|e| e.to_string())?;
+        log::info!("Database backed up to {}", file_name);
+
+        // Names sort chronologically, so drop everything past the newest N
+        let mut backups: Vec<_> = std::fs::read_dir(&backup_dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("sixarms-") && n.ends_with(".db"))
+            })
+            .collect();
+        backups.sort();
+
+        let excess = backups.len().saturating_sub(settings.scheduler.backup_keep as usize);
+        for old in &backups[..excess] {
+            if let Err(e) = std::fs::remove_file(old) {
+                log::warn!("Failed to remove old backup {}: {}", old.display(), e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run a scan of today's changes with AI analysis and anomaly checks
     pub async fn run_scheduled_scan(&self, app_handle: &AppHandle) -> Result<(), String> {
         log::info!("Running scheduled scan with AI analysis...");
 
@@ -261,14 +1212,10 @@ impl Scheduler {
         let grok = app_handle.state::<GrokClient>();
         let ai_agent = app_handle.state::<AiAgent>();
 
-        // Get settings
-        let settings_json = db.get_setting("user_settings")
-            .ok()
-            .flatten()
-            .unwrap_or_else(|| serde_json::to_string(&UserSettings::default()).unwrap());
-
-        let settings: UserSettings = serde_json::from_str(&settings_json)
-            .unwrap_or_default();
+        let settings = load_settings(&db);
+        let locale = Locale::new(&settings.language);
+        let force_refresh = self.force_refresh.load(Ordering::Relaxed);
+        let cache = ResponseCache::new(&db, &settings.ai, force_refresh);
 
         // Get all active projects
         let projects = db.get_projects().map_err(|e| format!("Failed to get projects: {}", e))?;
@@ -276,9 +1223,17 @@ impl Scheduler {
             .filter(|p| p.status == crate::models::ProjectStatus::Active)
             .collect();
 
+        // Cover days missed while the app was closed before looking at today
+        self.catch_up_missed_days(app_handle).await;
+
+        if let Err(e) = inbox::refresh(&db) {
+            log::warn!("Failed to refresh inbox: {}", e);
+        }
+
         log::info!("Scanning {} active projects", active_projects.len());
 
         let mut inbox_items_created = 0;
+        let mut today_diffs: HashMap<String, GitDiffResult> = HashMap::new();
 
         for project in &active_projects {
             let path = std::path::Path::new(&project.path);
@@ -299,54 +1254,76 @@ impl Scheduler {
                 }
             };
 
+            if let Err(e) = db.set_setting(&scan_cursor_key(&project.id), &chrono::Local::now().to_rfc3339()) {
+                log::warn!("Failed to record scan cursor for {}: {}", project.name, e);
+            }
+
             // Skip if no changes
             if diff.total_additions == 0 && diff.total_deletions == 0 {
                 continue;
             }
 
-            // Run AI analysis if enabled
-            if settings.scan.auto_classify || settings.scan.auto_summarize {
-                match ai_agent.analyze_daily_work(&grok, project, &diff).await {
+            today_diffs.insert(project.id.clone(), diff.clone());
+
+            // Re-analyze only when today's work has grown past what the log already covers,
+            // or when a forced refresh asks for it anyway
+            let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+            let existing_log = db.get_daily_log_for_date(&project.id, &today).ok().flatten();
+            let grown = force_refresh || diff_has_grown(existing_log.as_ref(), &diff);
+
+            // Past the monthly AI budget, keep today's log current with the offline classifier
+            if grown && usage::over_budget(&db, &settings.ai) {
+                let subjects = scanner
+                    .get_commit_subjects(path, &format!("{} 00:00:00", today), None)
+                    .unwrap_or_default();
+                let mut diff = diff.clone();
+                diff.date = today.clone();
+                if self.write_day_log(&db, &grok, &ai_agent, project, &diff, &subjects).await {
+                    let _ = app_handle.emit("data:daily-log-updated", ());
+                }
+            } else if grown && (settings.scan.auto_classify || settings.scan.auto_summarize) {
+                let corrections = db.get_corrected_logs(&project.id, MAX_CORRECTION_EXAMPLES).unwrap_or_default();
+                match ai_agent.analyze_daily_work(&grok, &cache, project, &diff, &corrections).await {
                     Ok(analysis) => {
                         // Create inbox item for daily summary
-                        let inbox_item = ai_agent.create_daily_summary_inbox(project, &analysis);
-
-                        if let Err(e) = db.create_inbox_item(&inbox_item) {
-                            log::error!("Failed to create inbox item: {}", e);
-                        } else {
-                            inbox_items_created += 1;
+                        // Later scans the same day update this item rather than adding another
+                        let mut inbox_item = ai_agent.create_daily_summary_inbox(locale, project, &analysis);
+                        inbox_item.dedupe_by_day(&today);
+
+                        match inbox::post(&db, &settings.version, inbox_item) {
+                            Ok(true) => inbox_items_created += 1,
+                            Ok(false) => {}
+                            Err(e) => log::error!("Failed to create inbox item: {}", e),
                         }
 
                         // Create daily log if auto-summarize is enabled
                         if settings.scan.auto_summarize {
-                            let category = match analysis.category.as_str() {
-                                "feature" => LogCategory::Feature,
-                                "bugfix" => LogCategory::Bugfix,
-                                "refactor" => LogCategory::Refactor,
-                                "ui" => LogCategory::Ui,
-                                "docs" => LogCategory::Docs,
-                                "test" => LogCategory::Test,
-                                "chore" => LogCategory::Chore,
-                                _ => LogCategory::Other,
-                            };
+                            let category = classifier::parse_category(&analysis.category);
 
                             let daily_log = DailyLog {
                                 id: uuid::Uuid::new_v4().to_string(),
                                 project_id: project.id.clone(),
-                                date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+                                date: today.clone(),
                                 summary: analysis.summary.clone(),
                                 category,
                                 files_changed: diff.files.clone(),
                                 ai_classification: Some(analysis.category.clone()),
                                 user_override: None,
                                 created_at: chrono::Utc::now(),
+                                prompt_version: analysis.prompt_version.clone(),
+                                summary_by_user: false,
                             };
 
-                            if let Err(e) = db.create_daily_log(&daily_log) {
-                                // Ignore duplicate entry errors
-                                if !e.to_string().contains("UNIQUE constraint") {
-                                    log::error!("Failed to create daily log: {}", e);
+                            match db.upsert_daily_log(&daily_log) {
+                                Ok(stored) => {
+                                    let undo = match existing_log.clone() {
+                                        Some(log) => UndoOperation::RestoreDailyLog { log },
+                                        None => UndoOperation::DeleteDailyLog { id: stored.id },
+                                    };
+                                    journal::record(&db, ActionTrigger::Scheduler, "progress_logged", &stored.summary, Some(&project.id), undo);
+                                    let _ = app_handle.emit("data:daily-log-updated", ());
                                 }
+                                Err(e) => log::error!("Failed to write daily log: {}", e),
                             }
                         }
                     }
@@ -365,13 +1342,14 @@ impl Scheduler {
                     || diff.total_deletions as u32 >= threshold.deletions;
 
                 if is_major {
-                    let question = format!(
-                        "偵測到 {} 的重大更新：{}個檔案變更，+{} 行，-{} 行。要記錄為里程碑嗎？",
-                        project.name,
-                        files_count,
-                        diff.total_additions,
-                        diff.total_deletions
-                    );
+                    let additions = diff.total_additions.to_string();
+                    let deletions = diff.total_deletions.to_string();
+                    let question = locale.t("inbox.major_update.question", &[
+                        ("project", &project.name),
+                        ("files", &files_count.to_string()),
+                        ("additions", &additions),
+                        ("deletions", &deletions),
+                    ]);
 
                     let mut inbox_item = crate::models::InboxItem::new(
                         crate::models::InboxItemType::MajorUpdate,
@@ -381,85 +1359,57 @@ impl Scheduler {
 
                     // Add context about the changes
                     let file_names: Vec<String> = diff.files.iter().map(|f| f.path.clone()).collect();
-                    inbox_item.context = Some(format!(
-                        "檔案: {}\n新增行數: {}\n刪除行數: {}",
-                        file_names.join(", "),
-                        diff.total_additions,
-                        diff.total_deletions
-                    ));
+                    inbox_item.context = Some(locale.t("inbox.major_update.context", &[
+                        ("files", &file_names.join(", ")),
+                        ("additions", &additions),
+                        ("deletions", &deletions),
+                    ]));
 
                     // Add suggested action to create milestone
                     inbox_item.suggested_actions = vec![
                         crate::models::SuggestedAction {
                             id: "create_milestone".to_string(),
-                            label: "建立里程碑".to_string(),
+                            label: locale.t("action.create_milestone", &[]),
                             icon: Some("milestone".to_string()),
                         },
                         crate::models::SuggestedAction {
                             id: "skip".to_string(),
-                            label: "略過".to_string(),
+                            label: locale.t("action.skip", &[]),
                             icon: Some("x".to_string()),
                         },
                     ];
 
-                    if let Err(e) = db.create_inbox_item(&inbox_item) {
-                        log::error!("Failed to create major update inbox item: {}", e);
-                    } else {
-                        inbox_items_created += 1;
-                        log::info!("Created major update suggestion for {}", project.name);
+                    inbox_item.dedupe_by_day(&today);
+                    match inbox::post(&db, &settings.version, inbox_item) {
+                        Ok(true) => {
+                            inbox_items_created += 1;
+                            log::info!("Created major update suggestion for {}", project.name);
+                        }
+                        Ok(false) => {}
+                        Err(e) => log::error!("Failed to create major update inbox item: {}", e),
                     }
                 }
             }
         }
 
-        // Sync git tags if version tracking is enabled
-        let mut tags_synced = 0;
-        let mut milestones_created = 0;
-
-        if settings.version.auto_refresh {
-            for project in &active_projects {
-                let path = std::path::Path::new(&project.path);
-                if !scanner.is_git_repo(path) {
-                    continue;
-                }
-
-                match self.sync_project_tags(
-                    &db,
-                    &scanner,
-                    &project.id,
-                    &project.path,
-                    settings.version.auto_milestones_from_tags,
-                ).await {
-                    Ok((new_tags, new_milestones)) => {
-                        tags_synced += new_tags;
-                        milestones_created += new_milestones;
-                    }
-                    Err(e) => {
-                        log::error!("Failed to sync tags for {}: {}", project.name, e);
-                    }
-                }
-            }
-
-            if tags_synced > 0 || milestones_created > 0 {
-                log::info!("Tag sync: {} new tags, {} milestones created", tags_synced, milestones_created);
-
-                // Emit tag sync event
-                if let Err(e) = app_handle.emit("scheduler:tags-synced", serde_json::json!({
-                    "new_tags": tags_synced,
-                    "milestones_created": milestones_created
-                })) {
-                    log::error!("Failed to emit tags-synced event: {}", e);
-                }
-            }
+        // Rule-based anomaly detection
+        if settings.anomaly.enabled {
+            inbox_items_created += self.check_anomalies(
+                &db,
+                &scanner,
+                &grok,
+                &ai_agent,
+                &active_projects,
+                &today_diffs,
+                &settings.anomaly,
+            ).await;
         }
 
         log::info!("Scheduled scan complete: {} inbox items created", inbox_items_created);
 
         // Emit scan complete event
         if let Err(e) = app_handle.emit("scheduler:scan-complete", serde_json::json!({
-            "inbox_items_created": inbox_items_created,
-            "tags_synced": tags_synced,
-            "milestones_created": milestones_created
+            "inbox_items_created": inbox_items_created
         })) {
             log::error!("Failed to emit scan-complete event: {}", e);
         }
@@ -477,3 +1427,72 @@ impl Default for Scheduler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AiCreateMode, FileChange, InboxStatus, LogCategory};
+
+    fn files(count: usize, lines: i32) -> Vec<FileChange> {
+        (0..count)
+            .map(|i| FileChange { path: format!("src/{}.rs", i), additions: lines, deletions: 0 })
+            .collect()
+    }
+
+    fn diff(count: usize, lines: i32) -> GitDiffResult {
+        GitDiffResult {
+            project_id: "p1".to_string(),
+            date: "2024-03-01".to_string(),
+            total_additions: count as i32 * lines,
+            total_deletions: 0,
+            files: files(count, lines),
+        }
+    }
+
+    fn log(count: usize, lines: i32) -> DailyLog {
+        DailyLog {
+            id: "log1".to_string(),
+            project_id: "p1".to_string(),
+            date: "2024-03-01".to_string(),
+            summary: "Morning work".to_string(),
+            category: LogCategory::Feature,
+            files_changed: files(count, lines),
+            ai_classification: None,
+            user_override: None,
+            created_at: chrono::Utc::now(),
+            prompt_version: None,
+            summary_by_user: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_has_grown() {
+        assert!(diff_has_grown(None, &diff(1, 5)));
+        assert!(!diff_has_grown(Some(&log(2, 10)), &diff(2, 10)));
+        assert!(diff_has_grown(Some(&log(2, 10)), &diff(2, 30)));
+        assert!(diff_has_grown(Some(&log(2, 10)), &diff(3, 5)));
+        assert!(!diff_has_grown(Some(&log(3, 10)), &diff(2, 10)));
+    }
+
+    #[test]
+    fn test_stale_project_waits_in_auto_mode() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Database::new(dir.path().to_path_buf()).unwrap();
+        let mut settings = UserSettings::default();
+        settings.version.ai_create_mode = AiCreateMode::Auto;
+        db.set_setting("user_settings", &serde_json::to_string(&settings).unwrap()).unwrap();
+
+        let mut project = Project::new("Parser".to_string(), dir.path().join("missing").display().to_string());
+        project.created_at = chrono::Utc::now() - chrono::Duration::days(30);
+        db.create_project(&project).unwrap();
+
+        let created = Scheduler::new().check_stale_projects(&db, &GitScanner::new(), &[&project], 14);
+        assert_eq!(created, 1);
+
+        let items = db.get_inbox_items(None).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].status, InboxStatus::Pending);
+        assert!(items[0].detected_actions.iter().all(|a| !a.confirmed));
+        assert!(db.get_todos(None).unwrap().is_empty());
+    }
+}
@@ -1,15 +1,14 @@
 use tauri::State;
-use crate::scheduler::Scheduler;
-use crate::models::ScanSettings;
+use crate::db::Database;
+use crate::scheduler::{Job, JobStatus, Scheduler};
 
-/// Start the scheduler with given settings
+/// Start the scheduler
 #[tauri::command]
 pub async fn start_scheduler(
     scheduler: State<'_, Scheduler>,
     app_handle: tauri::AppHandle,
-    settings: ScanSettings,
 ) -> Result<(), String> {
-    scheduler.start(app_handle, settings).await;
+    scheduler.start(app_handle).await;
     Ok(())
 }
 
@@ -20,25 +19,48 @@ pub async fn stop_scheduler(scheduler: State<'_, Scheduler>) -> Result<(), Strin
     Ok(())
 }
 
-/// Get scheduler status
+/// Get scheduler status, including per-job state
 #[tauri::command]
-pub async fn get_scheduler_status(scheduler: State<'_, Scheduler>) -> Result<SchedulerStatus, String> {
+pub async fn get_scheduler_status(
+    scheduler: State<'_, Scheduler>,
+    db: State<'_, Database>,
+) -> Result<SchedulerStatus, String> {
     let is_running = scheduler.is_running().await;
     let last_scan = scheduler.last_scan_time().await;
+    let jobs = scheduler.job_statuses(&db).await;
 
     Ok(SchedulerStatus {
         is_running,
         last_scan: last_scan.map(|t| t.to_rfc3339()),
+        jobs,
     })
 }
 
-/// Trigger a manual scan
+/// Trigger a manual scan followed by a tag sync. With `force_refresh`, AI analysis
+/// bypasses cached replies.
 #[tauri::command]
 pub async fn trigger_manual_scan(
     scheduler: State<'_, Scheduler>,
     app_handle: tauri::AppHandle,
+    force_refresh: Option<bool>,
+) -> Result<(), String> {
+    if force_refresh.unwrap_or(false) {
+        scheduler.run_forced_scan(&app_handle).await?;
+    } else {
+        scheduler.run_job(&app_handle, Job::Scan).await?;
+    }
+    scheduler.run_job(&app_handle, Job::TagSync).await
+}
+
+/// Run a named job immediately, regardless of its schedule
+#[tauri::command]
+pub async fn trigger_job(
+    scheduler: State<'_, Scheduler>,
+    app_handle: tauri::AppHandle,
+    name: String,
 ) -> Result<(), String> {
-    scheduler.run_scheduled_scan(&app_handle).await
+    let job = Job::from_name(&name).ok_or_else(|| format!("Unknown job: {}", name))?;
+    scheduler.run_job(&app_handle, job).await
 }
 
 /// Scheduler status response
@@ -46,4 +68,5 @@ pub async fn trigger_manual_scan(
 pub struct SchedulerStatus {
     pub is_running: bool,
     pub last_scan: Option<String>,
+    pub jobs: Vec<JobStatus>,
 }
@@ -1,8 +1,11 @@
 // Slash Commands module - Chat command parser and executor
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use crate::db::Database;
-use crate::models::{Todo, TodoStatus, TodoPriority, ProjectStatus};
+use crate::i18n::Locale;
+use crate::journal;
+use crate::models::{ActionTrigger, AnalyticsReport, MomentumTrend, Todo, TodoStatus, TodoPriority, ProjectStatus, UndoOperation};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -16,6 +19,7 @@ pub enum SlashCommand {
     // Action commands
     Add { task: String },
     Done { identifier: String },
+    Undo,
     Scan { project_id: Option<String> },
 
     // System commands
@@ -55,6 +59,7 @@ pub fn parse_command(input: &str) -> Option<SlashCommand> {
         "plan" => Some(SlashCommand::Plan),
         "add" => args.map(|task| SlashCommand::Add { task }),
         "done" => args.map(|identifier| SlashCommand::Done { identifier }),
+        "undo" => Some(SlashCommand::Undo),
         "scan" => Some(SlashCommand::Scan { project_id: args }),
         "project" => args.map(|name| SlashCommand::Project { name }),
         "projects" => Some(SlashCommand::Projects),
@@ -64,22 +69,8 @@ pub fn parse_command(input: &str) -> Option<SlashCommand> {
 }
 
 /// Generate help text for slash commands
-pub fn get_help_text() -> String {
-    r#"Query Commands
-  /status          Today's work progress summary
-  /todo [filter]   Show TODO list
-  /stats [period]  Statistics (week/month/year)
-  /plan            Weekly plan overview
-
-Action Commands
-  /add <task>      Add new TODO
-  /done <id|name>  Mark as complete
-  /scan [project]  Trigger manual scan
-
-System Commands
-  /project <name>  Switch current project
-  /projects        List all projects
-  /help            Show this help"#.to_string()
+pub fn get_help_text(locale: Locale) -> String {
+    locale.t("slash.help", &[])
 }
 
 /// Execute a slash command
@@ -87,17 +78,18 @@ pub fn execute_command(
     command: SlashCommand,
     db: &Database,
     selected_project_id: Option<&str>,
+    locale: Locale,
 ) -> CommandResult {
     match command {
         SlashCommand::Help => CommandResult {
             success: true,
             command: "/help".to_string(),
-            message: get_help_text(),
+            message: get_help_text(locale),
             data: None,
         },
 
         SlashCommand::Status => {
-            match get_status_summary(db, selected_project_id) {
+            match get_status_summary(db, selected_project_id, locale) {
                 Ok(summary) => {
                     let msg = summary.message.clone();
                     CommandResult {
@@ -110,7 +102,7 @@ pub fn execute_command(
                 Err(e) => CommandResult {
                     success: false,
                     command: "/status".to_string(),
-                    message: format!("Failed to get status: {}", e),
+                    message: locale.t("slash.status.failed", &[("error", &e)]),
                     data: None,
                 },
             }
@@ -146,14 +138,18 @@ pub fn execute_command(
                     CommandResult {
                         success: true,
                         command: "/todo".to_string(),
-                        message: format!("{} TODOs ({} pending, {} in progress)", count, pending, in_progress),
+                        message: locale.t("slash.todo", &[
+                            ("count", &count.to_string()),
+                            ("pending", &pending.to_string()),
+                            ("in_progress", &in_progress.to_string()),
+                        ]),
                         data: Some(serde_json::to_value(&project_filtered).unwrap()),
                     }
                 },
                 Err(e) => CommandResult {
                     success: false,
                     command: "/todo".to_string(),
-                    message: format!("Failed to get TODOs: {}", e),
+                    message: locale.t("slash.todo.failed", &[("error", &e.to_string())]),
                     data: None,
                 },
             }
@@ -166,14 +162,17 @@ pub fn execute_command(
                     CommandResult {
                         success: true,
                         command: "/projects".to_string(),
-                        message: format!("{} projects ({} active)", projects.len(), active),
+                        message: locale.t("slash.projects", &[
+                            ("count", &projects.len().to_string()),
+                            ("active", &active.to_string()),
+                        ]),
                         data: Some(serde_json::to_value(&projects).unwrap()),
                     }
                 },
                 Err(e) => CommandResult {
                     success: false,
                     command: "/projects".to_string(),
-                    message: format!("Failed to get projects: {}", e),
+                    message: locale.t("slash.projects.failed", &[("error", &e.to_string())]),
                     data: None,
                 },
             }
@@ -185,16 +184,68 @@ pub fn execute_command(
             new_todo.priority = TodoPriority::Medium;
 
             match db.create_todo(&new_todo) {
-                Ok(()) => CommandResult {
-                    success: true,
-                    command: "/add".to_string(),
-                    message: format!("Added TODO: {}", task),
-                    data: Some(serde_json::to_value(&new_todo).unwrap()),
+                Ok(()) => {
+                    journal::record(db, ActionTrigger::Chat, "todo_created", &task, selected_project_id, UndoOperation::DeleteTodo {
+                        id: new_todo.id.clone(),
+                    });
+                    CommandResult {
+                        success: true,
+                        command: "/add".to_string(),
+                        message: locale.t("slash.add", &[("task", &task)]),
+                        data: Some(serde_json::to_value(&new_todo).unwrap()),
+                    }
                 },
                 Err(e) => CommandResult {
                     success: false,
                     command: "/add".to_string(),
-                    message: format!("Failed to add: {}", e),
+                    message: locale.t("slash.add.failed", &[("error", &e.to_string())]),
+                    data: None,
+                },
+            }
+        },
+
+        SlashCommand::Undo => {
+            let undone = db.get_last_undoable_entry()
+                .map_err(|e| e.to_string())
+                .and_then(|entry| match entry {
+                    Some(entry) => journal::undo(db, &entry).map(|()| Some(entry)),
+                    None => Ok(None),
+                });
+            match undone {
+                Ok(Some(entry)) => CommandResult {
+                    success: true,
+                    command: "/undo".to_string(),
+                    message: locale.t("slash.undo", &[("description", &entry.description)]),
+                    data: Some(serde_json::to_value(&entry).unwrap()),
+                },
+                Ok(None) => CommandResult {
+                    success: false,
+                    command: "/undo".to_string(),
+                    message: locale.t("slash.undo.none", &[]),
+                    data: None,
+                },
+                Err(e) => CommandResult {
+                    success: false,
+                    command: "/undo".to_string(),
+                    message: locale.t("slash.undo.failed", &[("error", &e)]),
+                    data: None,
+                },
+            }
+        },
+
+        SlashCommand::Stats { period } => {
+            let period = period.to_lowercase();
+            match crate::analytics::build_report(db, &period) {
+                Ok(report) => CommandResult {
+                    success: true,
+                    command: "/stats".to_string(),
+                    message: format_stats_message(&report, locale),
+                    data: Some(serde_json::to_value(&report).unwrap()),
+                },
+                Err(e) => CommandResult {
+                    success: false,
+                    command: "/stats".to_string(),
+                    message: locale.t("slash.stats.failed", &[("error", &e)]),
                     data: None,
                 },
             }
@@ -203,7 +254,7 @@ pub fn execute_command(
         SlashCommand::Unknown { input } => CommandResult {
             success: false,
             command: input.clone(),
-            message: format!("Unknown command: {}. Type /help for available commands.", input),
+            message: locale.t("slash.unknown", &[("input", &input)]),
             data: None,
         },
 
@@ -211,7 +262,7 @@ pub fn execute_command(
         _ => CommandResult {
             success: false,
             command: format!("{:?}", command),
-            message: "This feature is in development".to_string(),
+            message: locale.t("slash.in_development", &[]),
             data: None,
         },
     }
@@ -226,7 +277,7 @@ struct StatusSummary {
     active_projects: usize,
 }
 
-fn get_status_summary(db: &Database, project_id: Option<&str>) -> Result<StatusSummary, String> {
+fn get_status_summary(db: &Database, project_id: Option<&str>, locale: Locale) -> Result<StatusSummary, String> {
     let todos = db.get_todos(None).map_err(|e| e.to_string())?;
     let projects = db.get_projects().map_err(|e| e.to_string())?;
 
@@ -253,10 +304,12 @@ fn get_status_summary(db: &Database, project_id: Option<&str>) -> Result<StatusS
     let active = projects.iter().filter(|p| p.status == ProjectStatus::Active).count();
 
     Ok(StatusSummary {
-        message: format!(
-            "Today's Status\nPending: {} | In Progress: {} | Completed Today: {}\nActive Projects: {}",
-            pending, in_progress, completed_today, active
-        ),
+        message: locale.t("slash.status", &[
+            ("pending", &pending.to_string()),
+            ("in_progress", &in_progress.to_string()),
+            ("completed", &completed_today.to_string()),
+            ("active", &active.to_string()),
+        ]),
         todo_pending: pending,
         todo_in_progress: in_progress,
         todo_completed_today: completed_today,
@@ -264,6 +317,46 @@ fn get_status_summary(db: &Database, project_id: Option<&str>) -> Result<StatusS
     })
 }
 
+fn format_stats_message(report: &AnalyticsReport, locale: Locale) -> String {
+    let created: u32 = report.throughput.iter().map(|w| w.created).sum();
+    let completed: u32 = report.throughput.iter().map(|w| w.completed).sum();
+    let cycle = report.mean_cycle_time_hours
+        .map(|h| locale.t("slash.stats.days", &[("days", &format!("{:.1}", h / 24.0))]))
+        .unwrap_or_else(|| locale.t("slash.stats.none", &[]));
+
+    let mut categories: BTreeMap<&str, u32> = BTreeMap::new();
+    for point in &report.category_trend {
+        for (category, count) in &point.counts {
+            *categories.entry(category.as_str()).or_insert(0) += count;
+        }
+    }
+    let mut categories: Vec<_> = categories.into_iter().collect();
+    categories.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    let top_categories = if categories.is_empty() {
+        locale.t("slash.stats.none", &[])
+    } else {
+        categories.iter().take(3).map(|(c, n)| format!("{} {}", c, n)).collect::<Vec<_>>().join(", ")
+    };
+
+    let momentum = report.project_momentum.iter()
+        .filter(|m| m.trend != MomentumTrend::Steady)
+        .map(|m| format!("{} {}", if m.trend == MomentumTrend::Rising { "↑" } else { "↓" }, m.project_name))
+        .collect::<Vec<_>>();
+
+    locale.t("slash.stats", &[
+        ("period", &report.period),
+        ("since", &report.since),
+        ("until", &report.until),
+        ("streak", &report.streaks.current.to_string()),
+        ("longest", &report.streaks.longest.to_string()),
+        ("created", &created.to_string()),
+        ("completed", &completed.to_string()),
+        ("cycle", &cycle),
+        ("categories", &top_categories),
+        ("momentum", &if momentum.is_empty() { locale.t("slash.stats.steady", &[]) } else { momentum.join(", ") }),
+    ])
+}
+
 /// Tauri command to execute a slash command
 #[tauri::command]
 pub fn execute_slash_command(
@@ -271,12 +364,13 @@ pub fn execute_slash_command(
     input: String,
     project_id: Option<String>,
 ) -> CommandResult {
+    let locale = Locale::new(&crate::scheduler::load_settings(&db).language);
     match parse_command(&input) {
-        Some(cmd) => execute_command(cmd, &db, project_id.as_deref()),
+        Some(cmd) => execute_command(cmd, &db, project_id.as_deref(), locale),
         None => CommandResult {
             success: false,
             command: input,
-            message: "Not a valid slash command".to_string(),
+            message: locale.t("slash.invalid", &[]),
             data: None,
         },
     }
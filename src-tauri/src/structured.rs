@@ -0,0 +1,245 @@
+// Structured module - JSON replies from the LLM: schema hints, tolerant extraction and validation
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Attempts at getting valid output before giving up; each retry feeds the error back
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Schema a reply must follow. Sent to the provider as `response_format` where supported,
+/// and enforced locally by deserializing into the target type either way.
+pub struct JsonSchema {
+    pub name: &'static str,
+    pub schema: Value,
+}
+
+impl JsonSchema {
+    pub fn response_format(&self) -> Value {
+        serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": self.name,
+                "schema": self.schema,
+                "strict": false,
+            }
+        })
+    }
+}
+
+/// Checks beyond what deserialization enforces, e.g. that a category is a known one
+pub trait Validate {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum StructuredError {
+    /// The request itself failed
+    Request(String),
+    /// Every attempt returned output that could not be parsed or validated
+    Invalid { error: String, raw: String },
+}
+
+impl std::fmt::Display for StructuredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StructuredError::Request(e) => write!(f, "{}", e),
+            StructuredError::Invalid { error, .. } => {
+                write!(f, "Invalid AI response after {} attempts: {}", MAX_ATTEMPTS, error)
+            }
+        }
+    }
+}
+
+impl From<StructuredError> for String {
+    fn from(e: StructuredError) -> String {
+        e.to_string()
+    }
+}
+
+/// Find the first valid JSON object in free text, skipping prose, code fences and
+/// brace-delimited fragments that don't parse
+pub fn extract_json(text: &str) -> Option<&str> {
+    for (start, _) in text.char_indices().filter(|(_, c)| *c == '{') {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        for (offset, c) in text[start..].char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match c {
+                '"' => in_string = true,
+                '{' | '[' => depth += 1,
+                '}' | ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let candidate = &text[start..start + offset + c.len_utf8()];
+                        if serde_json::from_str::<Value>(candidate).is_ok() {
+                            return Some(candidate);
+                        }
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+/// Extract, deserialize and validate a reply
+pub fn parse<T: DeserializeOwned + Validate>(text: &str) -> Result<T, String> {
+    let json = extract_json(text).ok_or_else(|| "No JSON object found in response".to_string())?;
+    let value: T = serde_json::from_str(json).map_err(|e| format!("JSON does not match the expected format: {}", e))?;
+    value.validate()?;
+    Ok(value)
+}
+
+/// Decode as much of a top-level string field as has arrived in a partial JSON
+/// object, so a streamed reply can be shown before the object is complete.
+/// Returns None until the field's opening quote has been seen.
+pub fn partial_string_field(text: &str, field: &str) -> Option<String> {
+    let key = format!("\"{}\"", field);
+    let after_key = &text[text.find(&key)? + key.len()..];
+    let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start();
+    let body = after_colon.strip_prefix('"')?;
+
+    let mut value = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => {
+                let decoded = match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) if hex.len() == 4 => c,
+                            // Incomplete or surrogate escape; wait for more text
+                            _ => break,
+                        }
+                    }
+                    Some(other) => other,
+                    None => break,
+                };
+                value.push(decoded);
+            }
+            _ => value.push(c),
+        }
+    }
+
+    Some(value)
+}
+
+/// Follows one string field of a JSON object streamed in pieces, handing back the part
+/// of the field's text that each new piece adds
+pub struct StreamedField {
+    field: &'static str,
+    received: String,
+    shown: usize,
+}
+
+impl StreamedField {
+    pub fn new(field: &'static str) -> Self {
+        StreamedField {
+            field,
+            received: String::new(),
+            shown: 0,
+        }
+    }
+
+    /// Add the next piece of the reply. Returns the field's new text, if any.
+    pub fn push(&mut self, delta: &str) -> Option<String> {
+        self.received.push_str(delta);
+        let text = partial_string_field(&self.received, self.field)?;
+        if text.len() <= self.shown {
+            return None;
+        }
+        let added = text[self.shown..].to_string();
+        self.shown = text.len();
+        Some(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Reply {
+        category: String,
+    }
+
+    impl Validate for Reply {
+        fn validate(&self) -> Result<(), String> {
+            if self.category == "feature" {
+                Ok(())
+            } else {
+                Err(format!("Unknown category: {}", self.category))
+            }
+        }
+    }
+
+    #[test]
+    fn test_extract_json_from_noisy_text() {
+        assert_eq!(extract_json(r#"{"a": 1}"#), Some(r#"{"a": 1}"#));
+        assert_eq!(
+            extract_json("Sure!\n```json\n{\"a\": {\"b\": [1, 2]}}\n```"),
+            Some(r#"{"a": {"b": [1, 2]}}"#)
+        );
+        // Braces inside strings don't end the object
+        assert_eq!(extract_json(r#"{"s": "a } b {"}"#), Some(r#"{"s": "a } b {"}"#));
+        // A fragment that isn't JSON is skipped
+        assert_eq!(extract_json(r#"use {x} here: {"a": "中文"}"#), Some(r#"{"a": "中文"}"#));
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_partial_string_field() {
+        assert_eq!(partial_string_field(r#"{"mess"#, "message"), None);
+        assert_eq!(partial_string_field(r#"{"message": "做咗\n登入"#, "message"), Some("做咗\n登入".to_string()));
+        assert_eq!(
+            partial_string_field(r#"{"message": "say \"hi\"", "detected_actions": []}"#, "message"),
+            Some("say \"hi\"".to_string())
+        );
+        // A half-received escape is held back
+        assert_eq!(partial_string_field(r#"{"message": "ab\u00"#, "message"), Some("ab".to_string()));
+    }
+
+    #[test]
+    fn test_streamed_field_across_chunks() {
+        // Chunks split inside the key and inside an escape
+        let chunks = [r#"{"mes"#, r#"sage": "Fixed the "#, r#"\"#, r#"n登"#, r#"入 bug", "detected_"#, r#"actions": []}"#];
+        let mut field = StreamedField::new("message");
+        let shown: Vec<Option<String>> = chunks.iter().map(|c| field.push(c)).collect();
+
+        assert_eq!(shown[0], None);
+        assert_eq!(shown[1].as_deref(), Some("Fixed the "));
+        assert_eq!(shown[2], None);
+        assert_eq!(shown[3].as_deref(), Some("\n登"));
+        assert_eq!(shown[5], None);
+        assert_eq!(shown.into_iter().flatten().collect::<String>(), "Fixed the \n登入 bug");
+    }
+
+    #[test]
+    fn test_parse_reports_distinct_failures() {
+        assert_eq!(parse::<Reply>(r#"{"category": "feature"}"#).unwrap().category, "feature");
+        assert!(parse::<Reply>("plain text").unwrap_err().contains("No JSON object"));
+        assert!(parse::<Reply>(r#"{"other": 1}"#).unwrap_err().contains("expected format"));
+        assert!(parse::<Reply>(r#"{"category": "misc"}"#).unwrap_err().contains("Unknown category"));
+    }
+}
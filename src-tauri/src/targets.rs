@@ -0,0 +1,206 @@
+// Targets module - finds the todo, milestone or project an action names by its title
+use crate::db::Database;
+use crate::models::{
+    ActionData, DetectedAction, Milestone, MilestoneStatus, Project, Todo, TodoStatus,
+};
+
+/// Titles less similar than this to what was named don't count as a match
+const MIN_SIMILARITY: f32 = 0.5;
+/// A runner-up this close to the best match makes the name ambiguous
+const AMBIGUITY_MARGIN: f32 = 0.05;
+
+/// How closely `title` matches a name the user or the AI gave: 1 for the same text, 0.9
+/// when one contains the other, else the overlap of their character pairs
+pub fn similarity(name: &str, title: &str) -> f32 {
+    let normalize = |s: &str| -> Vec<char> {
+        s.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+    };
+    let (a, b) = (normalize(name), normalize(title));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    let (a_str, b_str): (String, String) = (a.iter().collect(), b.iter().collect());
+    if a_str.contains(&b_str) || b_str.contains(&a_str) {
+        return 0.9;
+    }
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+
+    // Dice coefficient over character bigrams, which works for CJK titles as well as words
+    let mut pairs: Vec<(char, char)> = b.windows(2).map(|w| (w[0], w[1])).collect();
+    let total = (a.len() - 1 + pairs.len()) as f32;
+    let mut shared = 0;
+    for pair in a.windows(2).map(|w| (w[0], w[1])) {
+        if let Some(i) = pairs.iter().position(|p| *p == pair) {
+            pairs.swap_remove(i);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f32 / total
+}
+
+/// The item whose title best matches `name`. Fails when nothing is close enough, or when
+/// several items match about equally well.
+pub fn best_match<'a, T>(items: &'a [T], name: &str, kind: &str, title: impl Fn(&T) -> &str) -> Result<&'a T, String> {
+    let mut scored: Vec<(f32, &T)> = items.iter()
+        .map(|item| (similarity(name, title(item)), item))
+        .filter(|(score, _)| *score >= MIN_SIMILARITY)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    match scored.as_slice() {
+        [] => Err(format!("No {} matches \"{}\"", kind, name)),
+        [(best, _), (second, _), ..] if *best < 1.0 && best - second < AMBIGUITY_MARGIN => {
+            let close: Vec<&str> = scored.iter()
+                .take_while(|(score, _)| best - score < AMBIGUITY_MARGIN)
+                .map(|(_, item)| title(item))
+                .collect();
+            Err(format!("\"{}\" matches several {}s: {}", name, kind, close.join(", ")))
+        }
+        [(_, item), ..] => Ok(item),
+    }
+}
+
+/// Whether two titles likely name the same thing, allowing for rewording
+pub fn same_title(a: &str, b: &str) -> bool {
+    similarity(a, b) >= MIN_SIMILARITY
+}
+
+/// An open todo that a new one titled `title` would repeat
+pub fn duplicate_todo<'a>(todos: &'a [Todo], title: &str) -> Option<&'a Todo> {
+    todos.iter()
+        .filter(|t| matches!(t.status, TodoStatus::Pending | TodoStatus::InProgress))
+        .find(|t| same_title(title, &t.title))
+}
+
+/// A todo by id, else by title, preferring ones still open
+pub fn resolve_todo(db: &Database, id: Option<&str>, title: &str) -> Result<Todo, String> {
+    if let Some(id) = id {
+        return db.get_todo(id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Todo not found: {}", id));
+    }
+
+    let todos = db.get_todos(None).map_err(|e| e.to_string())?;
+    let (open, closed): (Vec<Todo>, Vec<Todo>) = todos.into_iter()
+        .partition(|t| matches!(t.status, TodoStatus::Pending | TodoStatus::InProgress));
+    best_match(&open, title, "todo", |t| &t.title)
+        .or_else(|_| best_match(&closed, title, "todo", |t| &t.title))
+        .cloned()
+}
+
+/// A milestone by id, else by title or version within `project_id` when given, preferring
+/// ones not yet completed
+pub fn resolve_milestone(db: &Database, id: Option<&str>, title: &str, project_id: Option<&str>) -> Result<Milestone, String> {
+    if let Some(id) = id {
+        return db.get_milestone(id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Milestone not found: {}", id));
+    }
+
+    let milestones = db.get_milestones(project_id).map_err(|e| e.to_string())?;
+    let (open, closed): (Vec<Milestone>, Vec<Milestone>) = milestones.into_iter()
+        .partition(|m| matches!(m.status, MilestoneStatus::Planned | MilestoneStatus::InProgress));
+    let by_version = |items: &[Milestone]| {
+        items.iter().find(|m| m.version.as_deref().is_some_and(|v| similarity(title, v) == 1.0)).cloned()
+    };
+    if let Some(milestone) = by_version(&open).or_else(|| by_version(&closed)) {
+        return Ok(milestone);
+    }
+    best_match(&open, title, "milestone", |m| &m.title)
+        .or_else(|_| best_match(&closed, title, "milestone", |m| &m.title))
+        .cloned()
+}
+
+pub fn resolve_project(db: &Database, id: Option<&str>, name: &str) -> Result<Project, String> {
+    let projects = db.get_projects().map_err(|e| e.to_string())?;
+    match id {
+        Some(id) => projects.into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Project not found: {}", id)),
+        None => best_match(&projects, name, "project", |p| &p.name).cloned(),
+    }
+}
+
+/// Fill in the id and stored title of the record an update or delete names, so the user
+/// confirms a specific one. Creating actions pass through unchanged.
+pub fn resolve_action(db: &Database, action: &mut DetectedAction, project_id: Option<&str>) -> Result<(), String> {
+    match &mut action.data {
+        ActionData::UpdateTodo { todo_id, title, .. } | ActionData::DeleteTodo { todo_id, title } => {
+            let todo = resolve_todo(db, todo_id.as_deref(), title)?;
+            *todo_id = Some(todo.id);
+            *title = todo.title;
+        }
+        ActionData::UpdateMilestone { milestone_id, title, .. } => {
+            let milestone = resolve_milestone(db, milestone_id.as_deref(), title, project_id)?;
+            *milestone_id = Some(milestone.id);
+            *title = milestone.title;
+        }
+        ActionData::UpdateProject { project_id: target, name, .. } => {
+            let project = resolve_project(db, target.as_deref(), name)?;
+            *target = Some(project.id);
+            *name = project.name;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Resolve each action's target, dropping the ones that name nothing that exists
+pub fn resolve_actions(db: &Database, actions: Vec<DetectedAction>, project_id: Option<&str>) -> Vec<DetectedAction> {
+    actions.into_iter()
+        .filter_map(|mut action| match resolve_action(db, &mut action, project_id) {
+            Ok(()) => Some(action),
+            Err(e) => {
+                log::info!("Dropped a detected action: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Login bug", "login-bug"), 1.0);
+        assert_eq!(similarity("login", "Fix login redirect"), 0.9);
+        assert!(similarity("fix the logn redirect", "Fix login redirect") > MIN_SIMILARITY);
+        assert!(similarity("release", "Write API docs") < MIN_SIMILARITY);
+        assert!(similarity("登入問題", "修正登入問題") > MIN_SIMILARITY);
+        assert_eq!(similarity("", "anything"), 0.0);
+    }
+
+    #[test]
+    fn test_best_match() {
+        fn title<'a>(t: &'a &str) -> &'a str {
+            t
+        }
+        let titles = ["Fix login redirect", "Login rate limit", "Write API docs"];
+
+        assert_eq!(*best_match(&titles, "write api docs", "todo", title).unwrap(), "Write API docs");
+        assert_eq!(*best_match(&titles, "login redirect", "todo", title).unwrap(), "Fix login redirect");
+        assert_eq!(
+            best_match(&titles, "login", "todo", title).unwrap_err(),
+            "\"login\" matches several todos: Fix login redirect, Login rate limit"
+        );
+        assert!(best_match(&titles, "release notes", "todo", title).unwrap_err().starts_with("No todo matches"));
+    }
+
+    #[test]
+    fn test_duplicate_todo() {
+        let mut done = Todo::new("Add release notes".to_string(), None);
+        done.status = TodoStatus::Completed;
+        let todos = vec![Todo::new("Write API docs".to_string(), None), done];
+
+        assert_eq!(duplicate_todo(&todos, "Write the API docs").unwrap().title, "Write API docs");
+        assert!(duplicate_todo(&todos, "Add release notes").is_none());
+        assert!(duplicate_todo(&todos, "Fix login redirect").is_none());
+    }
+}
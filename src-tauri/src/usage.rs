@@ -0,0 +1,48 @@
+// Usage module - records the cost of AI calls and checks it against the monthly budget
+use chrono::{Datelike, DateTime, Local, TimeZone, Utc};
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+use crate::db::Database;
+use crate::grok::GrokClient;
+use crate::models::AiSettings;
+
+/// Write every call the client completes to the `ai_usage` table
+pub fn start_recording(app_handle: AppHandle, grok: &GrokClient) {
+    let (sink, mut calls) = mpsc::unbounded_channel();
+    grok.set_usage_sink(sink);
+
+    tauri::async_runtime::spawn(async move {
+        while let Some(usage) = calls.recv().await {
+            if let Err(e) = app_handle.state::<Database>().record_ai_usage(&usage) {
+                log::warn!("Failed to record AI usage: {}", e);
+            }
+        }
+    });
+}
+
+/// Midnight on the first of the current local month
+fn month_start() -> DateTime<Utc> {
+    let now = Local::now();
+    let first = now.date_naive().with_day(1).unwrap_or(now.date_naive());
+    Local
+        .from_local_datetime(&first.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| now.with_timezone(&Utc))
+}
+
+/// True once this month's AI spend has reached the budget. Background work that
+/// has an offline fallback should skip AI then; chat is never blocked.
+pub fn over_budget(db: &Database, settings: &AiSettings) -> bool {
+    if settings.monthly_budget_usd <= 0.0 {
+        return false;
+    }
+
+    match db.get_ai_cost_since(month_start()) {
+        Ok(spent) => spent >= settings.monthly_budget_usd,
+        Err(e) => {
+            log::warn!("Failed to read AI spend: {}", e);
+            false
+        }
+    }
+}
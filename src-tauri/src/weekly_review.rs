@@ -0,0 +1,356 @@
+// Weekly review module - summarizes a week across projects and asks reflective questions
+use std::collections::BTreeMap;
+use chrono::{Duration, NaiveDate, Utc};
+use crate::db::Database;
+use crate::i18n::Locale;
+use crate::models::{
+    parse_date, ActionData, CachedGitTag, ConversationIntent, DailyLog, DetectedAction, InboxItem,
+    InboxItemType, Milestone, Project, ProjectWeekSummary, SlippedTodo,
+    SuggestedAction, Todo, TodoStatus, WeeklyReview, WeeklySummary,
+};
+
+/// Number of questions asked at the end of every weekly review
+const REFLECTION_QUESTIONS: usize = 3;
+
+/// The reflection questions in the user's language
+pub fn reflection_questions(locale: Locale) -> Vec<String> {
+    (1..=REFLECTION_QUESTIONS)
+        .map(|i| locale.t(&format!("weekly.reflection.{}", i), &[]))
+        .collect()
+}
+
+/// Highlights listed per project in the summary
+const MAX_HIGHLIGHTS: usize = 3;
+
+/// The day of a date or timestamp
+fn day_of(date: &str) -> Option<NaiveDate> {
+    parse_date(date.get(..10)?).ok()
+}
+
+fn is_open(todo: &Todo) -> bool {
+    matches!(todo.status, TodoStatus::Pending | TodoStatus::InProgress)
+}
+
+fn in_week(date: NaiveDate, monday: NaiveDate) -> bool {
+    date >= monday && date < monday + Duration::days(7)
+}
+
+/// Summarize logs, completed todos, milestones and tags that fall in the week starting
+/// `monday`, plus unfinished todos whose due date passed before `today`
+#[allow(clippy::too_many_arguments)]
+pub fn summarize_week(
+    monday: NaiveDate,
+    today: NaiveDate,
+    projects: &[Project],
+    logs: &[DailyLog],
+    todos: &[Todo],
+    milestones: &[Milestone],
+    tags: &[CachedGitTag],
+    locale: Locale,
+) -> WeeklySummary {
+    let project_name = |id: &str| {
+        projects.iter()
+            .find(|p| p.id == id)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    };
+
+    let mut by_project: BTreeMap<String, Vec<&DailyLog>> = BTreeMap::new();
+    for log in logs {
+        if day_of(&log.date).is_some_and(|d| in_week(d, monday)) {
+            by_project.entry(log.project_id.clone()).or_default().push(log);
+        }
+    }
+
+    let mut project_summaries: Vec<ProjectWeekSummary> = by_project
+        .into_iter()
+        .map(|(project_id, logs)| {
+            let mut categories: Vec<String> = logs.iter()
+                .map(|l| l.effective_category())
+                .collect();
+            categories.sort();
+            categories.dedup();
+
+            ProjectWeekSummary {
+                project_name: project_name(&project_id),
+                project_id,
+                log_count: logs.len() as u32,
+                categories,
+                highlights: logs.iter().take(MAX_HIGHLIGHTS).map(|l| l.summary.clone()).collect(),
+            }
+        })
+        .collect();
+    project_summaries.sort_by_key(|p| std::cmp::Reverse(p.log_count));
+
+    let completed_todos = todos.iter()
+        .filter(|t| t.status == TodoStatus::Completed)
+        .filter(|t| t.completed_at.is_some_and(|c| in_week(c.with_timezone(&chrono::Local).date_naive(), monday)))
+        .map(|t| t.title.clone())
+        .collect();
+
+    let milestones = milestones.iter()
+        .filter(|m| m.completed_at.is_some_and(|c| in_week(c.with_timezone(&chrono::Local).date_naive(), monday)))
+        .map(|m| locale.t("evidence.line", &[("label", &project_name(&m.project_id)), ("value", &m.title)]))
+        .collect();
+
+    let tags = tags.iter()
+        .filter(|t| day_of(&t.date).is_some_and(|d| in_week(d, monday)))
+        .map(|t| locale.t("evidence.line", &[("label", &project_name(&t.project_id)), ("value", &t.name)]))
+        .collect();
+
+    let slipped_todos = todos.iter()
+        .filter(|t| is_open(t))
+        .filter(|t| t.due_date.as_deref().and_then(day_of).is_some_and(|d| d < today))
+        .map(|t| SlippedTodo {
+            todo_id: t.id.clone(),
+            title: t.title.clone(),
+            due_date: t.due_date.clone(),
+        })
+        .collect();
+
+    WeeklySummary {
+        projects: project_summaries,
+        completed_todos,
+        milestones,
+        tags,
+        slipped_todos,
+    }
+}
+
+/// Offer to move unfinished todos due this week (or earlier) into next week,
+/// keeping the same weekday where possible
+pub fn carry_over_actions(monday: NaiveDate, todos: &[Todo]) -> Vec<DetectedAction> {
+    let next_monday = monday + Duration::days(7);
+
+    todos.iter()
+        .filter(|t| is_open(t))
+        .filter_map(|t| {
+            let due = t.due_date.as_deref().and_then(day_of)?;
+            if due >= next_monday {
+                return None;
+            }
+            let new_due = (due + Duration::days(7)).max(next_monday);
+            Some(DetectedAction {
+                intent: ConversationIntent::UpdateTodo,
+                confidence: 1.0,
+                data: ActionData::CarryOverTodo {
+                    todo_id: t.id.clone(),
+                    title: t.title.clone(),
+                    due_date: Some(new_due.format("%Y-%m-%d").to_string()),
+                },
+                confirmed: false,
+            })
+        })
+        .collect()
+}
+
+/// Render the summary as inbox context text
+fn format_context(summary: &WeeklySummary, questions: &[String], locale: Locale) -> String {
+    let mut lines = Vec::new();
+
+    if summary.projects.is_empty() {
+        lines.push(locale.t("weekly.no_progress", &[]));
+    } else {
+        lines.push(locale.t("weekly.progress", &[]));
+        for p in &summary.projects {
+            lines.push(locale.t("weekly.project_line", &[
+                ("project", &p.project_name),
+                ("count", &p.log_count.to_string()),
+                ("categories", &p.categories.join(", ")),
+            ]));
+            for h in &p.highlights {
+                lines.push(format!("  · {}", h));
+            }
+        }
+    }
+
+    if !summary.completed_todos.is_empty() {
+        lines.push(locale.t("weekly.completed_todos", &[("items", &locale.join(&summary.completed_todos))]));
+    }
+    if !summary.milestones.is_empty() {
+        lines.push(locale.t("weekly.milestones", &[("items", &locale.join(&summary.milestones))]));
+    }
+    if !summary.tags.is_empty() {
+        lines.push(locale.t("weekly.tags", &[("items", &locale.join(&summary.tags))]));
+    }
+    if !summary.slipped_todos.is_empty() {
+        lines.push(locale.t("weekly.slipped_todos", &[]));
+        for t in &summary.slipped_todos {
+            lines.push(locale.t("weekly.slipped_line", &[
+                ("title", &t.title),
+                ("due", t.due_date.as_deref().unwrap_or("-")),
+            ]));
+        }
+    }
+
+    lines.push(locale.t("weekly.reflection", &[]));
+    for (i, q) in questions.iter().enumerate() {
+        lines.push(format!("{}. {}", i + 1, q));
+    }
+
+    lines.join("\n")
+}
+
+/// Build the weekly review record and its inbox item for the week starting `monday`
+#[allow(clippy::too_many_arguments)]
+pub fn build_review(
+    monday: NaiveDate,
+    today: NaiveDate,
+    projects: &[Project],
+    logs: &[DailyLog],
+    todos: &[Todo],
+    milestones: &[Milestone],
+    tags: &[CachedGitTag],
+    locale: Locale,
+) -> (InboxItem, WeeklyReview) {
+    let summary = summarize_week(monday, today, projects, logs, todos, milestones, tags, locale);
+    let questions = reflection_questions(locale);
+    let sunday = monday + Duration::days(6);
+
+    let mut item = InboxItem::new(
+        InboxItemType::WeeklyReview,
+        locale.t("weekly.question", &[
+            ("start", &monday.format("%m/%d").to_string()),
+            ("end", &sunday.format("%m/%d").to_string()),
+            ("projects", &summary.projects.len().to_string()),
+            ("todos", &summary.completed_todos.len().to_string()),
+        ]),
+        None,
+    );
+    item.context = Some(format_context(&summary, &questions, locale));
+    item.suggested_actions = vec![
+        SuggestedAction {
+            id: "answer_review".to_string(),
+            label: locale.t("action.answer_review", &[]),
+            icon: Some("message-circle".to_string()),
+        },
+        SuggestedAction {
+            id: "skip".to_string(),
+            label: locale.t("action.skip", &[]),
+            icon: Some("x".to_string()),
+        },
+    ];
+    item.detected_actions = carry_over_actions(monday, todos);
+
+    let review = WeeklyReview {
+        id: uuid::Uuid::new_v4().to_string(),
+        inbox_item_id: item.id.clone(),
+        week_start: monday.format("%Y-%m-%d").to_string(),
+        summary,
+        questions,
+        answers: Vec::new(),
+        created_at: Utc::now(),
+        answered_at: None,
+    };
+
+    (item, review)
+}
+
+/// Load the week's data, then persist the review and its inbox item
+pub fn generate(db: &Database, monday: NaiveDate, today: NaiveDate, locale: Locale) -> Result<InboxItem, String> {
+    let since = monday.format("%Y-%m-%d").to_string();
+    let projects = db.get_projects().map_err(|e| e.to_string())?;
+    let logs = db.get_daily_logs_since(&since).map_err(|e| e.to_string())?;
+    let todos = db.get_todos(None).map_err(|e| e.to_string())?;
+    let milestones = db.get_milestones(None).map_err(|e| e.to_string())?;
+    let tags = db.get_all_cached_git_tags().map_err(|e| e.to_string())?;
+
+    // Carry-overs are proposals, so they wait for the user even in Auto mode
+    let (item, review) = build_review(monday, today, &projects, &logs, &todos, &milestones, &tags, locale);
+
+    db.create_inbox_item(&item).map_err(|e| e.to_string())?;
+    db.create_weekly_review(&review).map_err(|e| e.to_string())?;
+
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LogCategory;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn todo(title: &str, due: Option<&str>, status: TodoStatus) -> Todo {
+        let mut t = Todo::new(title.to_string(), None);
+        t.due_date = due.map(|s| s.to_string());
+        t.status = status;
+        t
+    }
+
+    #[test]
+    fn test_summarize_week_filters_to_week() {
+        let logs = vec![
+            DailyLog {
+                id: "1".to_string(),
+                project_id: "p1".to_string(),
+                date: "2024-01-16".to_string(),
+                summary: "Added login".to_string(),
+                category: LogCategory::Feature,
+                files_changed: vec![],
+                ai_classification: None,
+                user_override: None,
+                created_at: Utc::now(),
+                prompt_version: None,
+                summary_by_user: false,
+            },
+            DailyLog {
+                id: "2".to_string(),
+                project_id: "p1".to_string(),
+                date: "2024-01-08".to_string(),
+                summary: "Last week".to_string(),
+                category: LogCategory::Bugfix,
+                files_changed: vec![],
+                ai_classification: None,
+                user_override: None,
+                created_at: Utc::now(),
+                prompt_version: None,
+                summary_by_user: false,
+            },
+        ];
+        let todos = vec![
+            todo("Slipped", Some("2024-01-17"), TodoStatus::Pending),
+            todo("Future", Some("2024-01-30"), TodoStatus::Pending),
+            todo("Done late", Some("2024-01-10"), TodoStatus::Completed),
+        ];
+
+        let summary = summarize_week(d("2024-01-15"), d("2024-01-19"), &[], &logs, &todos, &[], &[], Locale::default());
+
+        assert_eq!(summary.projects.len(), 1);
+        assert_eq!(summary.projects[0].log_count, 1);
+        assert_eq!(summary.projects[0].categories, vec!["feature".to_string()]);
+        assert_eq!(summary.slipped_todos.len(), 1);
+        assert_eq!(summary.slipped_todos[0].title, "Slipped");
+
+        let (item, review) = build_review(d("2024-01-15"), d("2024-01-19"), &[], &logs, &todos, &[], &[], Locale::new("en"));
+        assert_eq!(item.question, "Weekly review (01/15 – 01/21): 1 projects made progress, 0 TODOs completed");
+        assert_eq!(review.questions.len(), 3);
+        assert!(item.context.unwrap().ends_with("3. What is the single most important thing for next week?"));
+    }
+
+    #[test]
+    fn test_carry_over_moves_into_next_week() {
+        let todos = vec![
+            todo("This week", Some("2024-01-17"), TodoStatus::Pending),
+            todo("Long overdue", Some("2024-01-02"), TodoStatus::InProgress),
+            todo("Next week", Some("2024-01-23"), TodoStatus::Pending),
+            todo("Undated", None, TodoStatus::Pending),
+            todo("Finished", Some("2024-01-16"), TodoStatus::Completed),
+        ];
+
+        let actions = carry_over_actions(d("2024-01-15"), &todos);
+        let due: Vec<(String, Option<String>)> = actions.into_iter()
+            .map(|a| match a.data {
+                ActionData::CarryOverTodo { title, due_date, .. } => (title, due_date),
+                _ => panic!("unexpected action"),
+            })
+            .collect();
+
+        assert_eq!(due, vec![
+            ("This week".to_string(), Some("2024-01-24".to_string())),
+            ("Long overdue".to_string(), Some("2024-01-22".to_string())),
+        ]);
+    }
+}
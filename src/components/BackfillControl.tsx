@@ -0,0 +1,113 @@
+import { useState } from "react";
+import { Pause, Play, X, History } from "lucide-react";
+import { backfillApi } from "../lib/api";
+import { useToast } from "./Toast";
+import type { BackfillJob } from "../lib/types";
+
+interface BackfillControlProps {
+  projectId: string;
+  job?: BackfillJob;
+  onClose: () => void;
+}
+
+function oneYearAgo() {
+  const date = new Date();
+  date.setFullYear(date.getFullYear() - 1);
+  return date.toISOString().slice(0, 10);
+}
+
+/**
+ * Start, pause, resume or cancel a history backfill for one project
+ */
+export function BackfillControl({ projectId, job, onClose }: BackfillControlProps) {
+  const [since, setSince] = useState(oneYearAgo);
+  const [useAi, setUseAi] = useState(false);
+  const toast = useToast();
+
+  const run = async (action: () => Promise<unknown>) => {
+    try {
+      await action();
+    } catch (error) {
+      toast.error(String(error));
+    }
+  };
+
+  const active = job && (job.status === "running" || job.status === "paused" || job.status === "failed");
+
+  if (active) {
+    const total = job.days.length;
+    const percent = total > 0 ? Math.round((job.processed / total) * 100) : 100;
+
+    return (
+      <div className="mt-2 p-3 bg-bg-primary rounded border border-border-subtle">
+        <div className="flex items-center justify-between text-xs mb-2">
+          <span className="text-text-secondary">
+            Backfill {job.since} → {job.until}: {job.processed}/{total} days, {job.logs_created} logs
+          </span>
+          <div className="flex items-center gap-2">
+            {job.status === "running" ? (
+              <button onClick={() => run(() => backfillApi.pause(projectId))} className="text-accent-amber hover:text-accent-amber/80">
+                <Pause size={14} />
+              </button>
+            ) : (
+              <button onClick={() => run(() => backfillApi.resume(projectId))} className="text-accent-green hover:text-accent-green/80">
+                <Play size={14} />
+              </button>
+            )}
+            <button onClick={() => run(() => backfillApi.cancel(projectId))} className="text-text-muted hover:text-accent-rose">
+              <X size={14} />
+            </button>
+          </div>
+        </div>
+        <div className="h-1.5 bg-bg-elevated rounded overflow-hidden">
+          <div
+            className={`h-full transition-all ${job.status === "failed" ? "bg-accent-rose" : "bg-accent-cyan"}`}
+            style={{ width: `${percent}%` }}
+          />
+        </div>
+        {job.error && <p className="text-accent-rose text-xs mt-2">{job.error}</p>}
+      </div>
+    );
+  }
+
+  return (
+    <div className="mt-2 p-3 bg-bg-primary rounded border border-border-subtle">
+      <div className="flex items-center gap-2 mb-2">
+        <History size={14} className="text-accent-cyan" />
+        <p className="text-text-primary text-xs">Backfill daily logs from git history</p>
+      </div>
+      <div className="flex items-center gap-3">
+        <label className="text-text-muted text-xs">Since</label>
+        <input
+          type="date"
+          value={since}
+          onChange={(e) => setSince(e.target.value)}
+          className="terminal-input text-xs py-1"
+        />
+        <label className="flex items-center gap-1 text-text-muted text-xs">
+          <input type="checkbox" checked={useAi} onChange={(e) => setUseAi(e.target.checked)} />
+          Summarize with AI
+        </label>
+        <div className="flex-1" />
+        <button onClick={onClose} className="text-xs text-text-secondary hover:text-text-primary">
+          Skip
+        </button>
+        <button
+          onClick={() => run(async () => {
+            await backfillApi.start(projectId, since, undefined, useAi);
+            onClose();
+          })}
+          disabled={!since}
+          className="px-2 py-1 text-xs bg-accent-cyan/20 text-accent-cyan border border-accent-cyan/50 rounded hover:bg-accent-cyan/30 disabled:opacity-50"
+        >
+          Start
+        </button>
+      </div>
+      {job && (
+        <p className="text-text-muted text-xs mt-2">
+          Last backfill {job.status}: {job.logs_created} logs from {job.since} to {job.until}
+        </p>
+      )}
+    </div>
+  );
+}
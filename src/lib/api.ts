@@ -3,16 +3,21 @@ import type {
   Project,
   ProjectStatus,
   DailyLog,
+  DailyLogRevision,
+  LogCategory,
+  ClassificationAccuracy,
   Milestone,
   MilestoneStatus,
   MilestoneSource,
   Todo,
   TodoStatus,
   InboxItem,
+  InboxActionResult,
+  InboxAnswerOutcome,
   InboxStatus,
+  InboxPriority,
   ChatMessage,
   UserSettings,
-  ScanSettings,
   SchedulerStatus,
   FileChange,
   GitDiffResult,
@@ -20,7 +25,22 @@ import type {
   CachedGitTag,
   TagSyncResult,
   AiResponseWithActions,
+  ChatStreamResult,
+  ChatAgentResult,
+  AiCommandError,
+  AiErrorKind,
   DetectedAction,
+  AnalyticsReport,
+  AiUsageSummary,
+  PromptTemplate,
+  AnalyticsPeriod,
+  WeeklyReview,
+  SchedulerJobName,
+  BackfillJob,
+  SemanticMatch,
+  SourceKind,
+  ActionTrigger,
+  JournalEntry,
 } from './types';
 
 // ============================================
@@ -58,6 +78,18 @@ export const dailyLogApi = {
       summary,
       category,
     }),
+
+  getRevisions: (logId: string) =>
+    invoke<DailyLogRevision[]>('get_daily_log_revisions', { log_id: logId }),
+
+  overrideCategory: (logId: string, category: LogCategory) =>
+    invoke<DailyLog>('override_log_category', { log_id: logId, category }),
+
+  edit: (logId: string, summary?: string, category?: LogCategory) =>
+    invoke<DailyLog>('edit_log', { log_id: logId, summary, category }),
+
+  getAccuracy: () =>
+    invoke<ClassificationAccuracy[]>('get_classification_accuracy'),
 };
 
 // ============================================
@@ -135,8 +167,9 @@ export const inboxApi = {
   getAll: (status?: InboxStatus) =>
     invoke<InboxItem[]>('get_inbox_items', { status }),
 
-  answer: (id: string, answer: string) =>
-    invoke<void>('answer_inbox_item', { id, answer }),
+  // actionId runs that suggested action; a free-text answer may yield follow-up actions
+  answer: (id: string, answer: string, actionId?: string) =>
+    invoke<InboxAnswerOutcome>('answer_inbox_item', { id, answer, action_id: actionId }),
 
   create: (
     itemType: string,
@@ -150,6 +183,24 @@ export const inboxApi = {
       project_id: projectId,
       context,
     }),
+
+  skip: (id: string) =>
+    invoke<void>('skip_inbox_item', { id }),
+
+  snooze: (id: string, until: string) =>
+    invoke<void>('snooze_inbox_item', { id, until }),
+
+  setPriority: (id: string, priority: InboxPriority) =>
+    invoke<void>('set_inbox_item_priority', { id, priority }),
+
+  applyActions: (itemId: string, actionIndices: number[]) =>
+    invoke<InboxActionResult[]>('apply_inbox_actions', { item_id: itemId, action_indices: actionIndices }),
+
+  getWeeklyReviews: (limit?: number) =>
+    invoke<WeeklyReview[]>('get_weekly_reviews', { limit }),
+
+  answerWeeklyReview: (id: string, answers: string[]) =>
+    invoke<void>('answer_weekly_review', { id, answers }),
 };
 
 // ============================================
@@ -182,6 +233,20 @@ export interface ChatHistoryItem {
   content: string;
 }
 
+function isAiCommandError(error: unknown): error is AiCommandError {
+  return typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+}
+
+/** Tell a bad API key apart from rate limits or x.ai being unreachable */
+export function aiErrorKind(error: unknown): AiErrorKind {
+  return isAiCommandError(error) ? error.kind : 'other';
+}
+
+/** The text of a failed command, whether it failed with a plain message or an AiCommandError */
+export function errorMessage(error: unknown): string {
+  return isAiCommandError(error) ? error.message : String(error);
+}
+
 export const grokApi = {
   setApiKey: (key: string) =>
     invoke<void>('set_api_key', { key }),
@@ -211,21 +276,33 @@ export const grokApi = {
       max_history_tokens: maxHistoryTokens ?? 4000,
     }),
 
-  classify: (filesChanged: string, diffSummary: string) =>
+  classify: (filesChanged: string, diffSummary: string, projectId?: string, forceRefresh?: boolean) =>
     invoke<string>('classify_with_grok', {
       files_changed: filesChanged,
       diff_summary: diffSummary,
+      project_id: projectId,
+      force_refresh: forceRefresh,
     }),
 
-  generateSummary: (filesChanged: string, context: string) =>
+  generateSummary: (filesChanged: string, context: string, forceRefresh?: boolean) =>
     invoke<string>('generate_summary_with_grok', {
       files_changed: filesChanged,
       context,
+      force_refresh: forceRefresh,
     }),
 
   sendMessages: (messages: GrokMessage[]) =>
     invoke<string>('send_grok_messages', { messages }),
 
+  getPromptTemplates: () =>
+    invoke<PromptTemplate[]>('get_prompt_templates'),
+
+  savePromptTemplate: (name: string, source: string) =>
+    invoke<PromptTemplate>('save_prompt_template', { name, source }),
+
+  resetPromptTemplate: (name: string) =>
+    invoke<PromptTemplate>('reset_prompt_template', { name }),
+
   chatWithIntent: (
     message: string,
     history: ChatHistoryItem[],
@@ -239,10 +316,48 @@ export const grokApi = {
       project_id: projectId,
     }),
 
-  executeAction: (action: DetectedAction, projectId?: string) =>
-    invoke<{ type: string; data: unknown }>('execute_detected_action', {
+  /** Streams the reply as `chat:delta` events and saves it to chat history */
+  chatStream: (
+    requestId: string,
+    message: string,
+    history: ChatHistoryItem[],
+    projectContext?: string,
+    projectId?: string
+  ) =>
+    invoke<ChatStreamResult>('chat_stream', {
+      request_id: requestId,
+      message,
+      history,
+      project_context: projectContext,
+      project_id: projectId,
+    }),
+
+  /** Answers with the tool-calling agent, emitting `chat:agent-step` and `chat:delta` events, and saves the reply */
+  chatAgent: (
+    requestId: string,
+    message: string,
+    history: ChatHistoryItem[],
+    projectContext?: string,
+    projectId?: string
+  ) =>
+    invoke<ChatAgentResult>('chat_agent', {
+      request_id: requestId,
+      message,
+      history,
+      project_context: projectContext,
+      project_id: projectId,
+    }),
+
+  /** Stops a running chatStream or chatAgent */
+  cancelChatStream: (requestId: string) =>
+    invoke<boolean>('cancel_chat_stream', { request_id: requestId }),
+
+  /** Applies a confirmed action; `journal_id` undoes it with journalApi.undo */
+  executeAction: (action: DetectedAction, projectId?: string, trigger?: ActionTrigger) =>
+    invoke<{ type: string; data: unknown; journal_id: string | null }>('execute_detected_action', {
       action,
       project_id: projectId,
+      trigger,
     }),
 };
 
@@ -315,6 +430,12 @@ export const statsApi = {
 
   getCategoryDistribution: () =>
     invoke<[string, number][]>('get_category_distribution'),
+
+  getAnalytics: (period?: AnalyticsPeriod) =>
+    invoke<AnalyticsReport>('get_analytics', { period }),
+
+  getAiUsage: (days?: number) =>
+    invoke<AiUsageSummary[]>('get_ai_usage', { days }),
 };
 
 // ============================================
@@ -322,8 +443,8 @@ export const statsApi = {
 // ============================================
 
 export const schedulerApi = {
-  start: (settings: ScanSettings) =>
-    invoke<void>('start_scheduler', { settings }),
+  start: () =>
+    invoke<void>('start_scheduler'),
 
   stop: () =>
     invoke<void>('stop_scheduler'),
@@ -331,8 +452,79 @@ export const schedulerApi = {
   getStatus: () =>
     invoke<SchedulerStatus>('get_scheduler_status'),
 
-  triggerManualScan: () =>
-    invoke<void>('trigger_manual_scan'),
+  /** With `forceRefresh`, AI analysis skips cached replies */
+  triggerManualScan: (forceRefresh?: boolean) =>
+    invoke<void>('trigger_manual_scan', { force_refresh: forceRefresh }),
+
+  triggerJob: (name: SchedulerJobName) =>
+    invoke<void>('trigger_job', { name }),
+};
+
+// ============================================
+// Action Journal API
+// ============================================
+
+export const journalApi = {
+  getAll: (limit?: number) =>
+    invoke<JournalEntry[]>('get_action_journal', { limit }),
+
+  /** Undoes the newest chat or inbox change; resolves to null when there is nothing left to undo */
+  undoLast: () =>
+    invoke<JournalEntry | null>('undo_last_action'),
+
+  undo: (id: string) =>
+    invoke<JournalEntry>('undo_action', { id }),
+};
+
+// ============================================
+// Semantic Search API
+// ============================================
+
+export const searchApi = {
+  /** Needs `embeddings.enabled`; searches what the `embeddings` job has indexed so far */
+  semantic: (query: string, limit?: number, kinds?: SourceKind[], projectId?: string) =>
+    invoke<SemanticMatch[]>('semantic_search', {
+      query,
+      limit,
+      kinds,
+      project_id: projectId,
+    }),
+
+  /** Kept in the keychain and sent as a bearer token; an empty key removes it */
+  setApiKey: (key: string) =>
+    invoke<void>('set_embeddings_api_key', { key }),
+
+  hasApiKey: () =>
+    invoke<boolean>('has_embeddings_api_key'),
+
+  deleteApiKey: () =>
+    invoke<void>('delete_embeddings_api_key'),
+};
+
+// ============================================
+// Backfill API
+// ============================================
+
+export const backfillApi = {
+  start: (projectId: string, since: string, until?: string, useAi = false) =>
+    invoke<BackfillJob>('start_backfill', {
+      project_id: projectId,
+      since,
+      until,
+      use_ai: useAi,
+    }),
+
+  pause: (projectId: string) =>
+    invoke<void>('pause_backfill', { project_id: projectId }),
+
+  resume: (projectId: string) =>
+    invoke<BackfillJob>('resume_backfill', { project_id: projectId }),
+
+  cancel: (projectId: string) =>
+    invoke<void>('cancel_backfill', { project_id: projectId }),
+
+  getAll: () =>
+    invoke<BackfillJob[]>('get_backfill_jobs'),
 };
 
 // ============================================
@@ -23,6 +23,25 @@ export interface DailyLog {
   ai_classification?: string;
   user_override?: string;
   created_at: string;
+  prompt_version?: string;
+  summary_by_user?: boolean;
+}
+
+export interface DailyLogRevision {
+  id: string;
+  log_id: string;
+  summary: string;
+  category: LogCategory;
+  files_changed: FileChange[];
+  ai_classification?: string;
+  revised_at: string;
+}
+
+export interface ClassificationAccuracy {
+  project_id: string;
+  ai_labeled: number;
+  corrected: number;
+  accuracy: number;
 }
 
 export type LogCategory = 'feature' | 'bugfix' | 'refactor' | 'ui' | 'docs' | 'test' | 'chore' | 'other';
@@ -67,8 +86,15 @@ export interface InboxItem {
   answer?: string;
   created_at: string;
   answered_at?: string;
+  prompt_version?: string;
+  priority: InboxPriority;
+  dedupe_key?: string; // type:project:date, so repeat scans update the item
+  snoozed_until?: string;
+  expires_at?: string;
 }
 
+export type InboxPriority = 'low' | 'normal' | 'high';
+
 export type InboxItemType = 'daily_summary' | 'classification' | 'todo_followup' | 'planning' | 'stale_project' | 'anomaly_detection' | 'weekly_review' | 'pattern_insight' | 'major_update';
 
 export interface SuggestedAction {
@@ -77,7 +103,7 @@ export interface SuggestedAction {
   icon?: string;
 }
 
-export type InboxStatus = 'pending' | 'answered' | 'skipped';
+export type InboxStatus = 'pending' | 'answered' | 'skipped' | 'snoozed' | 'expired';
 
 // ============================================
 // Chat Types
@@ -90,6 +116,30 @@ export interface ChatMessage {
   content: string;
   actions?: ChatAction;
   created_at: string;
+  prompt_version?: string;
+  citations?: Citation[]; // records an assistant reply cites
+}
+
+export type SourceKind = 'log' | 'commit' | 'todo' | 'milestone' | 'chat';
+
+/** A stored record an answer was grounded in, cited in its text as `[label]` */
+export interface Citation {
+  label: string;
+  kind: SourceKind;
+  id: string; // row id, or the abbreviated hash of a commit
+  date: string; // YYYY-MM-DD format
+  project_id?: string;
+  text: string;
+}
+
+/** A record found by semantic search, with its cosine similarity to the query */
+export interface SemanticMatch {
+  kind: SourceKind;
+  id: string;
+  date: string; // YYYY-MM-DD format
+  project_id?: string;
+  text: string;
+  score: number;
 }
 
 export type ChatRole = 'user' | 'assistant' | 'system';
@@ -109,6 +159,12 @@ export interface UserSettings {
   notifications: NotificationSettings;
   scan: ScanSettings;
   version: VersionSettings;
+  anomaly: AnomalySettings;
+  review: ReviewSettings;
+  insights: InsightSettings;
+  scheduler: SchedulerSettings;
+  ai: AiSettings;
+  embeddings: EmbeddingSettings;
   theme: string;
   language: string;
 }
@@ -117,6 +173,7 @@ export interface NotificationSettings {
   daily_summary: boolean;
   todo_reminder: boolean;
   stale_project: boolean;
+  stale_project_days: number;
 }
 
 export interface ScanSettings {
@@ -134,6 +191,8 @@ export interface VersionSettings {
   auto_major_updates: boolean;
   major_update_threshold: MajorUpdateThreshold;
   ai_create_mode: AiCreateMode;
+  auto_apply_types: ActionData['type'][]; // applied without asking in auto mode
+  auto_apply_confidence: number; // only above this confidence
 }
 
 export interface MajorUpdateThreshold {
@@ -143,9 +202,117 @@ export interface MajorUpdateThreshold {
 
 export type AiCreateMode = 'suggest' | 'auto' | 'disabled';
 
+export interface AnomalySettings {
+  enabled: boolean;
+  use_ai_phrasing: boolean;
+  baseline_weeks: number;
+  low_activity_ratio: number;
+  deletion_ratio: number;
+  min_deleted_lines: number;
+  late_night_start_hour: number;
+  late_night_end_hour: number;
+  late_night_min_commits: number;
+  category_switch_window: number;
+  lockfile_churn_lines: number;
+  binary_files: number;
+}
+
+export interface ReviewSettings {
+  weekly_review: boolean;
+}
+
+export type SchedulerJobName =
+  | 'scan'
+  | 'tag_sync'
+  | 'daily_summary'
+  | 'weekly_review'
+  | 'stale_check'
+  | 'insights'
+  | 'backup'
+  | 'embeddings';
+
+export interface SchedulerSettings {
+  // Cron expression, '@every 30m', 'HH:MM' or 'fri 17:00'; unset jobs use their default
+  jobs: Partial<Record<SchedulerJobName, string>>;
+  backup_keep: number;
+}
+
+export interface AiSettings {
+  request_timeout_secs: number;
+  max_retries: number;
+  max_concurrent_requests: number;
+  circuit_failure_threshold: number; // consecutive failed calls before AI work is paused
+  circuit_cooldown_secs: number;
+  prompt_price_per_million: number; // USD per million prompt tokens
+  completion_price_per_million: number;
+  monthly_budget_usd: number; // 0 means no budget
+  cache_ttl_days: number; // 0 turns the response cache off
+  cache_max_entries: number;
+  agent_max_steps: number; // tool-calling rounds per chat message before the agent must answer
+  interpret_answers: boolean; // read follow-up actions out of free-text inbox answers
+}
+
+/** The optional semantic index, built from an OpenAI-compatible embeddings endpoint */
+export interface EmbeddingSettings {
+  enabled: boolean;
+  endpoint: string; // e.g. a local Ollama server's /v1/embeddings
+  model: string;
+  batch_size: number; // texts sent per request
+  commit_days: number; // how far back commit messages are indexed
+}
+
+export type AiPurpose = 'classify' | 'summary' | 'analysis' | 'intent' | 'anomaly' | 'agent' | 'chat';
+
+export interface AiUsageSummary {
+  date: string; // YYYY-MM-DD format
+  purpose: AiPurpose;
+  calls: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  cost_usd: number;
+  avg_latency_ms: number;
+}
+
+/** A prompt template; `custom` when loaded from an override in the app data dir */
+export interface PromptTemplate {
+  name: string;
+  language: string;
+  version: number;
+  custom: boolean;
+  source: string;
+}
+
+/** Kind of a failed AI call; 'other' for failures outside the AI call itself */
+export type AiErrorKind = 'auth' | 'quota' | 'network' | 'parse' | 'api' | 'other';
+
+/** How chatAgent and chatStream fail */
+export interface AiCommandError {
+  kind: AiErrorKind;
+  message: string;
+}
+
+export interface InsightSettings {
+  enabled: boolean;
+  lookback_days: number;
+  min_samples: number;
+  abandon_gap_days: number;
+  repeat_days: number;
+}
+
 export interface SchedulerStatus {
   is_running: boolean;
   last_scan: string | null;
+  jobs: JobStatus[];
+}
+
+export interface JobStatus {
+  name: SchedulerJobName;
+  enabled: boolean;
+  schedule: string;
+  last_run: string | null;
+  next_run: string | null;
+  running: boolean;
+  last_error: string | null;
 }
 
 // ============================================
@@ -208,6 +375,107 @@ export interface TagSyncResult {
   new_tags: CachedGitTag[];
 }
 
+// ============================================
+// Weekly Review Types
+// ============================================
+
+export interface WeeklyReview {
+  id: string;
+  inbox_item_id: string;
+  week_start: string;
+  summary: WeeklySummary;
+  questions: string[];
+  answers: string[];
+  created_at: string;
+  answered_at: string | null;
+}
+
+export interface WeeklySummary {
+  projects: ProjectWeekSummary[];
+  completed_todos: string[];
+  milestones: string[];
+  tags: string[];
+  slipped_todos: SlippedTodo[];
+}
+
+export interface ProjectWeekSummary {
+  project_id: string;
+  project_name: string;
+  log_count: number;
+  categories: string[];
+  highlights: string[];
+}
+
+export interface SlippedTodo {
+  todo_id: string;
+  title: string;
+  due_date: string | null;
+}
+
+// ============================================
+// Backfill Types
+// ============================================
+
+export type BackfillStatus = 'running' | 'paused' | 'completed' | 'cancelled' | 'failed';
+
+export interface BackfillJob {
+  project_id: string;
+  since: string;
+  until: string;
+  use_ai: boolean;
+  status: BackfillStatus;
+  days: string[];
+  processed: number;
+  logs_created: number;
+  error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+// ============================================
+// Analytics Types
+// ============================================
+
+export interface AnalyticsReport {
+  period: AnalyticsPeriod;
+  since: string;
+  until: string;
+  streaks: StreakStats;
+  throughput: WeeklyThroughput[];
+  mean_cycle_time_hours: number | null;
+  category_trend: CategoryTrendPoint[];
+  project_momentum: ProjectMomentum[];
+}
+
+export type AnalyticsPeriod = 'week' | 'month' | 'year';
+
+export interface StreakStats {
+  current: number;
+  longest: number;
+  longest_start?: string;
+  longest_end?: string;
+  active_days: number;
+}
+
+export interface WeeklyThroughput {
+  week_start: string;
+  created: number;
+  completed: number;
+}
+
+export interface CategoryTrendPoint {
+  week_start: string;
+  counts: Record<string, number>;
+}
+
+export interface ProjectMomentum {
+  project_id: string;
+  project_name: string;
+  weekly_activity: number[];
+  slope: number;
+  trend: 'rising' | 'steady' | 'falling';
+}
+
 // ============================================
 // AI Action Types
 // ============================================
@@ -217,6 +485,10 @@ export type ConversationIntent =
   | 'log_progress'
   | 'create_inbox_item'
   | 'create_milestone'
+  | 'update_todo'
+  | 'delete_todo'
+  | 'update_milestone'
+  | 'update_project'
   | 'ask_question'
   | 'general_chat';
 
@@ -232,11 +504,97 @@ export type ActionData =
   | { type: 'progress'; summary: string; category: string; date?: string }
   | { type: 'inbox'; question: string; item_type: string }
   | { type: 'milestone'; title: string; description?: string; version?: string; git_tag?: string }
+  | { type: 'carry_over_todo'; todo_id: string; title: string; due_date?: string }
+  | {
+      type: 'update_todo';
+      todo_id?: string; // found by fuzzy title match when missing
+      title: string;
+      status?: TodoStatus;
+      priority?: TodoPriority;
+      due_date?: string;
+      column?: string;
+    }
+  | { type: 'delete_todo'; todo_id?: string; title: string }
+  | { type: 'update_milestone'; milestone_id?: string; title: string; status?: MilestoneStatus; target_date?: string }
+  | { type: 'update_project'; project_id?: string; name: string; status: ProjectStatus }
   | { type: 'none' };
 
+/** What answering an inbox item did */
+export interface InboxAnswerOutcome {
+  results: { type: string; data: unknown; journal_id: string | null }[]; // changes the chosen action made
+  follow_ups: DetectedAction[]; // read from a free-text answer, added to the item to confirm
+}
+
+/** One entry per index passed to apply_inbox_actions */
+export type InboxActionResult =
+  | { index: number; result: { type: string; data: unknown; journal_id: string | null } }
+  | { index: number; error: string };
+
 export interface AiResponseWithActions {
   message: string;
   detected_actions: DetectedAction[];
+  prompt_version?: string;
+}
+
+export interface ChatStreamResult {
+  message: ChatMessage;
+  detected_actions: DetectedAction[];
+  cancelled: boolean;
+}
+
+export interface ChatDelta {
+  request_id: string;
+  delta: string;
+}
+
+/** A tool call the chat agent made while answering */
+export interface AgentStep {
+  tool: string;
+  arguments: Record<string, unknown>;
+  result: unknown; // `{ error }` when the call failed
+  needs_confirmation: boolean; // a proposed change, returned in detected_actions
+}
+
+export interface AgentStepEvent {
+  request_id: string;
+  step: AgentStep;
+}
+
+export interface ChatAgentResult {
+  message: ChatMessage;
+  detected_actions: DetectedAction[];
+  steps: AgentStep[];
+  cancelled: boolean;
+}
+
+// ============================================
+// Action Journal Types
+// ============================================
+
+/** Where a journaled change came from */
+export type ActionTrigger = 'chat' | 'inbox' | 'scheduler';
+
+export type UndoOperation =
+  | { type: 'delete_todo'; id: string }
+  | { type: 'delete_daily_log'; id: string }
+  | { type: 'delete_inbox_item'; id: string }
+  | { type: 'delete_milestone'; id: string }
+  | { type: 'recreate_todo'; todo: Todo }
+  | { type: 'restore_todo'; todo: Todo }
+  | { type: 'restore_daily_log'; log: DailyLog }
+  | { type: 'restore_milestone'; milestone: Milestone }
+  | { type: 'restore_project'; project: Project };
+
+/** A change made by the AI or a slash command, kept so it can be undone */
+export interface JournalEntry {
+  id: string;
+  trigger: ActionTrigger;
+  action_type: string; // todo_created, todo_completed, progress_logged, ...
+  description: string;
+  project_id?: string;
+  undo: UndoOperation;
+  created_at: string;
+  undone_at?: string;
 }
 
 // ============================================
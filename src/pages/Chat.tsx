@@ -1,13 +1,14 @@
 import { useState, useRef, useEffect } from "react";
 import { motion, AnimatePresence } from "framer-motion";
-import { Send, Loader2, AlertCircle, RefreshCw, ListTodo, FileText, CheckCircle, X, Terminal } from "lucide-react";
+import { Link } from "react-router-dom";
+import { Send, Loader2, AlertCircle, RefreshCw, ListTodo, FileText, CheckCircle, X, Terminal, Square, Flag } from "lucide-react";
 import { useChatStore } from "../stores/chatStore";
 import { useProjectStore } from "../stores/projectStore";
 import { useSettingsStore } from "../stores/settingsStore";
 import { ProjectSelector } from "../components/ProjectSelector";
 import { CommandResponse } from "../components/chat/CommandResponse";
 import { slashApi, type CommandResult } from "../lib/api";
-import type { ChatMessage, ChatAction, DetectedAction } from "../lib/types";
+import type { ChatMessage, ChatAction, Citation, DetectedAction, SourceKind } from "../lib/types";
 
 function ActionCard({ action }: { action?: ChatAction }) {
   if (!action) return null;
@@ -40,6 +41,36 @@ function ActionCard({ action }: { action?: ChatAction }) {
   return null;
 }
 
+// Where each kind of cited record is listed; commits have no page of their own
+const CITATION_ROUTES: Partial<Record<SourceKind, string>> = {
+  log: "/dashboard",
+  todo: "/todos",
+  milestone: "/versions",
+};
+
+function Citations({ citations }: { citations?: Citation[] }) {
+  if (!citations || citations.length === 0) return null;
+
+  return (
+    <div className="mt-3 flex flex-wrap gap-2 text-xs font-mono">
+      {citations.map((citation) => {
+        const label = `[${citation.label}] ${citation.date} ${citation.kind === "commit" ? citation.id : citation.kind}`;
+        const route = CITATION_ROUTES[citation.kind];
+        const className = "px-2 py-0.5 rounded border border-border-subtle text-text-muted";
+        return route ? (
+          <Link key={citation.label} to={route} title={citation.text} className={`${className} hover:text-accent-cyan`}>
+            {label}
+          </Link>
+        ) : (
+          <span key={citation.label} title={citation.text} className={className}>
+            {label}
+          </span>
+        );
+      })}
+    </div>
+  );
+}
+
 function MessageBubble({ message }: { message: ChatMessage }) {
   const formatTime = (dateStr: string) => {
     const date = new Date(dateStr);
@@ -65,6 +96,7 @@ function MessageBubble({ message }: { message: ChatMessage }) {
         {message.content}
       </div>
       <ActionCard action={message.actions} />
+      <Citations citations={message.citations} />
     </div>
   );
 }
@@ -106,6 +138,52 @@ function ActionSuggestion({
           color: 'text-accent-amber',
           bgColor: 'bg-accent-amber/10 border-accent-amber/30',
         };
+      case 'milestone':
+        return {
+          icon: <Flag size={16} />,
+          label: '創建里程碑',
+          title: action.data.version ? `${action.data.title} (${action.data.version})` : action.data.title,
+          color: 'text-accent-cyan',
+          bgColor: 'bg-accent-cyan/10 border-accent-cyan/30',
+        };
+      case 'update_todo':
+        return {
+          icon: <ListTodo size={16} />,
+          label: action.data.status === 'completed' ? '完成待辦' : '更新待辦',
+          title: [
+            action.data.title,
+            action.data.status,
+            action.data.priority,
+            action.data.due_date,
+            action.data.column,
+          ].filter(Boolean).join(' · '),
+          color: 'text-accent-cyan',
+          bgColor: 'bg-accent-cyan/10 border-accent-cyan/30',
+        };
+      case 'delete_todo':
+        return {
+          icon: <ListTodo size={16} />,
+          label: '刪除待辦',
+          title: action.data.title,
+          color: 'text-accent-rose',
+          bgColor: 'bg-accent-rose/10 border-accent-rose/30',
+        };
+      case 'update_milestone':
+        return {
+          icon: <Flag size={16} />,
+          label: '更新里程碑',
+          title: [action.data.title, action.data.status, action.data.target_date].filter(Boolean).join(' · '),
+          color: 'text-accent-cyan',
+          bgColor: 'bg-accent-cyan/10 border-accent-cyan/30',
+        };
+      case 'update_project':
+        return {
+          icon: <Flag size={16} />,
+          label: '更新項目',
+          title: `${action.data.name} · ${action.data.status}`,
+          color: 'text-accent-amber',
+          bgColor: 'bg-accent-amber/10 border-accent-amber/30',
+        };
       default:
         return null;
     }
@@ -165,10 +243,13 @@ export function Chat() {
     pendingActions,
     loading,
     sending,
+    agentSteps,
+    streamingContent,
     executingAction,
     error,
     fetchMessages,
     sendMessage,
+    cancelStream,
     executeAction,
     dismissAction,
   } = useChatStore();
@@ -191,7 +272,7 @@ export function Chat() {
 
   useEffect(() => {
     scrollToBottom();
-  }, [messages, commandResults]);
+  }, [messages, commandResults, agentSteps, streamingContent]);
 
   const handleSend = async () => {
     if (!input.trim() || sending || executingCommand) return;
@@ -317,13 +398,45 @@ export function Chat() {
             animate={{ opacity: 1 }}
             className="flex justify-start"
           >
-            <div className="ai-bubble p-4 rounded">
-              <div className="flex items-center gap-1">
-                <span className="w-2 h-2 bg-accent-cyan rounded-full animate-bounce" style={{ animationDelay: "0ms" }} />
-                <span className="w-2 h-2 bg-accent-cyan rounded-full animate-bounce" style={{ animationDelay: "150ms" }} />
-                <span className="w-2 h-2 bg-accent-cyan rounded-full animate-bounce" style={{ animationDelay: "300ms" }} />
+            {agentSteps.length > 0 || streamingContent ? (
+              <div className="max-w-[80%] ai-bubble p-4 rounded">
+                <div className="flex items-center justify-between mb-2 text-xs text-text-muted font-mono">
+                  <span>AI</span>
+                  <button
+                    onClick={cancelStream}
+                    className="flex items-center gap-1 hover:text-accent-rose transition-colors"
+                  >
+                    <Square size={10} />
+                    Stop
+                  </button>
+                </div>
+                {agentSteps.length > 0 && (
+                  <div className="space-y-1 text-xs font-mono text-text-muted">
+                    {agentSteps.map((step, index) => (
+                      <div key={`step-${index}`} className="flex items-center gap-2">
+                        <span className={step.needs_confirmation ? "text-accent-amber" : "text-accent-cyan"}>→</span>
+                        <span>{step.tool}</span>
+                        {step.needs_confirmation && <span className="text-accent-amber">待確認</span>}
+                      </div>
+                    ))}
+                    {!streamingContent && <Loader2 size={12} className="animate-spin text-accent-cyan" />}
+                  </div>
+                )}
+                {streamingContent && (
+                  <div className={`text-text-primary whitespace-pre-wrap font-body${agentSteps.length > 0 ? " mt-2" : ""}`}>
+                    {streamingContent}
+                  </div>
+                )}
               </div>
-            </div>
+            ) : (
+              <div className="ai-bubble p-4 rounded">
+                <div className="flex items-center gap-1">
+                  <span className="w-2 h-2 bg-accent-cyan rounded-full animate-bounce" style={{ animationDelay: "0ms" }} />
+                  <span className="w-2 h-2 bg-accent-cyan rounded-full animate-bounce" style={{ animationDelay: "150ms" }} />
+                  <span className="w-2 h-2 bg-accent-cyan rounded-full animate-bounce" style={{ animationDelay: "300ms" }} />
+                </div>
+              </div>
+            )}
           </motion.div>
         )}
 
@@ -18,7 +18,35 @@ function InboxActionSuggestion({
   onDismiss: () => void;
   executing: boolean;
 }) {
-  if (action.data.type !== 'todo') return null;
+  const describe = (): [string, string] | null => {
+    const data = action.data;
+    switch (data.type) {
+      case 'todo':
+        return ['創建待辦', data.title];
+      case 'carry_over_todo':
+        return ['移到下週', data.due_date ? `${data.title} → ${data.due_date}` : data.title];
+      case 'update_todo':
+        return [data.status === 'completed' ? '完成待辦' : '更新待辦', data.title];
+      case 'delete_todo':
+        return ['刪除待辦', data.title];
+      case 'progress':
+        return ['記錄進度', data.summary];
+      case 'inbox':
+        return ['添加跟進', data.question];
+      case 'milestone':
+        return ['創建里程碑', data.title];
+      case 'update_milestone':
+        return ['更新里程碑', data.title];
+      case 'update_project':
+        return ['更新項目', `${data.name} · ${data.status}`];
+      default:
+        return null;
+    }
+  };
+
+  const described = describe();
+  if (!described) return null;
+  const [label, title] = described;
 
   return (
     <motion.div
@@ -30,31 +58,39 @@ function InboxActionSuggestion({
       <div className="flex items-center gap-3">
         <span className="text-accent-cyan"><ListTodo size={16} /></span>
         <div>
-          <span className="text-xs font-mono text-accent-cyan">創建待辦</span>
+          <span className="text-xs font-mono text-accent-cyan">{label}</span>
           <p className="text-sm text-text-primary truncate max-w-[300px]">
-            {action.data.title}
+            {title}
           </p>
         </div>
       </div>
       <div className="flex items-center gap-2">
-        <button
-          onClick={onDismiss}
-          disabled={executing}
-          className="p-1.5 text-text-muted hover:text-text-primary transition-colors"
-        >
-          <X size={14} />
-        </button>
-        <button
-          onClick={onExecute}
-          disabled={executing}
-          className="px-3 py-1.5 text-sm rounded text-accent-cyan bg-accent-cyan/10 border border-accent-cyan/30 hover:opacity-80 transition-opacity flex items-center gap-2"
-        >
-          {executing ? (
-            <Loader2 size={14} className="animate-spin" />
-          ) : (
-            <>確認</>
-          )}
-        </button>
+        {action.confirmed ? (
+          <span className="px-3 py-1.5 text-sm text-accent-green flex items-center gap-1">
+            <Check size={14} /> 已套用
+          </span>
+        ) : (
+          <>
+            <button
+              onClick={onDismiss}
+              disabled={executing}
+              className="p-1.5 text-text-muted hover:text-text-primary transition-colors"
+            >
+              <X size={14} />
+            </button>
+            <button
+              onClick={onExecute}
+              disabled={executing}
+              className="px-3 py-1.5 text-sm rounded text-accent-cyan bg-accent-cyan/10 border border-accent-cyan/30 hover:opacity-80 transition-opacity flex items-center gap-2"
+            >
+              {executing ? (
+                <Loader2 size={14} className="animate-spin" />
+              ) : (
+                <>確認</>
+              )}
+            </button>
+          </>
+        )}
       </div>
     </motion.div>
   );
@@ -63,14 +99,20 @@ function InboxActionSuggestion({
 function InboxItemCard({
   item,
   onAnswer,
-  onExecuteAction,
+  onSkip,
+  onSnooze,
+  onApplyActions,
   onDismissAction,
+  dismissedActions,
   executingAction,
 }: {
   item: InboxItem;
-  onAnswer: (id: string, answer: string) => void;
-  onExecuteAction: (itemId: string, action: DetectedAction, projectId?: string) => void;
+  onAnswer: (id: string, answer: string, actionId?: string) => void;
+  onSkip: (id: string) => void;
+  onSnooze: (id: string, until: Date) => void;
+  onApplyActions: (itemId: string, actionIndices: number[]) => void;
   onDismissAction: (itemId: string, actionIndex: number) => void;
+  dismissedActions: number[];
   executingAction: boolean;
 }) {
   const [inputValue, setInputValue] = useState("");
@@ -78,6 +120,12 @@ function InboxItemCard({
   const { projects } = useProjectStore();
   const project = projects.find((p) => p.id === item.project_id);
 
+  // Indices stay those of item.detected_actions, which apply_inbox_actions refers to
+  const visibleActions = (item.detected_actions ?? [])
+    .map((action, index) => ({ action, index }))
+    .filter(({ action, index }) => action.data.type !== 'none' && !dismissedActions.includes(index));
+  const unappliedIndices = visibleActions.filter(({ action }) => !action.confirmed).map(({ index }) => index);
+
   const formatTime = (dateStr: string) => {
     const date = new Date(dateStr);
     return date.toLocaleTimeString("en-US", {
@@ -103,17 +151,63 @@ function InboxItemCard({
     }
   };
 
+  // Shown while pending, and after answering for follow-ups still to confirm
+  const detectedActions = (
+    <div className="mb-4 space-y-2">
+      <div className="flex items-center justify-between">
+        <span className="text-xs font-mono text-text-muted">
+          AI 建議操作：
+        </span>
+        {unappliedIndices.length > 1 && (
+          <button
+            onClick={() => onApplyActions(item.id, unappliedIndices)}
+            disabled={executingAction}
+            className="text-xs font-mono text-accent-cyan hover:opacity-80 transition-opacity disabled:opacity-50"
+          >
+            全部確認 ({unappliedIndices.length})
+          </button>
+        )}
+      </div>
+      <AnimatePresence>
+        {visibleActions.map(({ action, index }) => (
+          <InboxActionSuggestion
+            key={index}
+            action={action}
+            onExecute={() => onApplyActions(item.id, [index])}
+            onDismiss={() => onDismissAction(item.id, index)}
+            executing={executingAction}
+          />
+        ))}
+      </AnimatePresence>
+    </div>
+  );
+
+  // Typed text is answered as written; a chosen suggestion runs its action
   const handleSubmit = () => {
-    const answer = inputValue || selectedAction || "";
-    if (answer) {
-      onAnswer(item.id, answer);
-      setInputValue("");
-      setSelectedAction(null);
+    const chosen = item.suggested_actions.find((a) => a.id === selectedAction);
+    if (inputValue) {
+      onAnswer(item.id, inputValue);
+    } else if (chosen) {
+      onAnswer(item.id, chosen.label, chosen.id);
+    } else if (selectedAction) {
+      onAnswer(item.id, selectedAction);
+    } else {
+      return;
     }
+    setInputValue("");
+    setSelectedAction(null);
   };
 
   const handleSkip = () => {
-    onAnswer(item.id, "skipped");
+    onSkip(item.id);
+  };
+
+  // Back in the pending list tomorrow morning
+  const handleSnooze = () => {
+    const until = new Date();
+    until.setDate(until.getDate() + 1);
+    until.setHours(9, 0, 0, 0);
+    onSnooze(item.id, until);
   };
 
   return (
@@ -128,6 +222,11 @@ function InboxItemCard({
           <span className="font-display text-xs text-accent-cyan tracking-wider">
             {getTypeLabel(item.item_type)}
           </span>
+          {item.priority === "high" && (
+            <span className="text-xs font-mono text-accent-rose bg-accent-rose/10 px-1.5 py-0.5 rounded">
+              HIGH
+            </span>
+          )}
           {project && (
             <>
               <span className="text-text-muted">·</span>
@@ -146,6 +245,14 @@ function InboxItemCard({
               Answered
             </span>
           )}
+          {item.status === "snoozed" && item.snoozed_until && (
+            <span className="bg-accent-amber/20 text-accent-amber px-2 py-0.5 rounded">
+              Snoozed until {new Date(item.snoozed_until).toLocaleString()}
+            </span>
+          )}
+          {item.status === "expired" && (
+            <span className="bg-bg-elevated px-2 py-0.5 rounded">Expired</span>
+          )}
         </div>
       </div>
 
@@ -174,6 +281,8 @@ function InboxItemCard({
         </div>
       )}
 
+      {item.status === "answered" && unappliedIndices.length > 0 && detectedActions}
+
       {/* Input and Actions (if pending) */}
       {item.status === "pending" && (
         <>
@@ -240,28 +349,17 @@ function InboxItemCard({
             </div>
           )}
 
-          {/* AI Detected Actions - executable todo suggestions */}
-          {item.detected_actions && item.detected_actions.length > 0 && (
-            <div className="mb-4 space-y-2">
-              <span className="text-xs font-mono text-text-muted">
-                AI 建議操作：
-              </span>
-              <AnimatePresence>
-                {item.detected_actions.map((action, index) => (
-                  <InboxActionSuggestion
-                    key={index}
-                    action={action}
-                    onExecute={() => onExecuteAction(item.id, action, item.project_id)}
-                    onDismiss={() => onDismissAction(item.id, index)}
-                    executing={executingAction}
-                  />
-                ))}
-              </AnimatePresence>
-            </div>
-          )}
+          {/* AI Detected Actions - executable suggestions */}
+          {visibleActions.length > 0 && detectedActions}
 
           {/* Action buttons */}
           <div className="flex items-center justify-end gap-2">
+            <button
+              onClick={handleSnooze}
+              className="px-4 py-2 text-sm text-text-secondary hover:text-text-primary transition-colors"
+            >
+              Snooze
+            </button>
             <button
               onClick={handleSkip}
               className="px-4 py-2 text-sm text-text-secondary hover:text-text-primary transition-colors"
@@ -293,9 +391,12 @@ export function Inbox() {
     filteredItems,
     fetchItems,
     answerItem,
+    skipItem,
+    snoozeItem,
     setFilter,
-    executeAction,
+    applyActions,
     dismissAction,
+    dismissedActions,
   } = useInboxStore();
 
   const { selectedProjectId, fetchProjects } = useProjectStore();
@@ -322,8 +423,8 @@ export function Inbox() {
     [projectFilteredItems]
   );
 
-  const handleAnswer = async (id: string, answer: string) => {
-    await answerItem(id, answer);
+  const handleAnswer = async (id: string, answer: string, actionId?: string) => {
+    await answerItem(id, answer, actionId);
   };
 
   const handleFilterChange = (newFilter: "all" | "pending" | "answered") => {
@@ -470,8 +571,11 @@ export function Inbox() {
                       key={item.id}
                       item={item}
                       onAnswer={handleAnswer}
-                      onExecuteAction={executeAction}
+                      onSkip={skipItem}
+                      onSnooze={snoozeItem}
+                      onApplyActions={applyActions}
                       onDismissAction={dismissAction}
+                      dismissedActions={dismissedActions[item.id] ?? []}
                       executingAction={executingAction}
                     />
                   ))}
@@ -1,15 +1,18 @@
 import { useState, useEffect, useCallback, DragEvent } from "react";
 import { motion } from "framer-motion";
-import { Key, FolderOpen, Bell, Eye, EyeOff, Plus, X, Check, Loader2, Upload, Timer, Bot, Play, Info, RefreshCw, Sparkles } from "lucide-react";
+import { Key, FolderOpen, Bell, Eye, EyeOff, Plus, X, Check, Loader2, Upload, Timer, Bot, Play, Info, RefreshCw, Sparkles, History } from "lucide-react";
 import { open } from "@tauri-apps/plugin-dialog";
 import { getVersion } from "@tauri-apps/api/app";
+import { listen } from "@tauri-apps/api/event";
 import { useProjectStore } from "../stores/projectStore";
 import { useSettingsStore } from "../stores/settingsStore";
 import { useUpdateStore } from "../stores/updateStore";
 import { useToast } from "../components/Toast";
 import { SkeletonProjectItem } from "../components/Skeleton";
 import { WhatsNew } from "../components/WhatsNew";
-import type { ProjectStatus } from "../lib/types";
+import { BackfillControl } from "../components/BackfillControl";
+import { backfillApi } from "../lib/api";
+import type { BackfillJob, ProjectStatus } from "../lib/types";
 
 export function Settings() {
   const [apiKey, setApiKey] = useState("");
@@ -20,6 +23,8 @@ export function Settings() {
   const [isDragOver, setIsDragOver] = useState(false);
   const [showWhatsNew, setShowWhatsNew] = useState(false);
   const [currentVersion, setCurrentVersion] = useState("");
+  const [backfillJobs, setBackfillJobs] = useState<Record<string, BackfillJob>>({});
+  const [backfillFor, setBackfillFor] = useState<string | null>(null);
 
   const toast = useToast();
   const { checking, update, checkForUpdates } = useUpdateStore();
@@ -44,6 +49,7 @@ export function Settings() {
     saveSettings,
     updateNotifications,
     updateScan,
+    updateLanguage,
     setApiKey: saveApiKey,
     checkApiKey,
     fetchSchedulerStatus,
@@ -57,6 +63,19 @@ export function Settings() {
     fetchSchedulerStatus();
   }, [fetchProjects, fetchSettings, checkApiKey, fetchSchedulerStatus]);
 
+  useEffect(() => {
+    backfillApi.getAll()
+      .then((jobs) => setBackfillJobs(Object.fromEntries(jobs.map((job) => [job.project_id, job]))))
+      .catch((err) => console.error("Failed to fetch backfill jobs:", err));
+
+    const unlisten = listen<BackfillJob>("backfill:progress", (event) => {
+      setBackfillJobs((jobs) => ({ ...jobs, [event.payload.project_id]: event.payload }));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   useEffect(() => {
     getVersion().then(setCurrentVersion).catch(() => setCurrentVersion("0.2.0"));
   }, []);
@@ -90,7 +109,11 @@ export function Settings() {
   const handleAddProject = async () => {
     if (newProjectName.trim() && newProjectPath.trim()) {
       try {
-        await createProject(newProjectName, newProjectPath);
+        const project = await createProject(newProjectName, newProjectPath);
+        // Offer to fill in the new project's history
+        if (project) {
+          setBackfillFor(project.id);
+        }
         setNewProjectName("");
         setNewProjectPath("");
         setShowAddProject(false);
@@ -194,10 +217,10 @@ export function Settings() {
     updateScan({ [key]: value });
   };
 
-  const handleManualScan = async () => {
+  const handleManualScan = async (forceRefresh = false) => {
     try {
-      await triggerManualScan();
-      toast.success("Manual scan triggered");
+      await triggerManualScan(forceRefresh);
+      toast.success(forceRefresh ? "Re-analyzing today's work" : "Manual scan triggered");
     } catch {
       toast.error("Manual scan failed");
     }
@@ -401,40 +424,57 @@ export function Settings() {
             {projects.map((project) => (
               <div
                 key={project.id}
-                className="flex items-center justify-between p-3 bg-bg-elevated rounded hover:bg-bg-elevated/80 transition-colors"
+                className="p-3 bg-bg-elevated rounded hover:bg-bg-elevated/80 transition-colors"
               >
-                <div className="flex items-center gap-3">
-                  <span
-                    className={`w-2 h-2 rounded-full ${
-                      project.status === "active"
-                        ? "bg-accent-green"
-                        : project.status === "paused"
-                        ? "bg-accent-amber"
-                        : "bg-text-muted"
-                    }`}
-                  />
-                  <div>
-                    <p className="font-display text-text-primary text-sm">{project.name}</p>
-                    <p className="text-text-muted text-xs font-mono">{project.path}</p>
+                <div className="flex items-center justify-between">
+                  <div className="flex items-center gap-3">
+                    <span
+                      className={`w-2 h-2 rounded-full ${
+                        project.status === "active"
+                          ? "bg-accent-green"
+                          : project.status === "paused"
+                          ? "bg-accent-amber"
+                          : "bg-text-muted"
+                      }`}
+                    />
+                    <div>
+                      <p className="font-display text-text-primary text-sm">{project.name}</p>
+                      <p className="text-text-muted text-xs font-mono">{project.path}</p>
+                    </div>
+                  </div>
+                  <div className="flex items-center gap-2">
+                    <button
+                      onClick={() => setBackfillFor(backfillFor === project.id ? null : project.id)}
+                      title="Backfill history"
+                      className="text-text-muted hover:text-accent-cyan transition-colors"
+                    >
+                      <History size={16} />
+                    </button>
+                    <select
+                      value={project.status}
+                      onChange={(e) => handleStatusChange(project.id, e.target.value)}
+                      className="bg-bg-primary text-text-secondary text-xs border border-border-subtle rounded px-2 py-1"
+                    >
+                      <option value="active">Active</option>
+                      <option value="paused">Paused</option>
+                      <option value="archived">Archived</option>
+                    </select>
+                    <button
+                      onClick={() => handleDeleteProject(project.id)}
+                      className="text-text-muted hover:text-accent-rose transition-colors"
+                    >
+                      <X size={16} />
+                    </button>
                   </div>
                 </div>
-                <div className="flex items-center gap-2">
-                  <select
-                    value={project.status}
-                    onChange={(e) => handleStatusChange(project.id, e.target.value)}
-                    className="bg-bg-primary text-text-secondary text-xs border border-border-subtle rounded px-2 py-1"
-                  >
-                    <option value="active">Active</option>
-                    <option value="paused">Paused</option>
-                    <option value="archived">Archived</option>
-                  </select>
-                  <button
-                    onClick={() => handleDeleteProject(project.id)}
-                    className="text-text-muted hover:text-accent-rose transition-colors"
-                  >
-                    <X size={16} />
-                  </button>
-                </div>
+                {(backfillFor === project.id
+                  || ["running", "paused", "failed"].includes(backfillJobs[project.id]?.status ?? "")) && (
+                  <BackfillControl
+                    projectId={project.id}
+                    job={backfillJobs[project.id]}
+                    onClose={() => setBackfillFor(null)}
+                  />
+                )}
               </div>
             ))}
           </div>
@@ -454,6 +494,23 @@ export function Settings() {
         </div>
 
         <div className="space-y-4">
+          <div className="flex items-center justify-between">
+            <div>
+              <p className="text-text-primary text-sm">Language</p>
+              <p className="text-text-muted text-xs">Used for notifications, inbox items and AI replies</p>
+            </div>
+            <select
+              value={settings.language}
+              onChange={(e) => updateLanguage(e.target.value)}
+              className="bg-bg-primary text-text-secondary text-sm border border-border-subtle rounded px-3 py-1.5"
+            >
+              <option value="zh-HK">廣東話</option>
+              <option value="zh-TW">繁體中文</option>
+              <option value="en">English</option>
+              <option value="ja">日本語</option>
+            </select>
+          </div>
+
           {[
             { key: "daily_summary" as const, label: "Daily Summary", description: "Daily reminder to summarize your work" },
             { key: "todo_reminder" as const, label: "TODO Reminder", description: "Notify when TODOs are overdue" },
@@ -507,14 +564,25 @@ export function Settings() {
               </span>
             )}
           </div>
-          <button
-            onClick={handleManualScan}
-            disabled={settingsLoading}
-            className="flex items-center gap-1.5 px-3 py-1.5 text-sm text-accent-cyan hover:bg-accent-cyan/10 rounded transition-colors"
-          >
-            {settingsLoading ? <Loader2 size={14} className="animate-spin" /> : <Play size={14} />}
-            Manual Scan
-          </button>
+          <div className="flex items-center gap-1">
+            <button
+              onClick={() => handleManualScan(true)}
+              disabled={settingsLoading}
+              title="Scan and re-run AI analysis, ignoring cached results"
+              className="flex items-center gap-1.5 px-3 py-1.5 text-sm text-text-secondary hover:bg-bg-elevated rounded transition-colors"
+            >
+              <RefreshCw size={14} />
+              Force Refresh
+            </button>
+            <button
+              onClick={() => handleManualScan()}
+              disabled={settingsLoading}
+              className="flex items-center gap-1.5 px-3 py-1.5 text-sm text-accent-cyan hover:bg-accent-cyan/10 rounded transition-colors"
+            >
+              {settingsLoading ? <Loader2 size={14} className="animate-spin" /> : <Play size={14} />}
+              Manual Scan
+            </button>
+          </div>
         </div>
 
         {schedulerStatus?.last_scan && (
@@ -523,6 +591,26 @@ export function Settings() {
           </p>
         )}
 
+        {schedulerStatus?.jobs && schedulerStatus.jobs.length > 0 && (
+          <div className="mb-4 space-y-1">
+            {schedulerStatus.jobs.map((job) => (
+              <div key={job.name} className="flex items-center justify-between text-xs font-mono">
+                <span className={job.enabled ? "text-text-secondary" : "text-text-muted line-through"}>
+                  {job.name}
+                  <span className="text-text-muted ml-2">{job.schedule}</span>
+                </span>
+                <span className={job.last_error ? "text-accent-rose" : "text-text-muted"} title={job.last_error ?? undefined}>
+                  {job.running
+                    ? "running"
+                    : job.next_run
+                      ? `next ${new Date(job.next_run).toLocaleString('en-US')}`
+                      : "—"}
+                </span>
+              </div>
+            ))}
+          </div>
+        )}
+
         <div className="space-y-4">
           {/* Enable Auto Scan Toggle */}
           <div className="flex items-center justify-between">
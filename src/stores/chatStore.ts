@@ -1,18 +1,31 @@
 import { create } from 'zustand';
-import { chatApi, grokApi } from '../lib/api';
-import type { ChatMessage, DetectedAction, AiResponseWithActions } from '../lib/types';
+import { listen } from '@tauri-apps/api/event';
+import { aiErrorKind, chatApi, errorMessage, grokApi } from '../lib/api';
+import type { AgentStep, AgentStepEvent, AiErrorKind, ChatDelta, ChatMessage, DetectedAction } from '../lib/types';
+
+const AI_ERROR_MESSAGES: Partial<Record<AiErrorKind, string>> = {
+  auth: 'your API key was rejected. Please check it in Settings.',
+  quota: 'the AI rate limit or quota has been reached. Please try again later.',
+  network: 'AI is temporarily unavailable.',
+};
 
 interface ChatState {
   messages: ChatMessage[];
   pendingActions: DetectedAction[];
   loading: boolean;
   sending: boolean;
+  /** Tool calls the agent has made so far for the reply being worked on */
+  agentSteps: AgentStep[];
+  /** Reply text received so far while a response streams in */
+  streamingContent: string;
+  streamRequestId: string | null;
   executingAction: boolean;
   error: string | null;
 
   // Actions
   fetchMessages: (projectId?: string, limit?: number) => Promise<void>;
   sendMessage: (content: string, projectId?: string) => Promise<void>;
+  cancelStream: () => Promise<void>;
   executeAction: (action: DetectedAction, projectId?: string) => Promise<void>;
   dismissAction: (actionIndex: number) => void;
   clearMessages: () => void;
@@ -24,6 +37,9 @@ export const useChatStore = create<ChatState>((set, get) => ({
   pendingActions: [],
   loading: false,
   sending: false,
+  agentSteps: [],
+  streamingContent: '',
+  streamRequestId: null,
   executingAction: false,
   error: null,
 
@@ -55,71 +71,97 @@ export const useChatStore = create<ChatState>((set, get) => ({
         messages: [...state.messages, userMessage],
       }));
 
-      // Get AI response with intent detection
+      // Answer with the tool-calling agent; the backend saves the reply when done
+      const requestId = crypto.randomUUID();
+      set({ streamRequestId: requestId, agentSteps: [], streamingContent: '' });
+      const unlistenSteps = await listen<AgentStepEvent>('chat:agent-step', (event) => {
+        if (event.payload.request_id === requestId) {
+          // Text before a tool call was the agent thinking aloud, not the answer
+          set((state) => ({ agentSteps: [...state.agentSteps, event.payload.step], streamingContent: '' }));
+        }
+      });
+      const unlistenDeltas = await listen<ChatDelta>('chat:delta', (event) => {
+        if (event.payload.request_id === get().streamRequestId) {
+          set((state) => ({ streamingContent: state.streamingContent + event.payload.delta }));
+        }
+      });
+
       try {
-        const aiResponse: AiResponseWithActions = await grokApi.chatWithIntent(
+        // The backend describes the project and retrieves what the question is about
+        const result = await grokApi.chatAgent(
+          requestId,
           content,
           history,
-          projectId,
+          undefined,
           projectId
         );
 
-        // Save AI response to database
-        const assistantMessage = await chatApi.createMessage(
-          'assistant',
-          aiResponse.message,
-          projectId
-        );
-
-        // Filter actions with confidence > 0.5 and not 'none' type
-        const meaningfulActions = aiResponse.detected_actions.filter(
-          (a) => a.confidence > 0.5 && a.data.type !== 'none'
-        );
-
+        // Changes the agent proposed wait here for the user to confirm
         set((state) => ({
-          messages: [...state.messages, assistantMessage],
-          pendingActions: meaningfulActions,
+          messages: [...state.messages, result.message],
+          pendingActions: result.detected_actions,
           sending: false,
+          agentSteps: [],
+          streamingContent: '',
+          streamRequestId: null,
         }));
       } catch (aiError) {
-        // Fallback to simple chat if intent detection fails
+        set({ agentSteps: [], streamingContent: '', streamRequestId: null });
+
+        // Fall back to the streamed chat with intent detection if the agent fails, e.g. on a
+        // model without tool calls; it won't get past a bad key, an exhausted quota or an outage either
+        const kind = aiErrorKind(aiError);
         try {
-          const fallbackResponse = await grokApi.chatWithHistory(
-            content,
-            history,
-            projectId,
-            4000
-          );
+          if (kind === 'auth' || kind === 'quota' || kind === 'network') throw aiError;
 
-          const assistantMessage = await chatApi.createMessage(
-            'assistant',
-            fallbackResponse,
-            projectId
-          );
+          const fallbackId = crypto.randomUUID();
+          set({ streamRequestId: fallbackId });
+          const result = await grokApi.chatStream(fallbackId, content, history, undefined, projectId);
 
+          const meaningfulActions = result.detected_actions.filter(
+            (a) => a.confidence > 0.5 && a.data.type !== 'none'
+          );
           set((state) => ({
-            messages: [...state.messages, assistantMessage],
+            messages: [...state.messages, result.message],
+            pendingActions: meaningfulActions,
             sending: false,
+            streamingContent: '',
+            streamRequestId: null,
           }));
         } catch (fallbackError) {
+          set({ streamingContent: '', streamRequestId: null });
           const errorMessage = await chatApi.createMessage(
             'assistant',
-            `Sorry, AI is temporarily unavailable. Error: ${String(aiError)}`,
+            `Sorry, ${AI_ERROR_MESSAGES[kind] ?? 'AI is temporarily unavailable.'} Error: ${errorMessage(aiError)}`,
             projectId
           );
 
           set((state) => ({
             messages: [...state.messages, errorMessage],
             sending: false,
-            error: String(aiError),
+            error: errorMessage(aiError),
           }));
         }
+      } finally {
+        unlistenSteps();
+        unlistenDeltas();
       }
     } catch (error) {
       set({ error: String(error), sending: false });
     }
   },
 
+  cancelStream: async () => {
+    const requestId = get().streamRequestId;
+    if (!requestId) return;
+    try {
+      // The partial reply is still saved and returned by the pending sendMessage
+      await grokApi.cancelChatStream(requestId);
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
+
   executeAction: async (action: DetectedAction, projectId?: string) => {
     set({ executingAction: true });
     try {
@@ -136,6 +178,24 @@ export const useChatStore = create<ChatState>((set, get) => ({
       } else if (result.type === 'inbox_created') {
         const data = result.data as { question: string };
         confirmationText = `✅ 已添加跟進事項：「${data.question}」`;
+      } else if (result.type === 'milestone_created') {
+        const data = result.data as { title: string };
+        confirmationText = `✅ 已創建里程碑：「${data.title}」`;
+      } else if (result.type === 'todo_updated') {
+        const data = result.data as { title: string };
+        confirmationText = `✅ 已更新待辦事項：「${data.title}」`;
+      } else if (result.type === 'todo_completed') {
+        const data = result.data as { title: string };
+        confirmationText = `✅ 已完成待辦事項：「${data.title}」`;
+      } else if (result.type === 'todo_deleted') {
+        const data = result.data as { title: string };
+        confirmationText = `🗑️ 已刪除待辦事項：「${data.title}」`;
+      } else if (result.type === 'milestone_updated') {
+        const data = result.data as { title: string };
+        confirmationText = `✅ 已更新里程碑：「${data.title}」`;
+      } else if (result.type === 'project_updated') {
+        const data = result.data as { name: string; status: string };
+        confirmationText = `✅ 已將項目「${data.name}」設為 ${data.status}`;
       }
 
       if (confirmationText) {
@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { dailyLogApi, scannerApi } from '../lib/api';
-import type { DailyLog, Project } from '../lib/types';
+import type { DailyLog, LogCategory, Project } from '../lib/types';
 
 interface DailyLogState {
   logs: DailyLog[];
@@ -16,6 +16,7 @@ interface DailyLogState {
     summary: string,
     category: string
   ) => Promise<DailyLog | null>;
+  editLog: (logId: string, summary?: string, category?: LogCategory) => Promise<DailyLog | null>;
   scanProject: (project: Project) => Promise<{ additions: number; deletions: number } | null>;
 }
 
@@ -53,6 +54,19 @@ export const useDailyLogStore = create<DailyLogState>((set) => ({
     }
   },
 
+  editLog: async (logId: string, summary?: string, category?: LogCategory) => {
+    try {
+      const log = await dailyLogApi.edit(logId, summary, category);
+      set((state) => ({
+        logs: state.logs.map((l) => (l.id === log.id ? log : l)),
+      }));
+      return log;
+    } catch (error) {
+      set({ error: String(error) });
+      return null;
+    }
+  },
+
   scanProject: async (project: Project) => {
     set({ scanning: true, error: null });
     try {
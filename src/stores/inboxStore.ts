@@ -1,6 +1,6 @@
 import { create } from 'zustand';
-import { inboxApi, grokApi } from '../lib/api';
-import type { InboxItem, InboxStatus, DetectedAction } from '../lib/types';
+import { inboxApi } from '../lib/api';
+import type { InboxItem, InboxStatus, InboxPriority } from '../lib/types';
 
 interface InboxState {
   items: InboxItem[];
@@ -8,6 +8,7 @@ interface InboxState {
   error: string | null;
   filter: 'all' | 'pending' | 'answered';
   executingAction: boolean;
+  dismissedActions: Record<string, number[]>; // action indices hidden per item, kept stable for apply
 
   // Computed
   pendingCount: () => number;
@@ -16,7 +17,10 @@ interface InboxState {
 
   // Actions
   fetchItems: (status?: InboxStatus) => Promise<void>;
-  answerItem: (id: string, answer: string) => Promise<void>;
+  answerItem: (id: string, answer: string, actionId?: string) => Promise<void>;
+  skipItem: (id: string) => Promise<void>;
+  snoozeItem: (id: string, until: Date) => Promise<void>;
+  setPriority: (id: string, priority: InboxPriority) => Promise<void>;
   createItem: (
     itemType: string,
     question: string,
@@ -24,7 +28,7 @@ interface InboxState {
     context?: string
   ) => Promise<InboxItem | null>;
   setFilter: (filter: 'all' | 'pending' | 'answered') => void;
-  executeAction: (itemId: string, action: DetectedAction, projectId?: string) => Promise<void>;
+  applyActions: (itemId: string, actionIndices: number[]) => Promise<void>;
   dismissAction: (itemId: string, actionIndex: number) => void;
 }
 
@@ -34,6 +38,7 @@ export const useInboxStore = create<InboxState>((set, get) => ({
   error: null,
   filter: 'all',
   executingAction: false,
+  dismissedActions: {},
 
   pendingCount: () => get().items.filter((i) => i.status === 'pending').length,
   answeredCount: () => get().items.filter((i) => i.status === 'answered').length,
@@ -55,9 +60,14 @@ export const useInboxStore = create<InboxState>((set, get) => ({
     }
   },
 
-  answerItem: async (id: string, answer: string) => {
+  answerItem: async (id: string, answer: string, actionId?: string) => {
     try {
-      await inboxApi.answer(id, answer);
+      const outcome = await inboxApi.answer(id, answer, actionId);
+      if (actionId) {
+        // The chosen action may have closed the item differently or applied its actions
+        await get().fetchItems();
+        return;
+      }
       set((state) => ({
         items: state.items.map((item) =>
           item.id === id
@@ -66,6 +76,7 @@ export const useInboxStore = create<InboxState>((set, get) => ({
                 status: 'answered' as const,
                 answer,
                 answered_at: new Date().toISOString(),
+                detected_actions: [...item.detected_actions, ...outcome.follow_ups],
               }
             : item
         ),
@@ -75,6 +86,44 @@ export const useInboxStore = create<InboxState>((set, get) => ({
     }
   },
 
+  skipItem: async (id: string) => {
+    try {
+      await inboxApi.skip(id);
+      set((state) => ({
+        items: state.items.map((item) =>
+          item.id === id ? { ...item, status: 'skipped' as const, snoozed_until: undefined } : item
+        ),
+      }));
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
+
+  snoozeItem: async (id: string, until: Date) => {
+    try {
+      const snoozedUntil = until.toISOString();
+      await inboxApi.snooze(id, snoozedUntil);
+      set((state) => ({
+        items: state.items.map((item) =>
+          item.id === id ? { ...item, status: 'snoozed' as const, snoozed_until: snoozedUntil } : item
+        ),
+      }));
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
+
+  setPriority: async (id: string, priority: InboxPriority) => {
+    try {
+      await inboxApi.setPriority(id, priority);
+      set((state) => ({
+        items: state.items.map((item) => (item.id === id ? { ...item, priority } : item)),
+      }));
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
+
   createItem: async (
     itemType: string,
     question: string,
@@ -97,20 +146,25 @@ export const useInboxStore = create<InboxState>((set, get) => ({
     set({ filter });
   },
 
-  executeAction: async (itemId: string, action: DetectedAction, projectId?: string) => {
+  applyActions: async (itemId: string, actionIndices: number[]) => {
     set({ executingAction: true });
     try {
-      await grokApi.executeAction(action, projectId);
-      // Mark the action as confirmed and remove from detected_actions
+      const results = await inboxApi.applyActions(itemId, actionIndices);
+      const applied = new Set(results.filter((r) => 'result' in r).map((r) => r.index));
+      const errors = results.flatMap((r) => ('error' in r ? [r.error] : []));
+      // Applied actions stay on the item, marked confirmed
       set((state) => ({
         items: state.items.map((item) =>
           item.id === itemId
             ? {
                 ...item,
-                detected_actions: item.detected_actions.filter((a) => a !== action),
+                detected_actions: item.detected_actions.map((a, i) =>
+                  applied.has(i) ? { ...a, confirmed: true } : a
+                ),
               }
             : item
         ),
+        error: errors.length > 0 ? errors.join('\n') : null,
         executingAction: false,
       }));
     } catch (error) {
@@ -120,14 +174,10 @@ export const useInboxStore = create<InboxState>((set, get) => ({
 
   dismissAction: (itemId: string, actionIndex: number) => {
     set((state) => ({
-      items: state.items.map((item) =>
-        item.id === itemId
-          ? {
-              ...item,
-              detected_actions: item.detected_actions.filter((_, i) => i !== actionIndex),
-            }
-          : item
-      ),
+      dismissedActions: {
+        ...state.dismissedActions,
+        [itemId]: [...(state.dismissedActions[itemId] ?? []), actionIndex],
+      },
     }));
   },
 }));
@@ -15,6 +15,7 @@ interface SettingsState {
   saveSettings: (settings: UserSettings) => Promise<void>;
   updateNotifications: (notifications: Partial<NotificationSettings>) => void;
   updateScan: (scan: Partial<ScanSettings>) => void;
+  updateLanguage: (language: string) => void;
   setApiKey: (key: string) => Promise<boolean>;
   checkApiKey: () => Promise<void>;
   deleteApiKey: () => Promise<void>;
@@ -24,7 +25,7 @@ interface SettingsState {
   fetchSchedulerStatus: () => Promise<void>;
   startScheduler: () => Promise<void>;
   stopScheduler: () => Promise<void>;
-  triggerManualScan: () => Promise<void>;
+  triggerManualScan: (forceRefresh?: boolean) => Promise<void>;
 }
 
 const defaultSettings: UserSettings = {
@@ -32,6 +33,7 @@ const defaultSettings: UserSettings = {
     daily_summary: true,
     todo_reminder: true,
     stale_project: false,
+    stale_project_days: 30,
   },
   scan: {
     enabled: true,
@@ -50,9 +52,60 @@ const defaultSettings: UserSettings = {
       files_changed: 20,
     },
     ai_create_mode: 'suggest',
+    auto_apply_types: ['todo'],
+    auto_apply_confidence: 0.8,
+  },
+  anomaly: {
+    enabled: true,
+    use_ai_phrasing: false,
+    baseline_weeks: 8,
+    low_activity_ratio: 0.25,
+    deletion_ratio: 3.0,
+    min_deleted_lines: 300,
+    late_night_start_hour: 0,
+    late_night_end_hour: 5,
+    late_night_min_commits: 3,
+    category_switch_window: 7,
+    lockfile_churn_lines: 2000,
+    binary_files: 10,
+  },
+  review: {
+    weekly_review: true,
+  },
+  insights: {
+    enabled: true,
+    lookback_days: 180,
+    min_samples: 30,
+    abandon_gap_days: 21,
+    repeat_days: 90,
+  },
+  scheduler: {
+    jobs: {},
+    backup_keep: 7,
+  },
+  ai: {
+    request_timeout_secs: 60,
+    max_retries: 3,
+    max_concurrent_requests: 2,
+    circuit_failure_threshold: 5,
+    circuit_cooldown_secs: 300,
+    prompt_price_per_million: 0.2,
+    completion_price_per_million: 0.5,
+    monthly_budget_usd: 0,
+    cache_ttl_days: 30,
+    cache_max_entries: 2000,
+    agent_max_steps: 6,
+    interpret_answers: true,
+  },
+  embeddings: {
+    enabled: false,
+    endpoint: 'http://localhost:11434/v1/embeddings',
+    model: 'nomic-embed-text',
+    batch_size: 32,
+    commit_days: 90,
   },
   theme: 'dark',
-  language: 'en',
+  language: 'zh-HK',
 };
 
 export const useSettingsStore = create<SettingsState>((set, get) => ({
@@ -80,17 +133,9 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
       set({ settings, loading: false, saved: true });
       setTimeout(() => set({ saved: false }), 2000);
 
-      // Restart scheduler with new settings if scan settings changed
-      const { schedulerStatus } = get();
-      if (settings.scan.enabled) {
-        // Always restart with new settings to apply changes
-        await schedulerApi.start(settings.scan);
-        await get().fetchSchedulerStatus();
-      } else if (schedulerStatus?.is_running) {
-        // Stop scheduler if disabled
-        await schedulerApi.stop();
-        await get().fetchSchedulerStatus();
-      }
+      // The scheduler re-reads settings on every tick; just make sure it is running
+      await schedulerApi.start();
+      await get().fetchSchedulerStatus();
     } catch (error) {
       set({ error: String(error), loading: false });
     }
@@ -120,6 +165,15 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     }));
   },
 
+  updateLanguage: (language: string) => {
+    set((state) => ({
+      settings: {
+        ...state.settings,
+        language,
+      },
+    }));
+  },
+
   setApiKey: async (key: string) => {
     set({ loading: true, error: null });
     try {
@@ -164,8 +218,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
 
   startScheduler: async () => {
     try {
-      const { settings } = get();
-      await schedulerApi.start(settings.scan);
+      await schedulerApi.start();
       await get().fetchSchedulerStatus();
     } catch (error) {
       set({ error: String(error) });
@@ -181,10 +234,10 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
     }
   },
 
-  triggerManualScan: async () => {
+  triggerManualScan: async (forceRefresh?: boolean) => {
     set({ loading: true });
     try {
-      await schedulerApi.triggerManualScan();
+      await schedulerApi.triggerManualScan(forceRefresh);
       set({ loading: false });
     } catch (error) {
       set({ error: String(error), loading: false });
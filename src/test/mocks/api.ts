@@ -33,6 +33,7 @@ export const createMockInboxItem = (overrides: Partial<InboxItem> = {}): InboxIt
   ],
   detected_actions: [],
   status: 'pending',
+  priority: 'normal',
   created_at: '2025-01-01T00:00:00Z',
   ...overrides,
 })